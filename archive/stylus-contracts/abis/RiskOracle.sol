@@ -7,6 +7,52 @@
 pragma solidity ^0.8.24;
 
 interface IRiskOracle  {
+    error Unauthorized();
+
+    error AlreadyInitialized();
+
+    error InvalidAddress();
+
+    error InvalidParameter();
+
+    error MemberNotEligible(address member);
+
+    error InvalidTierId();
+
+    error TierOrderViolation(uint256 tier_id);
+
+    error EmptyMemberList();
+
+    error NoTiersConfigured();
+
+    error AguayoCallFailed(address member);
+
+    error RecalibrationMismatch();
+
+    error InvalidProviderId();
+
+    error NoProvidersConfigured();
+
+    error QuoteNotFound(uint256 quote_id);
+
+    error QuoteExpired(uint256 quote_id, uint256 expiry);
+
+    error QuoteAlreadyUsed(uint256 quote_id);
+
+    error QuoteMembersMismatch(uint256 quote_id);
+
+    error MemberNotDenylisted(address member);
+
+    error QuoteNotConsumed(uint256 quote_id);
+
+    error OutcomeAlreadyRecorded(uint256 quote_id);
+
+    error BatchTooLarge(uint256 member_count, uint256 max_members);
+
+    error ParameterStepExceeded(uint8 parameter, uint256 current, uint256 requested, uint256 max_step);
+
+    error ParameterChangeTooSoon(uint8 parameter, uint256 next_change_at);
+
     function initialize(address aguayo_sbt_address) external;
 
     function areAllMembersEligible(address[] memory members) external view returns (bool);
@@ -15,12 +61,70 @@ interface IRiskOracle  {
 
     function getWeightedProbabilities(address[] memory members) external view returns (uint256[] memory);
 
+    function previewDrawOdds(address[] memory members) external view returns (address,uint256,uint256)[] memory;
+
     function isMemberEligible(address member, bool is_credit_mode) external view returns (bool);
 
+    function evaluateGroup(address[] memory members) external view returns (bool, uint256[] memory, uint256, uint256, uint256);
+
+    function evaluateGroups(address[][] memory groups) external view returns (bool,uint256,uint256)[] memory;
+
+    function getRiskReport(address[] memory members) external view returns (RiskReport);
+
+    function getMemberScore(address member) external view returns (uint256);
+
+    function getMemberReputation(address member) external view returns (uint256, uint256);
+
+    function recordCircleOutcome(address[] memory members, uint256 quote_id, address[] memory defaulted_members, uint256 loss_bps) external;
+
+    function getTierOutcomes(uint256 tier_id) external view returns (uint256, uint256, uint256, uint256, uint256);
+
+    function getTierKey(uint256 tier_id) external view returns (uint256);
+
+    function getLevelOutcomes(uint8 level) external view returns (uint256, uint256, uint256);
+
+    function suggestTierRates() external view returns (uint256[] memory);
+
+    function isOutcomeReporter(address reporter) external view returns (bool);
+
+    function getReputationProviderCount() external view returns (uint256);
+
+    function getReputationProvider(uint256 provider_id) external view returns (address, uint8, uint256, uint256);
+
+    function quote(address[] memory members, uint256 amount, uint256 duration_in_days) external returns (uint256);
+
+    function consumeQuote(uint256 quote_id, address[] memory members) external returns (uint256, uint256, uint256, uint256);
+
+    function getQuote(uint256 quote_id) external view returns (bytes32, uint256, uint256, uint256, uint256, uint256, bool);
+
+    function getMemberSetHash(address[] memory members) external view returns (bytes32);
+
+    function quoteValidity() external view returns (uint256);
+
+    function isQuoteConsumer(address consumer) external view returns (bool);
+
+    function drawWeightMode() external view returns (uint8);
+
+    function isDenylisted(address member) external view returns (bool);
+
+    function getDenylistEntry(address member) external view returns (bool, uint8, uint256, bool);
+
+    function getDenylistCount() external view returns (uint256);
+
+    function getDenylist(uint256 offset, uint256 limit) external view returns (address,uint8,uint256)[] memory;
+
+    function isRiskManager(address manager) external view returns (bool);
+
+    function denyMember(address member, uint8 reason, uint256 expiry) external;
+
+    function rehabilitateMember(address member) external;
+
     function getLeverageTierCount() external view returns (uint256);
 
     function getLeverageTier(uint256 tier_id) external view returns (uint8, uint256, uint256);
 
+    function getAllTiers() external view returns (uint8,uint256,uint256)[] memory;
+
     function owner() external view returns (address);
 
     function aguayoSbt() external view returns (address);
@@ -33,10 +137,22 @@ interface IRiskOracle  {
 
     function riskPremiumPerStainBps() external view returns (uint256);
 
+    function getScoreWeights() external view returns (uint256, uint256, uint256, uint256, uint256);
+
+    function getDispersionPenalty() external view returns (uint256, uint256);
+
+    function getParameterLimits(uint8 parameter) external view returns (uint256, uint256);
+
+    function getPendingParameterLimits(uint8 parameter) external view returns (uint256, uint256, uint256);
+
     function addLeverageTier(uint8 min_average_level, uint256 multiplier, uint256 interest_rate_bps) external;
 
     function updateLeverageTier(uint256 tier_id, uint8 min_average_level, uint256 multiplier, uint256 interest_rate_bps) external;
 
+    function removeLeverageTier(uint256 tier_id) external;
+
+    function replaceAllTiers((uint8,uint256,uint256)[] memory tiers) external;
+
     function setMinLevelForCredit(uint8 new_min_level) external;
 
     function setMaxLeverageMultiplier(uint256 new_max_multiplier) external;
@@ -45,11 +161,54 @@ interface IRiskOracle  {
 
     function setRiskPremium(uint256 new_premium_bps) external;
 
-    function transferOwnership(address new_owner) external;
+    function setScoreWeights(uint256 threads_weight, uint256 circles_weight, uint256 history_weight, uint256 stain_penalty_bps, uint256 decay_half_life) external;
+
+    function setParameterLimits(uint8 parameter, uint256 max_step, uint256 min_interval) external;
+
+    function setDispersionPenalty(uint256 variance_penalty_bps, uint256 new_member_penalty_bps) external;
+
+    function addReputationProvider(address provider, uint8 kind, uint256 weight, uint256 full_score_stake) external;
 
-    function onlyOwner() external view;
+    function updateReputationProvider(uint256 provider_id, uint256 weight, uint256 full_score_stake) external;
 
-    function getGroupStats(address[] memory members) external view returns (uint8, uint256);
+    function removeReputationProvider(uint256 provider_id) external;
+
+    function setRiskManager(address manager, bool authorized) external;
+
+    function setDrawWeightMode(uint8 mode) external;
+
+    function setQuoteConsumer(address consumer, bool authorized) external;
+
+    function setQuoteValidity(uint256 validity) external;
+
+    function setOutcomeReporter(address reporter, bool authorized) external;
+
+    function applyTierRecalibration(uint256[] memory expected_spreads) external;
+
+    function transferOwnership(address new_owner) external;
 
-    function getTierForAverageLevel(uint8 avg_level) external view returns (uint256, uint256);
+    struct RiskReport {
+        uint256 tierId;
+        uint256 averageLevelX100;
+        uint8 minLevel;
+        bool minLevelCapped;
+        uint256 levelVarianceX100;
+        uint256 newMemberShareBps;
+        uint256 groupScore;
+        uint8[] memberLevels;
+        bool[] memberStained;
+        uint256[] memberScores;
+        uint256[] memberConfidence;
+        uint256 tierMultiplier;
+        uint256 stainLeverageReduction;
+        uint256 dispersionPenaltyBps;
+        uint256 dispersionLeverageReduction;
+        bool leverageCapped;
+        uint256 multiplier;
+        uint256 baseRateBps;
+        uint256 tierSpreadBps;
+        uint256 stainPremiumBps;
+        bool rateCapped;
+        uint256 interestRateBps;
+    }
 }
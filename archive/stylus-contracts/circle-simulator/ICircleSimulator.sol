@@ -1,4 +1,10 @@
 interface ICircleSimulator  {
+    error AlreadyInitialized();
+
+    error InvalidMemberCount(uint8 num_members);
+
+    error InvalidSimulationCount(uint16 num_simulations);
+
     function initialize() external;
 
     function simulateCircle(uint8 num_members, uint256 cuota_amount, uint8 num_rounds, uint32 avg_default_probability, uint16 num_simulations) external returns (uint32, uint256, uint32, uint256, uint256);
@@ -5,9 +5,9 @@ extern crate alloc;
 use alloc::vec;
 use alloc::vec::Vec;
 
+use alloy_sol_types::{sol, SolError};
 use stylus_sdk::{
     alloy_primitives::{Address, U256},
-    msg,
     prelude::*,
 };
 
@@ -37,6 +37,13 @@ sol_storage! {
 // Note: Events would be defined here in production
 // Skipped for now to avoid SDK compatibility issues
 
+// Errors
+sol! {
+    error AlreadyInitialized();
+    error InvalidMemberCount(uint8 num_members);
+    error InvalidSimulationCount(uint16 num_simulations);
+}
+
 // Private implementation
 impl CircleSimulator {
     /// Run single simulation scenario
@@ -72,14 +79,14 @@ impl CircleSimulator {
             }
 
             // If >30% default in a round, circle fails
-            let default_threshold = (num_members * 30) / 100;
-            if (num_members - round_payments) > default_threshold {
+            let default_threshold = (num_members as u16 * 30) / 100;
+            if ((num_members - round_payments) as u16) > default_threshold {
                 had_catastrophic_default = true;
                 break;
             }
 
             // Collect payments
-            total_collected = total_collected + (cuota * U256::from(round_payments));
+            total_collected += cuota * U256::from(round_payments);
         }
 
         // Calculate final payout per member
@@ -129,7 +136,7 @@ impl CircleSimulator {
         let result = (a.wrapping_mul(combined).wrapping_add(c)) % m;
 
         // Map to 0-10000
-        (result % 10000) as u32
+        result % 10000
     }
 }
 
@@ -138,9 +145,10 @@ impl CircleSimulator {
     /// Initialize simulator
     pub fn initialize(&mut self) -> Result<(), Vec<u8>> {
         if self.owner.get() != Address::ZERO {
-            return Err(b"Already initialized".to_vec());
+            return Err(AlreadyInitialized {}.abi_encode());
         }
-        self.owner.set(msg::sender());
+        let deployer = self.vm().msg_sender();
+        self.owner.set(deployer);
         Ok(())
     }
 
@@ -159,10 +167,10 @@ impl CircleSimulator {
 
         // Validation
         if num_members == 0 || num_members > 100 {
-            return Err(b"Invalid member count".to_vec());
+            return Err(InvalidMemberCount { num_members }.abi_encode());
         }
         if num_simulations == 0 || num_simulations > 10000 {
-            return Err(b"Invalid simulation count".to_vec());
+            return Err(InvalidSimulationCount { num_simulations }.abi_encode());
         }
 
         // Track simulation count
@@ -186,7 +194,7 @@ impl CircleSimulator {
                 successes += 1;
             }
 
-            total_return = total_return + outcome.final_payout;
+            total_return += outcome.final_payout;
             results.push(outcome.final_payout);
         }
 
@@ -253,5 +261,6 @@ impl CircleSimulator {
 struct SimulationOutcome {
     success: bool,
     final_payout: U256,
+    #[allow(dead_code)]
     defaults_count: u32,
 }
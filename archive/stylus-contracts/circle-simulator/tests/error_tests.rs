@@ -0,0 +1,57 @@
+//! Typed revert data from CircleSimulator.
+
+#[cfg(test)]
+mod tests {
+    use alloy_sol_types::SolError;
+    use circle_simulator::*;
+    use stylus_sdk::alloy_primitives::{Address, U256};
+    use stylus_sdk::testing::*;
+
+    const OWNER: Address = Address::new([0x01; 20]);
+
+    fn selector(revert: &[u8]) -> [u8; 4] {
+        revert[..4].try_into().unwrap()
+    }
+
+    #[test]
+    fn test_initialize_records_sender_once() {
+        let vm = TestVM::new();
+        let mut sim = CircleSimulator::from(&vm);
+        vm.set_sender(OWNER);
+        sim.initialize().unwrap();
+        assert_eq!(sim.owner(), OWNER);
+
+        let err = sim.initialize().unwrap_err();
+        assert_eq!(selector(&err), AlreadyInitialized::SELECTOR);
+        assert_eq!(err, AlreadyInitialized {}.abi_encode());
+    }
+
+    #[test]
+    fn test_invalid_member_count_carries_value() {
+        let vm = TestVM::new();
+        let mut sim = CircleSimulator::from(&vm);
+
+        let err = sim.simulate_circle(101, U256::from(100), 12, 1000, 10).unwrap_err();
+        assert_eq!(selector(&err), InvalidMemberCount::SELECTOR);
+        assert_eq!(InvalidMemberCount::abi_decode(&err, true).unwrap().num_members, 101);
+
+        assert_eq!(
+            sim.quick_simulate(0, U256::from(100), 1000),
+            Err(InvalidMemberCount { num_members: 0 }.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_invalid_simulation_count_carries_value() {
+        let vm = TestVM::new();
+        let mut sim = CircleSimulator::from(&vm);
+
+        let err = sim.simulate_circle(10, U256::from(100), 12, 1000, 10_001).unwrap_err();
+        assert_eq!(selector(&err), InvalidSimulationCount::SELECTOR);
+        assert_eq!(
+            InvalidSimulationCount::abi_decode(&err, true).unwrap().num_simulations,
+            10_001
+        );
+        assert_eq!(sim.simulation_count(), U256::ZERO);
+    }
+}
@@ -7,31 +7,94 @@
 
 #![cfg_attr(not(any(test, feature = "export-abi")), no_main)]
 #![cfg_attr(not(any(test, feature = "export-abi")), no_std)]
+#![recursion_limit = "256"]
 
 #[macro_use]
 extern crate alloc;
 
 use alloc::vec::Vec;
+use alloy_sol_types::{sol, SolCall, SolError};
 use stylus_sdk::{
-    alloy_primitives::{Address, U256},
-    block,
-    call::Call,
-    evm,
-    msg,
+    abi::Bytes,
+    alloy_primitives::{b256, Address, B256, U256, U8},
     prelude::*,
-    storage::{StorageAddress, StorageBool, StorageMap, StorageU256},
+    storage::{StorageAddress, StorageBool, StorageMap, StorageU256, StorageU8, StorageVec},
+    stylus_core::calls::context::Call,
 };
 
-// Interfaz para ERC20 (llamadas al asset token)
-stylus_sdk::sol_interface! {
+// Re-export for macros
+use stylus_sdk::alloy_primitives;
+
+// Interfaces externas (asset token, receptores de flash loans, estrategia, Circles, RiskOracle
+// y el ERC-721 de posiciones de préstamo)
+// Las llamadas pasan por `self.vm()` para que funcionen igual en WASM y en stylus-test
+sol! {
     interface IERC20 {
         function transferFrom(address from, address to, uint256 amount) external returns (bool);
         function transfer(address to, uint256 amount) external returns (bool);
         function balanceOf(address account) external view returns (uint256);
     }
+
+    interface IERC3156FlashBorrower {
+        function onFlashLoan(address initiator, address token, uint256 amount, uint256 fee, bytes calldata data) external returns (bytes32);
+    }
+
+    interface IYieldStrategy {
+        function deposit(uint256 amount) external returns (uint256);
+        function withdraw(uint256 amount) external returns (uint256);
+        function totalValue() external view returns (uint256);
+    }
+
+    interface ICircle {
+        function getMembers() external view returns (address[] memory);
+        function totalCollateral() external view returns (uint256);
+    }
+
+    interface IRiskOracle {
+        function getLeverageLevel(address[] calldata members) external view returns (uint256, uint256);
+        function consumeQuote(uint256 quoteId, address[] calldata members) external returns (uint256, uint256, uint256, uint256);
+    }
+
+    interface ILoanPosition {
+        function mint(address to, uint256 tokenId) external;
+        function transferFrom(address from, address to, uint256 tokenId) external;
+    }
 }
 
-// Estructura de Loan
+/// Estado de un préstamo en el registro
+pub const LOAN_STATUS_NONE: u8 = 0;
+pub const LOAN_STATUS_ACTIVE: u8 = 1;
+pub const LOAN_STATUS_REPAID: u8 = 2;
+pub const LOAN_STATUS_LIQUIDATED: u8 = 3;
+
+/// Tipo de reestructuración registrada en el linaje de un préstamo
+pub const RESTRUCTURE_EXTENSION: u8 = 1;
+pub const RESTRUCTURE_REFINANCE: u8 = 2;
+
+/// keccak256("ERC3156FlashBorrower.onFlashLoan")
+pub const FLASH_LOAN_CALLBACK_SUCCESS: B256 =
+    b256!("439148f0bbc682ca079e46d6e2c2f0c1e3b820f1a291b069d8882abf8cf18dd9");
+
+/// Precompile ecrecover
+pub const ECRECOVER: Address = Address::new([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+/// Tipos EIP-712 de las acciones firmadas por LPs
+pub const EIP712_DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+pub const DEPOSIT_TYPE: &str = "Deposit(address lp,uint256 amount,uint256 nonce,uint256 deadline)";
+pub const WITHDRAW_TYPE: &str = "Withdraw(address lp,uint256 amount,uint256 nonce,uint256 deadline)";
+pub const CLAIM_TYPE: &str = "Claim(address lp,uint256 nonce,uint256 deadline)";
+
+/// secp256k1n / 2: firmas con `s` mayor son maleables
+const SECP256K1_HALF_N: U256 = U256::from_limbs([
+    0xdfe92f46681b20a0,
+    0x5d576e7357a4501d,
+    0xffffffffffffffff,
+    0x7fffffffffffffff,
+]);
+
+// Estructura de Loan (layout de referencia; el storage usa mappings por campo)
+#[allow(dead_code)]
 #[derive(Default)]
 pub struct Loan {
     principal: U256,
@@ -43,6 +106,19 @@ pub struct Loan {
 }
 
 sol_storage! {
+    /// Términos reemplazados por una extensión o refinanciamiento
+    pub struct LoanRevision {
+        uint8 kind;                     // RESTRUCTURE_*
+        uint256 principal;
+        uint256 interest_rate;
+        uint256 start_time;
+        uint256 duration;
+        uint256 paid;
+        uint256 capitalized_interest;   // interés impago sumado al nuevo principal
+        uint256 fee;                    // fee de extensión sumado al nuevo principal
+        uint256 restructured_at;
+    }
+
     #[entrypoint]
     pub struct KuyayVault {
         // Core state
@@ -72,11 +148,67 @@ sol_storage! {
         StorageMap<Address, StorageU256> loan_duration;
         StorageMap<Address, StorageU256> loan_paid;
         StorageMap<Address, StorageBool> loan_is_active;
+
+        // Flash loans (ERC-3156)
+        uint256 flash_fee_bps;  // 9 = 0.09%
+        uint256 total_flash_fees_earned;
+
+        // Idle-liquidity yield strategy
+        address strategy;
+        uint256 strategy_deployed;       // last synced strategy value
+        uint256 liquidity_buffer_bps;    // 2000 = 20% of liquid assets kept idle
+
+        // Loan pricing
+        address risk_oracle;
+
+        // Loan health monitoring
+        StorageVec<StorageAddress> loan_circles;     // every circle that ever borrowed
+        StorageMap<Address, StorageBool> loan_registered;
+        StorageMap<Address, StorageBool> loan_health_warned;
+        uint256 health_warning_threshold_bps;        // 5000 = 0.5
+
+        // Loan registry
+        StorageMap<Address, StorageU8> loan_status;  // LOAN_STATUS_*
+        StorageVec<StorageAddress> active_loans;
+        StorageMap<Address, StorageU256> active_loan_index; // position + 1, 0 = absent
+        uint256 repaid_loan_count;
+        uint256 liquidated_loan_count;
+
+        // Loan restructuring
+        uint256 next_loan_id;
+        StorageMap<Address, StorageU256> loan_id;    // current loan of each circle
+        StorageMap<U256, StorageVec<LoanRevision>> loan_revisions;
+        uint256 extension_fee_bps;                   // 100 = 1%
+
+        // Repayment contributions (by loan id)
+        StorageMap<U256, StorageMap<Address, StorageU256>> loan_contributions;
+        StorageMap<U256, StorageVec<StorageAddress>> loan_payers;
+
+        // Loan positions (ERC-721, token id = loan id)
+        address loan_position_nft;
+        StorageMap<U256, StorageAddress> position_circle;
+        StorageMap<U256, StorageAddress> position_holder; // zero = vault
+        StorageMap<U256, StorageU256> position_list_price;  // zero = not for sale
+
+        // LP delegation and signed actions
+        StorageMap<Address, StorageMap<Address, StorageBool>> operators;
+        StorageMap<Address, StorageU256> nonces;
+        StorageMap<Address, StorageU256> lp_principal;   // deposits not yet withdrawn (cost basis)
+
+        // Set while a flash loan's callback runs
+        bool flash_loan_active;
+
+        // Loan restructuring: interest and fees folded into principal, booked when repaid
+        StorageMap<Address, StorageU256> loan_capitalized;
+        uint256 restructure_min_health_bps;          // 5000 = 0.5
+
+        // ERC-721 contract each position was minted on; zero = never tokenized
+        StorageMap<U256, StorageAddress> position_nft;
     }
 }
 
 // Events
-stylus_sdk::sol! {
+sol! {
     event Deposit(address indexed lp, uint256 amount, uint256 shares_minted);
     event Withdraw(address indexed lp, uint256 amount, uint256 shares_burned);
     event LoanIssued(address indexed circle, uint256 principal, uint256 interest_rate, uint256 duration);
@@ -90,6 +222,27 @@ stylus_sdk::sol! {
     event TreasuryUpdated(address indexed new_treasury);
     event InsurancePoolFunded(uint256 amount);
     event OwnershipTransferred(address indexed previous_owner, address indexed new_owner);
+    event FlashLoan(address indexed receiver, address indexed token, uint256 amount, uint256 fee);
+    event FlashFeeUpdated(uint256 new_fee_bps);
+    event StrategyUpdated(address indexed previous_strategy, address indexed new_strategy);
+    event LiquidityBufferUpdated(uint256 new_buffer_bps);
+    event StrategyDeployed(address indexed strategy, uint256 amount);
+    event StrategyRecalled(address indexed strategy, uint256 requested, uint256 received);
+    event StrategySynced(address indexed strategy, uint256 previous_value, uint256 current_value);
+    event RiskOracleUpdated(address indexed new_risk_oracle);
+    event LoanHealthWarning(address indexed circle, uint256 health_factor_bps);
+    event LoanHealthRecovered(address indexed circle, uint256 health_factor_bps);
+    event HealthWarningThresholdUpdated(uint256 new_threshold_bps);
+    event LoanRestructured(address indexed circle, uint256 indexed loan_id, uint8 kind, uint256 previous_principal, uint256 new_principal, uint256 new_interest_rate, uint256 new_duration, uint256 capitalized_interest, uint256 fee);
+    event ExtensionFeeUpdated(uint256 new_fee_bps);
+    event RestructureMinHealthUpdated(uint256 new_min_health_bps);
+    event LoanPositionNftUpdated(address indexed new_nft);
+    event LoanPositionListed(uint256 indexed loan_id, uint256 price);
+    event LoanPositionSold(uint256 indexed loan_id, address indexed buyer, uint256 price, uint256 book_value);
+    event LoanPositionTransferred(uint256 indexed loan_id, address indexed from, address indexed to);
+    event OperatorSet(address indexed lp, address indexed operator, bool approved);
+    event YieldClaimed(address indexed lp, uint256 amount);
+    event LoanRepaidBy(address indexed payer, address indexed circle, uint256 indexed loan_id, uint256 amount);
 
     error Unauthorized();
     error NotAuthorizedCircle();
@@ -103,6 +256,26 @@ stylus_sdk::sol! {
     error InvalidParameter();
     error TransferFailed();
     error AlreadyInitialized();
+    error UnsupportedToken();
+    error FlashLoanCallbackFailed();
+    error FlashLoanNotRepaid();
+    error FlashLoanInProgress();
+    error StrategyCallFailed();
+    error RiskOracleNotSet();
+    error RiskCheckFailed();
+    error LeverageExceeded(uint256 requested, uint256 max_allowed);
+    error InterestRateTooLow(uint256 offered_bps, uint256 min_rate_bps);
+    error QuoteRejected(uint256 quote_id);
+    error QuoteTermsExceeded(uint256 quote_id);
+    error LoanHealthTooLow(uint256 health_factor_bps, uint256 min_health_bps);
+    error LoanMatured();
+    error LoanPositionUnavailable();
+    error LoanPositionSoldAlready();
+    error LoanPositionCallFailed();
+    error NotOperator();
+    error SignatureExpired(uint256 deadline);
+    error InvalidSignature();
+    error NothingToClaim();
 }
 
 #[public]
@@ -110,120 +283,143 @@ impl KuyayVault {
     /// Initialize the vault
     pub fn initialize(&mut self, asset_address: Address, treasury_address: Address) -> Result<(), Vec<u8>> {
         if self.owner.get() != Address::ZERO {
-            return Err(AlreadyInitialized {}.encode());
+            return Err(AlreadyInitialized {}.abi_encode());
         }
 
         if asset_address == Address::ZERO || treasury_address == Address::ZERO {
-            return Err(InvalidAddress {}.encode());
+            return Err(InvalidAddress {}.abi_encode());
         }
 
         self.asset.set(asset_address);
         self.treasury.set(treasury_address);
-        self.owner.set(msg::sender());
+        self.owner.set(self.vm().msg_sender());
         self.origination_fee_bps.set(U256::from(300)); // 3%
+        self.flash_fee_bps.set(U256::from(9)); // 0.09%
+        self.liquidity_buffer_bps.set(U256::from(2000)); // 20%
+        self.health_warning_threshold_bps.set(U256::from(5000)); // 0.5
+        self.extension_fee_bps.set(U256::from(100)); // 1%
+        self.restructure_min_health_bps.set(U256::from(5000)); // 0.5
 
         Ok(())
     }
 
     /// Deposit assets and receive shares (optimized for gas)
     pub fn deposit(&mut self, amount: U256) -> Result<U256, Vec<u8>> {
-        if amount == U256::ZERO {
-            return Err(InvalidAmount {}.encode());
-        }
-
-        // Calculate shares to mint (uses storage caching - virtually free on re-access)
-        let shares_to_mint = self.calculate_shares_for_deposit(amount)?;
-
-        // Transfer tokens from user
-        let asset = IERC20::new(self.asset.get());
-        let success = asset
-            .transfer_from(self, msg::sender(), contract::address(), amount)
-            .map_err(|_| TransferFailed {}.encode())?;
-
-        if !success {
-            return Err(TransferFailed {}.encode());
-        }
-
-        // Update state (storage caching makes multiple writes cheaper)
-        let sender = msg::sender();
-        let mut user_shares = self.shares.setter(sender);
-        let current_shares = user_shares.get();
-        user_shares.set(current_shares + shares_to_mint);
-
-        self.total_shares.set(self.total_shares.get() + shares_to_mint);
-        self.total_assets.set(self.total_assets.get() + amount);
-
-        evm::log(Deposit {
-            lp: sender,
-            amount,
-            shares_minted: shares_to_mint,
-        });
-
-        Ok(shares_to_mint)
+        let sender = self.vm().msg_sender();
+        self.deposit_for_lp(sender, amount)
     }
 
     /// Withdraw assets by burning shares (optimized)
     pub fn withdraw(&mut self, amount: U256) -> Result<(), Vec<u8>> {
-        if amount == U256::ZERO {
-            return Err(InvalidAmount {}.encode());
-        }
+        let sender = self.vm().msg_sender();
+        self.withdraw_for_lp(sender, amount)
+    }
 
-        let sender = msg::sender();
+    /// Retira solo el rendimiento acumulado (balance por encima de lo depositado)
+    pub fn claim(&mut self) -> Result<U256, Vec<u8>> {
+        let sender = self.vm().msg_sender();
+        self.claim_for_lp(sender)
+    }
 
-        // Get vault value and user balance (storage caching optimization)
-        let vault_value = self.get_vault_value();
-        let total_shares = self.total_shares.get();
+    // ========== OPERATORS & SIGNED ACTIONS ==========
 
-        if total_shares == U256::ZERO || vault_value == U256::ZERO {
-            return Err(InvalidAmount {}.encode());
+    /// Autoriza (o revoca) a `operator` para depositar, retirar y reclamar en nombre del caller
+    pub fn set_operator(&mut self, operator: Address, approved: bool) -> Result<(), Vec<u8>> {
+        if operator == Address::ZERO {
+            return Err(InvalidAddress {}.abi_encode());
         }
 
-        let user_shares = self.shares.get(sender);
-        let user_balance = (user_shares * vault_value) / total_shares;
+        let lp = self.vm().msg_sender();
+        self.operators.setter(lp).setter(operator).set(approved);
+        log(self.vm(), OperatorSet { lp, operator, approved });
+        Ok(())
+    }
 
-        if user_balance < amount {
-            return Err(InsufficientBalance {}.encode());
-        }
+    pub fn is_operator(&self, lp: Address, operator: Address) -> bool {
+        self.operators.getter(lp).get(operator)
+    }
 
-        // Check liquidity
-        let available = self.available_liquidity();
-        if available < amount {
-            return Err(InsufficientLiquidity {}.encode());
-        }
+    /// Deposita desde la cuenta de `lp` (que debe haber aprobado al vault)
+    pub fn deposit_for(&mut self, lp: Address, amount: U256) -> Result<U256, Vec<u8>> {
+        self.only_lp_or_operator(lp)?;
+        self.deposit_for_lp(lp, amount)
+    }
 
-        // Calculate shares to burn
-        let shares_to_burn = (amount * total_shares) / vault_value;
+    /// Retira a la cuenta de `lp`
+    pub fn withdraw_for(&mut self, lp: Address, amount: U256) -> Result<(), Vec<u8>> {
+        self.only_lp_or_operator(lp)?;
+        self.withdraw_for_lp(lp, amount)
+    }
 
-        // Update state
-        let mut user_shares_setter = self.shares.setter(sender);
-        user_shares_setter.set(user_shares - shares_to_burn);
+    pub fn claim_for(&mut self, lp: Address) -> Result<U256, Vec<u8>> {
+        self.only_lp_or_operator(lp)?;
+        self.claim_for_lp(lp)
+    }
 
-        self.total_shares.set(total_shares - shares_to_burn);
-        self.total_assets.set(self.total_assets.get() - amount);
+    /// Depósito firmado por `lp` (EIP-712), enviado por cualquier relayer
+    pub fn deposit_with_sig(
+        &mut self,
+        lp: Address,
+        amount: U256,
+        deadline: U256,
+        signature: Bytes,
+    ) -> Result<U256, Vec<u8>> {
+        let nonce = self.nonces.get(lp);
+        let struct_hash = self.hash_struct(DEPOSIT_TYPE, &[lp.into_word(), amount.into(), nonce.into(), deadline.into()]);
+        self.use_signature(lp, struct_hash, deadline, &signature)?;
+        self.deposit_for_lp(lp, amount)
+    }
 
-        // Transfer tokens
-        let asset = IERC20::new(self.asset.get());
-        let success = asset
-            .transfer(self, sender, amount)
-            .map_err(|_| TransferFailed {}.encode())?;
+    pub fn withdraw_with_sig(
+        &mut self,
+        lp: Address,
+        amount: U256,
+        deadline: U256,
+        signature: Bytes,
+    ) -> Result<(), Vec<u8>> {
+        let nonce = self.nonces.get(lp);
+        let struct_hash = self.hash_struct(WITHDRAW_TYPE, &[lp.into_word(), amount.into(), nonce.into(), deadline.into()]);
+        self.use_signature(lp, struct_hash, deadline, &signature)?;
+        self.withdraw_for_lp(lp, amount)
+    }
 
-        if !success {
-            return Err(TransferFailed {}.encode());
-        }
+    pub fn claim_with_sig(&mut self, lp: Address, deadline: U256, signature: Bytes) -> Result<U256, Vec<u8>> {
+        let nonce = self.nonces.get(lp);
+        let struct_hash = self.hash_struct(CLAIM_TYPE, &[lp.into_word(), nonce.into(), deadline.into()]);
+        self.use_signature(lp, struct_hash, deadline, &signature)?;
+        self.claim_for_lp(lp)
+    }
 
-        evm::log(Withdraw {
-            lp: sender,
-            amount,
-            shares_burned: shares_to_burn,
-        });
+    pub fn nonces(&self, lp: Address) -> U256 {
+        self.nonces.get(lp)
+    }
 
-        Ok(())
+    #[selector(name = "DOMAIN_SEPARATOR")]
+    pub fn domain_separator(&self) -> B256 {
+        let name = self.vm().native_keccak256(b"KuyayVault");
+        let version = self.vm().native_keccak256(b"1");
+        self.hash_struct(EIP712_DOMAIN_TYPE, &[
+            name,
+            version,
+            U256::from(self.vm().chain_id()).into(),
+            self.vm().contract_address().into_word(),
+        ])
+    }
+
+    /// Rendimiento que `lp` puede reclamar hoy
+    pub fn claimable(&self, lp: Address) -> U256 {
+        self.balance_of(lp).saturating_sub(self.lp_principal.get(lp))
+    }
+
+    pub fn principal_of(&self, lp: Address) -> U256 {
+        self.lp_principal.get(lp)
     }
 
     /// Batch deposit for multiple LPs (Stylus exclusive feature)
     pub fn batch_deposit(&mut self, lps: Vec<Address>, amounts: Vec<U256>) -> Result<Vec<U256>, Vec<u8>> {
+        self.not_in_flash_loan()?;
         if lps.len() != amounts.len() {
-            return Err(InvalidParameter {}.encode());
+            return Err(InvalidParameter {}.abi_encode());
         }
 
         let mut shares_minted = Vec::new();
@@ -240,15 +436,17 @@ impl KuyayVault {
             // Storage caching makes this loop extremely cheap
             let shares = self.calculate_shares_for_deposit(amount)?;
 
-            let mut lp_shares = self.shares.setter(lp);
-            lp_shares.set(lp_shares.get() + shares);
+            let lp_shares = self.shares.get(lp);
+            self.shares.setter(lp).set(lp_shares + shares);
+            let principal = self.lp_principal.get(lp);
+            self.lp_principal.setter(lp).set(principal + amount);
 
             self.total_shares.set(self.total_shares.get() + shares);
             self.total_assets.set(self.total_assets.get() + amount);
 
             shares_minted.push(shares);
 
-            evm::log(Deposit {
+            log(self.vm(), Deposit {
                 lp,
                 amount,
                 shares_minted: shares,
@@ -268,114 +466,71 @@ impl KuyayVault {
         self.only_authorized_circle()?;
 
         if amount == U256::ZERO {
-            return Err(InvalidAmount {}.encode());
+            return Err(InvalidAmount {}.abi_encode());
         }
 
-        let circle = msg::sender();
+        let circle = self.vm().msg_sender();
 
         if self.loan_is_active.get(circle) {
-            return Err(LoanAlreadyActive {}.encode());
-        }
-
-        // Check liquidity
-        let available = self.available_liquidity();
-        if available < amount {
-            return Err(InsufficientLiquidity {}.encode());
+            return Err(LoanAlreadyActive {}.abi_encode());
         }
 
-        // Calculate origination fee
-        let fee_bps = self.origination_fee_bps.get();
-        let origination_fee = (amount * fee_bps) / U256::from(10000);
-        let net_amount = amount - origination_fee;
+        // Enforce RiskOracle leverage and minimum rate
+        let (max_loan, min_rate_bps) = self.oracle_loan_terms(circle)?;
+        self.issue_loan(circle, amount, duration_in_days, interest_rate_bps, max_loan, min_rate_bps)
+    }
 
-        // Convert duration to seconds (optimized multiplication)
-        let duration_seconds = duration_in_days * U256::from(86400);
+    /// Request loan at the terms of a RiskOracle quote, which is consumed (only authorized circles)
+    pub fn request_loan_with_quote(
+        &mut self,
+        amount: U256,
+        duration_in_days: U256,
+        interest_rate_bps: U256,
+        quote_id: U256,
+    ) -> Result<U256, Vec<u8>> {
+        self.only_authorized_circle()?;
 
-        // Store loan data (packed storage)
-        self.loan_principal.setter(circle).set(amount);
-        self.loan_interest_rate.setter(circle).set(interest_rate_bps);
-        self.loan_start_time.setter(circle).set(U256::from(block::timestamp()));
-        self.loan_duration.setter(circle).set(duration_seconds);
-        self.loan_paid.setter(circle).set(U256::ZERO);
-        self.loan_is_active.setter(circle).set(true);
+        if amount == U256::ZERO {
+            return Err(InvalidAmount {}.abi_encode());
+        }
 
-        self.total_loaned.set(self.total_loaned.get() + amount);
+        let circle = self.vm().msg_sender();
 
-        // Transfer fee to treasury
-        if origination_fee > U256::ZERO {
-            let asset = IERC20::new(self.asset.get());
-            let _ = asset.transfer(self, self.treasury.get(), origination_fee)
-                .map_err(|_| TransferFailed {}.encode())?;
+        if self.loan_is_active.get(circle) {
+            return Err(LoanAlreadyActive {}.abi_encode());
         }
 
-        // Transfer net amount to circle
-        let asset = IERC20::new(self.asset.get());
-        let _ = asset.transfer(self, circle, net_amount)
-            .map_err(|_| TransferFailed {}.encode())?;
-
-        evm::log(LoanIssued {
-            circle,
-            principal: amount,
-            interest_rate: interest_rate_bps,
-            duration: duration_seconds,
-        });
+        let oracle = self.risk_oracle.get();
+        if oracle == Address::ZERO {
+            return Err(RiskOracleNotSet {}.abi_encode());
+        }
+        let (members, collateral) = self.circle_members_and_collateral(circle)?;
+
+        // The oracle checks expiry, reuse and the member set
+        let terms = self
+            .call_contract(oracle, &IRiskOracle::consumeQuoteCall { quoteId: quote_id, members })
+            .ok_or_else(|| QuoteRejected { quote_id }.abi_encode())?;
+        let (multiplier, min_rate_bps, quoted_amount, quoted_days) = (terms._0, terms._1, terms._2, terms._3);
+        if amount > quoted_amount || duration_in_days > quoted_days {
+            return Err(QuoteTermsExceeded { quote_id }.abi_encode());
+        }
 
-        Ok(net_amount)
+        let max_loan = max_loan_for(collateral, multiplier);
+        self.issue_loan(circle, amount, duration_in_days, interest_rate_bps, max_loan, min_rate_bps)
     }
 
     /// Repay loan (optimized interest calculation)
     pub fn repay_loan(&mut self, amount: U256) -> Result<(), Vec<u8>> {
         self.only_authorized_circle()?;
 
-        let circle = msg::sender();
-
-        if !self.loan_is_active.get(circle) {
-            return Err(NoActiveLoan {}.encode());
-        }
-
-        if amount == U256::ZERO {
-            return Err(InvalidAmount {}.encode());
-        }
-
-        // Transfer tokens from circle
-        let asset = IERC20::new(self.asset.get());
-        let _ = asset.transfer_from(self, circle, contract::address(), amount)
-            .map_err(|_| TransferFailed {}.encode())?;
-
-        // Update paid amount
-        let mut loan_paid = self.loan_paid.setter(circle);
-        loan_paid.set(loan_paid.get() + amount);
-
-        // Calculate total debt (uses efficient Rust arithmetic)
-        let total_debt = self.calculate_total_debt(circle)?;
-        let paid = loan_paid.get();
-        let remaining_debt = if total_debt > paid {
-            total_debt - paid
-        } else {
-            U256::ZERO
-        };
-
-        // If fully paid, mark as inactive
-        if remaining_debt == U256::ZERO {
-            let principal = self.loan_principal.get(circle);
-            self.total_loaned.set(self.total_loaned.get() - principal);
-            self.loan_is_active.setter(circle).set(false);
-        }
-
-        // Track interest earned
-        let principal = self.loan_principal.get(circle);
-        if paid > principal {
-            let interest = paid - principal;
-            self.total_interest_earned.set(self.total_interest_earned.get() + interest);
-        }
-
-        evm::log(LoanRepayment {
-            circle,
-            amount,
-            remaining_debt,
-        });
+        let circle = self.vm().msg_sender();
+        self.apply_repayment(circle, circle, amount)
+    }
 
-        Ok(())
+    /// Pay a circle's debt on its behalf (members, sponsors, agents); funds come from the caller
+    pub fn repay_loan_for(&mut self, circle: Address, amount: U256) -> Result<(), Vec<u8>> {
+        let payer = self.vm().msg_sender();
+        self.apply_repayment(circle, payer, amount)
     }
 
     /// Liquidate circle (only owner)
@@ -383,25 +538,34 @@ impl KuyayVault {
         self.only_owner()?;
 
         if !self.loan_is_active.get(circle_address) {
-            return Err(NoActiveLoan {}.encode());
+            return Err(NoActiveLoan {}.abi_encode());
         }
 
-        let total_debt = self.calculate_total_debt(circle_address)?;
-        let paid = self.loan_paid.get(circle_address);
-        let unpaid_debt = if total_debt > paid {
-            total_debt - paid
-        } else {
-            U256::ZERO
-        };
+        let unpaid_debt = self.calculate_total_debt(circle_address)?;
 
         // Mark loan as inactive
-        self.loan_is_active.setter(circle_address).set(false);
-        let principal = self.loan_principal.get(circle_address);
-        self.total_loaned.set(self.total_loaned.get() - principal);
+        self.close_loan(circle_address, LOAN_STATUS_LIQUIDATED);
+
+        // A sold position's loss belongs to its holder, and so does what is recovered
+        let loan_id = self.loan_id.get(circle_address);
+        let sold = self.is_position_sold(loan_id);
+        if sold {
+            let recovered = collateral_recovered.min(unpaid_debt);
+            if recovered > U256::ZERO {
+                let success = self.transfer_asset(self.position_holder.get(loan_id), recovered)?;
+                if !success {
+                    return Err(TransferFailed {}.abi_encode());
+                }
+            }
+        } else {
+            let basis = self.loan_basis(circle_address);
+            self.total_loaned.set(self.total_loaned.get() - basis);
+        }
 
-        // Calculate loss
+        // Capitalized amounts were never booked, so they are not a loss either
+        let unpaid_debt = unpaid_debt.saturating_sub(self.loan_capitalized.get(circle_address));
         let mut loss = U256::ZERO;
-        if collateral_recovered < unpaid_debt {
+        if !sold && collateral_recovered < unpaid_debt {
             loss = unpaid_debt - collateral_recovered;
 
             // Try to cover from insurance pool
@@ -410,13 +574,13 @@ impl KuyayVault {
                 self.insurance_pool.set(insurance - loss);
                 loss = U256::ZERO;
             } else {
-                loss = loss - insurance;
+                loss -= insurance;
                 self.insurance_pool.set(U256::ZERO);
                 self.total_assets.set(self.total_assets.get() - loss);
             }
         }
 
-        evm::log(LoanLiquidated {
+        log(self.vm(), LoanLiquidated {
             circle: circle_address,
             recovered_amount: collateral_recovered,
             loss_amount: loss,
@@ -425,95 +589,498 @@ impl KuyayVault {
         Ok(())
     }
 
-    // ========== VIEW FUNCTIONS ==========
+    // ========== LOAN RESTRUCTURING ==========
 
-    pub fn balance_of(&self, lp: Address) -> U256 {
-        let total_shares = self.total_shares.get();
-        if total_shares == U256::ZERO {
-            return U256::ZERO;
+    /// Extiende el vencimiento `extra_days`; el interés impago y el fee se capitalizan
+    pub fn extend_loan(&mut self, extra_days: U256) -> Result<U256, Vec<u8>> {
+        self.only_authorized_circle()?;
+
+        if extra_days == U256::ZERO {
+            return Err(InvalidParameter {}.abi_encode());
         }
 
-        let vault_value = self.get_vault_value();
-        let user_shares = self.shares.get(lp);
+        let circle = self.vm().msg_sender();
+        let rate = self.loan_interest_rate.get(circle);
+        self.restructure_loan(circle, RESTRUCTURE_EXTENSION, rate, extra_days * U256::from(86400))
+    }
 
-        (user_shares * vault_value) / total_shares
+    /// Refinancia a `new_rate_bps` hasta el vencimiento actual; el interés impago y el fee se capitalizan
+    pub fn refinance_loan(&mut self, new_rate_bps: U256) -> Result<U256, Vec<u8>> {
+        self.only_authorized_circle()?;
+
+        let circle = self.vm().msg_sender();
+        self.restructure_loan(circle, RESTRUCTURE_REFINANCE, new_rate_bps, U256::ZERO)
     }
 
-    pub fn calculate_total_debt(&self, circle: Address) -> Result<U256, Vec<u8>> {
-        if !self.loan_is_active.get(circle) {
-            return Ok(U256::ZERO);
+    /// Total aportado por `payer` al préstamo `loan_id`
+    pub fn get_contribution(&self, loan_id: U256, payer: Address) -> U256 {
+        self.loan_contributions.getter(loan_id).get(payer)
+    }
+
+    /// Quienes pagaron el préstamo `loan_id` y cuánto aportó cada uno
+    pub fn get_loan_payers(&self, loan_id: U256) -> (Vec<Address>, Vec<U256>) {
+        let payers = self.loan_payers.getter(loan_id);
+        let contributions = self.loan_contributions.getter(loan_id);
+
+        (0..payers.len())
+            .filter_map(|i| payers.get(i))
+            .map(|payer| (payer, contributions.get(payer)))
+            .unzip()
+    }
+
+    pub fn get_loan_id(&self, circle: Address) -> U256 {
+        self.loan_id.get(circle)
+    }
+
+    pub fn get_loan_revision_count(&self, loan_id: U256) -> U256 {
+        U256::from(self.loan_revisions.getter(loan_id).len())
+    }
+
+    /// (kind, principal, rate, start, duration, paid, interés capitalizado, fee, fecha) de una revisión
+    #[allow(clippy::type_complexity)]
+    pub fn get_loan_revision(
+        &self,
+        loan_id: U256,
+        index: U256,
+    ) -> Result<(u8, U256, U256, U256, U256, U256, U256, U256, U256), Vec<u8>> {
+        let revisions = self.loan_revisions.getter(loan_id);
+        let revision = index
+            .try_into()
+            .ok()
+            .and_then(|i: usize| revisions.get(i))
+            .ok_or_else(|| InvalidParameter {}.abi_encode())?;
+
+        Ok((
+            revision.kind.get().to::<u8>(),
+            revision.principal.get(),
+            revision.interest_rate.get(),
+            revision.start_time.get(),
+            revision.duration.get(),
+            revision.paid.get(),
+            revision.capitalized_interest.get(),
+            revision.fee.get(),
+            revision.restructured_at.get(),
+        ))
+    }
+
+    // ========== LOAN POSITIONS (ERC-721) ==========
+
+    /// Pone a la venta la posición de un préstamo activo en poder del vault (precio 0 = retirar)
+    pub fn list_loan_position(&mut self, loan_id: U256, price: U256) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        // Loans issued before the NFT was configured have no token to sell
+        let circle = self.position_circle.get(loan_id);
+        if !self.is_position_tokenized(loan_id)
+            || circle == Address::ZERO
+            || !self.loan_is_active.get(circle) || self.loan_id.get(circle) != loan_id {
+            return Err(LoanPositionUnavailable {}.abi_encode());
+        }
+        if self.is_position_sold(loan_id) {
+            return Err(LoanPositionSoldAlready {}.abi_encode());
         }
 
-        let principal = self.loan_principal.get(circle);
-        let rate = self.loan_interest_rate.get(circle);
-        let start_time = self.loan_start_time.get(circle);
-        let duration = self.loan_duration.get(circle);
-        let paid = self.loan_paid.get(circle);
+        self.position_list_price.setter(loan_id).set(price);
+        log(self.vm(), LoanPositionListed { loan_id, price });
+        Ok(())
+    }
 
-        // Calculate time elapsed (clamped to duration)
-        let current_time = U256::from(block::timestamp());
-        let mut time_elapsed = if current_time > start_time {
-            current_time - start_time
-        } else {
-            U256::ZERO
-        };
+    /// Compra una posición listada; desde ahí los repagos del préstamo van al holder del NFT
+    pub fn buy_loan_position(&mut self, loan_id: U256, max_price: U256) -> Result<(), Vec<u8>> {
+        let price = self.position_list_price.get(loan_id);
+        if price == U256::ZERO {
+            return Err(LoanPositionUnavailable {}.abi_encode());
+        }
+        if price > max_price {
+            return Err(InvalidAmount {}.abi_encode());
+        }
 
-        if time_elapsed > duration {
-            time_elapsed = duration;
+        let circle = self.position_circle.get(loan_id);
+        if !self.is_position_tokenized(loan_id)
+            || !self.loan_is_active.get(circle) || self.loan_id.get(circle) != loan_id {
+            return Err(LoanPositionUnavailable {}.abi_encode());
         }
 
-        // Calculate interest: (principal * rate * time) / (10000 * 365 days)
-        // Optimized calculation using Rust's native arithmetic
-        let seconds_per_year = U256::from(31536000); // 365 * 24 * 60 * 60
-        let interest = (principal * rate * time_elapsed) / (U256::from(10000) * seconds_per_year);
+        let buyer = self.vm().msg_sender();
+        let vault = self.vm().contract_address();
+        if buyer == vault {
+            return Err(InvalidAddress {}.abi_encode());
+        }
 
-        let total_debt = principal + interest;
+        let success = self.transfer_asset_from(buyer, vault, price)?;
+        if !success {
+            return Err(TransferFailed {}.abi_encode());
+        }
 
-        if total_debt > paid {
-            Ok(total_debt - paid)
+        let nft = self.loan_position_nft.get();
+        self.call_contract(
+            nft,
+            &ILoanPosition::transferFromCall {
+                from: vault,
+                to: buyer,
+                tokenId: loan_id,
+            },
+        )
+        .ok_or_else(|| LoanPositionCallFailed {}.abi_encode())?;
+
+        // Loan leaves the balance sheet at the cash lent; payments already received stay with the vault
+        let principal = self.loan_basis(circle);
+        let received = self.loan_paid.get(circle).min(principal);
+        let settled = price + received;
+        let total_assets = self.total_assets.get();
+        if settled >= principal {
+            self.total_assets.set(total_assets + (settled - principal));
         } else {
-            Ok(U256::ZERO)
+            self.total_assets.set(total_assets.saturating_sub(principal - settled));
         }
+        self.total_loaned.set(self.total_loaned.get() - principal);
+
+        self.position_holder.setter(loan_id).set(buyer);
+        self.position_list_price.setter(loan_id).set(U256::ZERO);
+
+        log(self.vm(), LoanPositionSold {
+            loan_id,
+            buyer,
+            price,
+            book_value: principal,
+        });
+        Ok(())
     }
 
-    pub fn available_liquidity(&self) -> U256 {
-        let total = self.total_assets.get();
-        let loaned = self.total_loaned.get();
+    /// Hook del ERC-721 en transferencias que no inicia el vault
+    pub fn on_loan_position_transfer(&mut self, from: Address, to: Address, token_id: U256) -> Result<(), Vec<u8>> {
+        if self.vm().msg_sender() != self.loan_position_nft.get() {
+            return Err(Unauthorized {}.abi_encode());
+        }
 
-        if total > loaned {
-            total - loaned
-        } else {
-            U256::ZERO
+        // Positions only leave the vault through buy_loan_position
+        if !self.is_position_sold(token_id) || self.position_holder.get(token_id) != from {
+            return Err(LoanPositionUnavailable {}.abi_encode());
         }
+        if to == Address::ZERO || to == self.vm().contract_address() {
+            return Err(InvalidAddress {}.abi_encode());
+        }
+
+        self.position_holder.setter(token_id).set(to);
+        log(self.vm(), LoanPositionTransferred {
+            loan_id: token_id,
+            from,
+            to,
+        });
+        Ok(())
     }
 
-    pub fn get_loan(&self, circle: Address) -> (U256, U256, U256, U256, U256, bool) {
+    /// (circle, holder, vendida, precio de lista) de la posición `loan_id`
+    pub fn get_loan_position(&self, loan_id: U256) -> (Address, Address, bool, U256) {
+        let holder = if self.is_position_sold(loan_id) {
+            self.position_holder.get(loan_id)
+        } else {
+            self.vm().contract_address()
+        };
+
         (
-            self.loan_principal.get(circle),
-            self.loan_interest_rate.get(circle),
-            self.loan_start_time.get(circle),
-            self.loan_duration.get(circle),
-            self.loan_paid.get(circle),
-            self.loan_is_active.get(circle),
+            self.position_circle.get(loan_id),
+            holder,
+            self.is_position_sold(loan_id),
+            self.position_list_price.get(loan_id),
         )
     }
 
-    pub fn current_apy(&self) -> U256 {
-        let total = self.total_assets.get();
-        if total == U256::ZERO {
+    /// A dónde van los repagos del préstamo actual de `circle`: (loan id, destinatario)
+    pub fn get_cash_flow_route(&self, circle: Address) -> (U256, Address) {
+        let loan_id = self.loan_id.get(circle);
+        let (_, recipient, _, _) = self.get_loan_position(loan_id);
+        (loan_id, recipient)
+    }
+
+    // ========== FLASH LOANS (ERC-3156) ==========
+
+    /// Máximo prestable en flash loan: solo la liquidez ociosa, nunca lo prestado a Circles
+    /// ni lo desplegado en la estrategia
+    pub fn max_flash_loan(&self, token: Address) -> U256 {
+        if token != self.asset.get() {
             return U256::ZERO;
         }
 
-        let loaned = self.total_loaned.get();
-        (loaned * U256::from(1000)) / total
+        self.idle_liquidity()
     }
 
-    pub fn owner(&self) -> Address {
-        self.owner.get()
-    }
+    /// Fee cobrado por un flash loan de `amount`
+    pub fn flash_fee(&self, token: Address, amount: U256) -> Result<U256, Vec<u8>> {
+        if token != self.asset.get() {
+            return Err(UnsupportedToken {}.abi_encode());
+        }
 
-    pub fn asset(&self) -> Address {
-        self.asset.get()
+        Ok(self.calculate_flash_fee(amount))
+    }
+
+    /// Flash loan de liquidez ociosa; el fee se acumula para los LPs. Mientras dura, el
+    /// receptor no puede depositar ni retirar a un precio de share sin el fee
+    pub fn flash_loan(
+        &mut self,
+        receiver: Address,
+        token: Address,
+        amount: U256,
+        data: Bytes,
+    ) -> Result<bool, Vec<u8>> {
+        self.not_in_flash_loan()?;
+
+        self.flash_loan_active.set(true);
+        let result = self.execute_flash_loan(receiver, token, amount, data);
+        self.flash_loan_active.set(false);
+        result
+    }
+
+    // ========== LOAN HEALTH ==========
+
+    /// Health factor en bps (10000 = 1.0): colateral / (deuda restante + atraso vs calendario)
+    pub fn health_factor(&self, circle: Address) -> Result<U256, Vec<u8>> {
+        if !self.loan_is_active.get(circle) {
+            return Err(NoActiveLoan {}.abi_encode());
+        }
+
+        Ok(self.loan_health(circle).0)
+    }
+
+    /// Desglose de salud: (health factor, colateral, deuda restante, pago esperado a la fecha,
+    /// pagado, segundos al vencimiento)
+    #[allow(clippy::type_complexity)]
+    pub fn get_loan_health(&self, circle: Address) -> Result<(U256, U256, U256, U256, U256, U256), Vec<u8>> {
+        if !self.loan_is_active.get(circle) {
+            return Err(NoActiveLoan {}.abi_encode());
+        }
+
+        let (health, collateral, remaining_debt, expected_paid) = self.loan_health(circle);
+        let maturity = self.loan_start_time.get(circle) + self.loan_duration.get(circle);
+        let now = U256::from(self.vm().block_timestamp());
+        let time_to_maturity = if maturity > now { maturity - now } else { U256::ZERO };
+
+        Ok((
+            health,
+            collateral,
+            remaining_debt,
+            expected_paid,
+            self.loan_paid.get(circle),
+            time_to_maturity,
+        ))
+    }
+
+    /// Préstamos activos con health factor bajo `threshold_bps`, paginado sobre los préstamos activos
+    pub fn at_risk_loans(
+        &self,
+        threshold_bps: U256,
+        offset: U256,
+        limit: U256,
+    ) -> (Vec<Address>, Vec<U256>) {
+        let mut circles = Vec::new();
+        let mut factors = Vec::new();
+
+        for circle in self.get_active_loans(offset, limit) {
+            let (health, _, _, _) = self.loan_health(circle);
+            if health < threshold_bps {
+                circles.push(circle);
+                factors.push(health);
+            }
+        }
+
+        (circles, factors)
+    }
+
+    /// Recalcula la salud de un préstamo (keepers) y emite aviso al cruzar el umbral
+    pub fn check_loan_health(&mut self, circle: Address) -> Result<U256, Vec<u8>> {
+        if !self.loan_is_active.get(circle) {
+            return Err(NoActiveLoan {}.abi_encode());
+        }
+
+        Ok(self.update_loan_health(circle))
+    }
+
+    // ========== LOAN REGISTRY ==========
+
+    /// Circles con préstamo activo, paginado
+    pub fn get_active_loans(&self, offset: U256, limit: U256) -> Vec<Address> {
+        let total = self.active_loans.len();
+        let start: usize = offset.try_into().unwrap_or(total).min(total);
+        let count: usize = limit.try_into().unwrap_or(total);
+        let end = start.saturating_add(count).min(total);
+
+        (start..end).filter_map(|i| self.active_loans.get(i)).collect()
+    }
+
+    /// Circles cuyo último préstamo tiene `status`; `offset` y `limit` cuentan coincidencias
+    pub fn get_loans_by_status(&self, status: u8, offset: U256, limit: U256) -> Result<Vec<Address>, Vec<u8>> {
+        if status == LOAN_STATUS_NONE || status > LOAN_STATUS_LIQUIDATED {
+            return Err(InvalidParameter {}.abi_encode());
+        }
+
+        let skip: usize = offset.try_into().unwrap_or(usize::MAX);
+        let take: usize = limit.try_into().unwrap_or(usize::MAX);
+
+        Ok((0..self.loan_circles.len())
+            .filter_map(|i| self.loan_circles.get(i))
+            .filter(|circle| self.loan_status.get(*circle).to::<u8>() == status)
+            .skip(skip)
+            .take(take)
+            .collect())
+    }
+
+    pub fn get_loan_status(&self, circle: Address) -> u8 {
+        self.loan_status.get(circle).to::<u8>()
+    }
+
+    /// (activos, pagados, liquidados, circles que alguna vez pidieron préstamo)
+    pub fn get_loan_stats(&self) -> (U256, U256, U256, U256) {
+        (
+            U256::from(self.active_loans.len()),
+            self.repaid_loan_count.get(),
+            self.liquidated_loan_count.get(),
+            U256::from(self.loan_circles.len()),
+        )
+    }
+
+    pub fn get_loan_count(&self) -> U256 {
+        U256::from(self.loan_circles.len())
+    }
+
+    // ========== VIEW FUNCTIONS ==========
+
+    pub fn balance_of(&self, lp: Address) -> U256 {
+        let total_shares = self.total_shares.get();
+        if total_shares == U256::ZERO {
+            return U256::ZERO;
+        }
+
+        let vault_value = self.get_vault_value();
+        let user_shares = self.shares.get(lp);
+
+        (user_shares * vault_value) / total_shares
+    }
+
+    pub fn calculate_total_debt(&self, circle: Address) -> Result<U256, Vec<u8>> {
+        if !self.loan_is_active.get(circle) {
+            return Ok(U256::ZERO);
+        }
+
+        let principal = self.loan_principal.get(circle);
+        let rate = self.loan_interest_rate.get(circle);
+        let start_time = self.loan_start_time.get(circle);
+        let duration = self.loan_duration.get(circle);
+        let paid = self.loan_paid.get(circle);
+
+        // Calculate time elapsed (clamped to duration)
+        let current_time = U256::from(self.vm().block_timestamp());
+        let mut time_elapsed = if current_time > start_time {
+            current_time - start_time
+        } else {
+            U256::ZERO
+        };
+
+        if time_elapsed > duration {
+            time_elapsed = duration;
+        }
+
+        // Calculate interest: (principal * rate * time) / (10000 * 365 days)
+        // Optimized calculation using Rust's native arithmetic
+        let seconds_per_year = U256::from(31536000); // 365 * 24 * 60 * 60
+        let interest = (principal * rate * time_elapsed) / (U256::from(10000) * seconds_per_year);
+
+        let total_debt = principal + interest;
+
+        if total_debt > paid {
+            Ok(total_debt - paid)
+        } else {
+            Ok(U256::ZERO)
+        }
+    }
+
+    /// Liquidez disponible para retiros y préstamos (incluye lo recuperable de la estrategia)
+    pub fn available_liquidity(&self) -> U256 {
+        let total = self.total_assets.get() + self.strategy_total_value();
+        let committed = self.total_loaned.get() + self.strategy_deployed.get();
+
+        if total > committed {
+            total - committed
+        } else {
+            U256::ZERO
+        }
+    }
+
+    /// Liquidez en caja del vault (sin contar la estrategia)
+    pub fn idle_liquidity(&self) -> U256 {
+        let total = self.total_assets.get();
+        let committed = self.total_loaned.get() + self.strategy_deployed.get();
+
+        if total > committed {
+            total - committed
+        } else {
+            U256::ZERO
+        }
+    }
+
+    /// Valor actual reportado por la estrategia (último valor sincronizado si la llamada falla)
+    pub fn strategy_total_value(&self) -> U256 {
+        let strategy = self.strategy.get();
+        if strategy == Address::ZERO {
+            return U256::ZERO;
+        }
+
+        self.static_call_contract(strategy, &IYieldStrategy::totalValueCall {})
+            .map(|ret| ret._0)
+            .unwrap_or_else(|| self.strategy_deployed.get())
+    }
+
+    pub fn get_loan(&self, circle: Address) -> (U256, U256, U256, U256, U256, bool) {
+        (
+            self.loan_principal.get(circle),
+            self.loan_interest_rate.get(circle),
+            self.loan_start_time.get(circle),
+            self.loan_duration.get(circle),
+            self.loan_paid.get(circle),
+            self.loan_is_active.get(circle),
+        )
+    }
+
+    pub fn current_apy(&self) -> U256 {
+        let total = self.total_assets.get();
+        if total == U256::ZERO {
+            return U256::ZERO;
+        }
+
+        let loaned = self.total_loaned.get();
+        (loaned * U256::from(1000)) / total
+    }
+
+    /// Préstamo máximo y tasa mínima que el RiskOracle aprueba hoy para un Circle
+    pub fn get_loan_terms(&self, circle: Address) -> Result<(U256, U256), Vec<u8>> {
+        self.oracle_loan_terms(circle)
+    }
+
+    pub fn owner(&self) -> Address {
+        self.owner.get()
+    }
+
+    pub fn risk_oracle(&self) -> Address {
+        self.risk_oracle.get()
+    }
+
+    pub fn health_warning_threshold_bps(&self) -> U256 {
+        self.health_warning_threshold_bps.get()
+    }
+
+    pub fn extension_fee_bps(&self) -> U256 {
+        self.extension_fee_bps.get()
+    }
+
+    pub fn restructure_min_health_bps(&self) -> U256 {
+        self.restructure_min_health_bps.get()
+    }
+
+    pub fn loan_position_nft(&self) -> Address {
+        self.loan_position_nft.get()
+    }
+
+    pub fn asset(&self) -> Address {
+        self.asset.get()
     }
 
     pub fn treasury(&self) -> Address {
@@ -540,27 +1107,47 @@ impl KuyayVault {
         self.total_interest_earned.get()
     }
 
+    pub fn flash_fee_bps(&self) -> U256 {
+        self.flash_fee_bps.get()
+    }
+
+    pub fn total_flash_fees_earned(&self) -> U256 {
+        self.total_flash_fees_earned.get()
+    }
+
+    pub fn strategy(&self) -> Address {
+        self.strategy.get()
+    }
+
+    pub fn strategy_deployed(&self) -> U256 {
+        self.strategy_deployed.get()
+    }
+
+    pub fn liquidity_buffer_bps(&self) -> U256 {
+        self.liquidity_buffer_bps.get()
+    }
+
     // ========== ADMIN FUNCTIONS ==========
 
     pub fn authorize_circle(&mut self, circle: Address) -> Result<(), Vec<u8>> {
         // Can be called by factory or owner
-        if !self.authorized_factories.get(msg::sender()) && msg::sender() != self.owner.get() {
-            return Err(NotAuthorizedFactory {}.encode());
+        if !self.authorized_factories.get(self.vm().msg_sender()) && self.vm().msg_sender() != self.owner.get() {
+            return Err(NotAuthorizedFactory {}.abi_encode());
         }
 
         if circle == Address::ZERO {
-            return Err(InvalidAddress {}.encode());
+            return Err(InvalidAddress {}.abi_encode());
         }
 
         self.authorized_circles.setter(circle).set(true);
-        evm::log(CircleAuthorized { circle });
+        log(self.vm(), CircleAuthorized { circle });
         Ok(())
     }
 
     pub fn revoke_circle(&mut self, circle: Address) -> Result<(), Vec<u8>> {
         self.only_owner()?;
         self.authorized_circles.setter(circle).set(false);
-        evm::log(CircleRevoked { circle });
+        log(self.vm(), CircleRevoked { circle });
         Ok(())
     }
 
@@ -568,18 +1155,18 @@ impl KuyayVault {
         self.only_owner()?;
 
         if factory == Address::ZERO {
-            return Err(InvalidAddress {}.encode());
+            return Err(InvalidAddress {}.abi_encode());
         }
 
         self.authorized_factories.setter(factory).set(true);
-        evm::log(FactoryAuthorized { factory });
+        log(self.vm(), FactoryAuthorized { factory });
         Ok(())
     }
 
     pub fn revoke_factory(&mut self, factory: Address) -> Result<(), Vec<u8>> {
         self.only_owner()?;
         self.authorized_factories.setter(factory).set(false);
-        evm::log(FactoryRevoked { factory });
+        log(self.vm(), FactoryRevoked { factory });
         Ok(())
     }
 
@@ -587,11 +1174,142 @@ impl KuyayVault {
         self.only_owner()?;
 
         if new_fee_bps > U256::from(1000) {
-            return Err(InvalidParameter {}.encode());
+            return Err(InvalidParameter {}.abi_encode());
         }
 
         self.origination_fee_bps.set(new_fee_bps);
-        evm::log(OriginationFeeUpdated { new_fee_bps });
+        log(self.vm(), OriginationFeeUpdated { new_fee_bps });
+        Ok(())
+    }
+
+    pub fn set_flash_fee(&mut self, new_fee_bps: U256) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        if new_fee_bps > U256::from(1000) {
+            return Err(InvalidParameter {}.abi_encode());
+        }
+
+        self.flash_fee_bps.set(new_fee_bps);
+        log(self.vm(), FlashFeeUpdated { new_fee_bps });
+        Ok(())
+    }
+
+    /// Cambia la estrategia de rendimiento; recupera todo lo desplegado en la anterior
+    pub fn set_strategy(&mut self, new_strategy: Address) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        let previous_strategy = self.strategy.get();
+        if previous_strategy != Address::ZERO {
+            self.sync_strategy()?;
+            let deployed = self.strategy_deployed.get();
+            if deployed > U256::ZERO {
+                self.recall_from_strategy(deployed)?;
+            }
+
+            // Anything the old strategy could not return is written off
+            let unrecovered = self.strategy_deployed.get();
+            if unrecovered > U256::ZERO {
+                let total = self.total_assets.get();
+                self.total_assets.set(if total > unrecovered { total - unrecovered } else { U256::ZERO });
+                self.strategy_deployed.set(U256::ZERO);
+            }
+        }
+
+        self.strategy.set(new_strategy);
+        log(self.vm(), StrategyUpdated { previous_strategy, new_strategy });
+        Ok(())
+    }
+
+    pub fn set_liquidity_buffer(&mut self, new_buffer_bps: U256) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        if new_buffer_bps > U256::from(10000) {
+            return Err(InvalidParameter {}.abi_encode());
+        }
+
+        self.liquidity_buffer_bps.set(new_buffer_bps);
+        log(self.vm(), LiquidityBufferUpdated { new_buffer_bps });
+        Ok(())
+    }
+
+    /// Mueve liquidez entre caja y estrategia hasta dejar el buffer configurado
+    pub fn rebalance_strategy(&mut self) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        if self.strategy.get() == Address::ZERO {
+            return Err(InvalidAddress {}.abi_encode());
+        }
+
+        self.sync_strategy()?;
+
+        let total = self.total_assets.get();
+        let loaned = self.total_loaned.get();
+        let liquid = if total > loaned { total - loaned } else { U256::ZERO };
+        let target_idle = (liquid * self.liquidity_buffer_bps.get()) / U256::from(10000);
+        let idle = self.idle_liquidity();
+
+        if idle > target_idle {
+            self.deploy_to_strategy(idle - target_idle)?;
+        } else if idle < target_idle {
+            self.recall_from_strategy(target_idle - idle)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn set_risk_oracle(&mut self, new_risk_oracle: Address) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        if new_risk_oracle == Address::ZERO {
+            return Err(InvalidAddress {}.abi_encode());
+        }
+
+        self.risk_oracle.set(new_risk_oracle);
+        log(self.vm(), RiskOracleUpdated { new_risk_oracle });
+        Ok(())
+    }
+
+    pub fn set_health_warning_threshold(&mut self, new_threshold_bps: U256) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        if new_threshold_bps == U256::ZERO {
+            return Err(InvalidParameter {}.abi_encode());
+        }
+
+        self.health_warning_threshold_bps.set(new_threshold_bps);
+        log(self.vm(), HealthWarningThresholdUpdated { new_threshold_bps });
+        Ok(())
+    }
+
+    pub fn set_extension_fee(&mut self, new_fee_bps: U256) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        if new_fee_bps > U256::from(1000) {
+            return Err(InvalidParameter {}.abi_encode()); // Max 10%
+        }
+
+        self.extension_fee_bps.set(new_fee_bps);
+        log(self.vm(), ExtensionFeeUpdated { new_fee_bps });
+        Ok(())
+    }
+
+    /// Health factor mínimo para extender o refinanciar (0 = sin control)
+    pub fn set_restructure_min_health(&mut self, new_min_health_bps: U256) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+        self.restructure_min_health_bps.set(new_min_health_bps);
+        log(self.vm(), RestructureMinHealthUpdated { new_min_health_bps });
+        Ok(())
+    }
+
+    pub fn set_loan_position_nft(&mut self, new_nft: Address) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        if new_nft == Address::ZERO {
+            return Err(InvalidAddress {}.abi_encode());
+        }
+
+        self.loan_position_nft.set(new_nft);
+        log(self.vm(), LoanPositionNftUpdated { new_nft });
         Ok(())
     }
 
@@ -599,27 +1317,25 @@ impl KuyayVault {
         self.only_owner()?;
 
         if new_treasury == Address::ZERO {
-            return Err(InvalidAddress {}.encode());
+            return Err(InvalidAddress {}.abi_encode());
         }
 
         self.treasury.set(new_treasury);
-        evm::log(TreasuryUpdated { new_treasury });
+        log(self.vm(), TreasuryUpdated { new_treasury });
         Ok(())
     }
 
     pub fn fund_insurance_pool(&mut self, amount: U256) -> Result<(), Vec<u8>> {
         if amount == U256::ZERO {
-            return Err(InvalidAmount {}.encode());
+            return Err(InvalidAmount {}.abi_encode());
         }
 
-        let asset = IERC20::new(self.asset.get());
-        let _ = asset.transfer_from(self, msg::sender(), contract::address(), amount)
-            .map_err(|_| TransferFailed {}.encode())?;
+        let _ = self.transfer_asset_from(self.vm().msg_sender(), self.vm().contract_address(), amount)?;
 
         self.insurance_pool.set(self.insurance_pool.get() + amount);
         self.total_assets.set(self.total_assets.get() + amount);
 
-        evm::log(InsurancePoolFunded { amount });
+        log(self.vm(), InsurancePoolFunded { amount });
         Ok(())
     }
 
@@ -627,47 +1343,884 @@ impl KuyayVault {
         self.only_owner()?;
 
         if new_owner == Address::ZERO {
-            return Err(InvalidAddress {}.encode());
+            return Err(InvalidAddress {}.abi_encode());
         }
 
         let previous_owner = self.owner.get();
         self.owner.set(new_owner);
 
-        evm::log(OwnershipTransferred { previous_owner, new_owner });
+        log(self.vm(), OwnershipTransferred { previous_owner, new_owner });
         Ok(())
     }
+}
 
-    // ========== INTERNAL FUNCTIONS ==========
+// ========== INTERNAL FUNCTIONS ==========
 
-    fn only_owner(&self) -> Result<(), Vec<u8>> {
-        if msg::sender() != self.owner.get() {
-            return Err(Unauthorized {}.encode());
+impl KuyayVault {
+
+    fn execute_flash_loan(
+        &mut self,
+        receiver: Address,
+        token: Address,
+        amount: U256,
+        data: Bytes,
+    ) -> Result<bool, Vec<u8>> {
+        if token != self.asset.get() {
+            return Err(UnsupportedToken {}.abi_encode());
         }
-        Ok(())
-    }
 
-    fn only_authorized_circle(&self) -> Result<(), Vec<u8>> {
-        if !self.authorized_circles.get(msg::sender()) {
-            return Err(NotAuthorizedCircle {}.encode());
+        if receiver == Address::ZERO {
+            return Err(InvalidAddress {}.abi_encode());
         }
-        Ok(())
-    }
 
-    fn get_vault_value(&self) -> U256 {
-        self.total_assets.get() + self.total_interest_earned.get()
-    }
+        if amount == U256::ZERO {
+            return Err(InvalidAmount {}.abi_encode());
+        }
 
-    fn calculate_shares_for_deposit(&self, amount: U256) -> Result<U256, Vec<u8>> {
-        let total_shares = self.total_shares.get();
+        if amount > self.max_flash_loan(token) {
+            return Err(InsufficientLiquidity {}.abi_encode());
+        }
 
-        if total_shares == U256::ZERO {
-            Ok(amount)
-        } else {
+        let fee = self.calculate_flash_fee(amount);
+        let vault = self.vm().contract_address();
+        let balance_before = self.asset_balance(vault)?;
+
+        // Send funds to receiver
+        let success = self.transfer_asset(receiver, amount)?;
+        if !success {
+            return Err(TransferFailed {}.abi_encode());
+        }
+
+        // Receiver callback must return the ERC-3156 magic value
+        let callback = IERC3156FlashBorrower::onFlashLoanCall {
+            initiator: self.vm().msg_sender(),
+            token,
+            amount,
+            fee,
+            data: data.0.into(),
+        };
+        let result = self
+            .call_contract(receiver, &callback)
+            .ok_or_else(|| FlashLoanCallbackFailed {}.abi_encode())?;
+
+        if result._0 != FLASH_LOAN_CALLBACK_SUCCESS {
+            return Err(FlashLoanCallbackFailed {}.abi_encode());
+        }
+
+        // Pull principal + fee back from receiver
+        let success = self
+            .transfer_asset_from(receiver, vault, amount + fee)
+            .map_err(|_| FlashLoanNotRepaid {}.abi_encode())?;
+        if !success {
+            return Err(FlashLoanNotRepaid {}.abi_encode());
+        }
+
+        // Verify repayment against actual token balance
+        let balance_after = self.asset_balance(vault)?;
+        if balance_after < balance_before + fee {
+            return Err(FlashLoanNotRepaid {}.abi_encode());
+        }
+
+        // Fee accrues to LPs through vault value
+        self.total_assets.set(self.total_assets.get() + fee);
+        self.total_flash_fees_earned.set(self.total_flash_fees_earned.get() + fee);
+
+        log(self.vm(), FlashLoan {
+            receiver,
+            token,
+            amount,
+            fee,
+        });
+
+        Ok(true)
+    }
+
+    fn deposit_for_lp(&mut self, lp: Address, amount: U256) -> Result<U256, Vec<u8>> {
+        self.not_in_flash_loan()?;
+        if amount == U256::ZERO {
+            return Err(InvalidAmount {}.abi_encode());
+        }
+
+        // Calculate shares to mint (uses storage caching - virtually free on re-access)
+        let shares_to_mint = self.calculate_shares_for_deposit(amount)?;
+
+        // Transfer tokens from the LP
+        let success = self.transfer_asset_from(lp, self.vm().contract_address(), amount)?;
+
+        if !success {
+            return Err(TransferFailed {}.abi_encode());
+        }
+
+        // Update state (storage caching makes multiple writes cheaper)
+        let mut user_shares = self.shares.setter(lp);
+        let current_shares = user_shares.get();
+        user_shares.set(current_shares + shares_to_mint);
+
+        self.total_shares.set(self.total_shares.get() + shares_to_mint);
+        self.total_assets.set(self.total_assets.get() + amount);
+        let principal = self.lp_principal.get(lp);
+        self.lp_principal.setter(lp).set(principal + amount);
+
+        log(self.vm(), Deposit {
+            lp,
+            amount,
+            shares_minted: shares_to_mint,
+        });
+
+        Ok(shares_to_mint)
+    }
+
+    fn claim_for_lp(&mut self, lp: Address) -> Result<U256, Vec<u8>> {
+        let amount = self.claimable(lp);
+        if amount == U256::ZERO {
+            return Err(NothingToClaim {}.abi_encode());
+        }
+
+        self.withdraw_for_lp(lp, amount)?;
+        log(self.vm(), YieldClaimed { lp, amount });
+        Ok(amount)
+    }
+
+    fn withdraw_for_lp(&mut self, lp: Address, amount: U256) -> Result<(), Vec<u8>> {
+        self.not_in_flash_loan()?;
+        if amount == U256::ZERO {
+            return Err(InvalidAmount {}.abi_encode());
+        }
+
+        // Get vault value and user balance (storage caching optimization)
+        let vault_value = self.get_vault_value();
+        let total_shares = self.total_shares.get();
+
+        if total_shares == U256::ZERO || vault_value == U256::ZERO {
+            return Err(InvalidAmount {}.abi_encode());
+        }
+
+        let user_shares = self.shares.get(lp);
+        let user_balance = (user_shares * vault_value) / total_shares;
+
+        if user_balance < amount {
+            return Err(InsufficientBalance {}.abi_encode());
+        }
+
+        // Check liquidity
+        let available = self.available_liquidity();
+        if available < amount {
+            return Err(InsufficientLiquidity {}.abi_encode());
+        }
+
+        // Recall from strategy if idle cash is short
+        self.ensure_idle_liquidity(amount)?;
+
+        // Calculate shares to burn
+        let shares_to_burn = (amount * total_shares) / vault_value;
+
+        // Update state; withdrawals come out of yield before principal
+        let mut user_shares_setter = self.shares.setter(lp);
+        user_shares_setter.set(user_shares - shares_to_burn);
+
+        let principal = self.lp_principal.get(lp);
+        let yield_part = user_balance.saturating_sub(principal);
+        self.lp_principal
+            .setter(lp)
+            .set(principal.saturating_sub(amount.saturating_sub(yield_part)));
+
+        self.total_shares.set(total_shares - shares_to_burn);
+        self.total_assets.set(self.total_assets.get() - amount);
+
+        // Transfer tokens
+        let success = self.transfer_asset(lp, amount)?;
+
+        if !success {
+            return Err(TransferFailed {}.abi_encode());
+        }
+
+        log(self.vm(), Withdraw {
+            lp,
+            amount,
+            shares_burned: shares_to_burn,
+        });
+
+        Ok(())
+    }
+
+    fn only_owner(&self) -> Result<(), Vec<u8>> {
+        if self.vm().msg_sender() != self.owner.get() {
+            return Err(Unauthorized {}.abi_encode());
+        }
+        Ok(())
+    }
+
+    fn only_lp_or_operator(&self, lp: Address) -> Result<(), Vec<u8>> {
+        let sender = self.vm().msg_sender();
+        if sender != lp && !self.operators.getter(lp).get(sender) {
+            return Err(NotOperator {}.abi_encode());
+        }
+        Ok(())
+    }
+
+    fn not_in_flash_loan(&self) -> Result<(), Vec<u8>> {
+        if self.flash_loan_active.get() {
+            return Err(FlashLoanInProgress {}.abi_encode());
+        }
+        Ok(())
+    }
+
+    fn only_authorized_circle(&self) -> Result<(), Vec<u8>> {
+        if !self.authorized_circles.get(self.vm().msg_sender()) {
+            return Err(NotAuthorizedCircle {}.abi_encode());
+        }
+        Ok(())
+    }
+
+    fn get_vault_value(&self) -> U256 {
+        // Strategy funds count at their live value, not at the last synced value
+        let book_assets = self.total_assets.get().saturating_sub(self.strategy_deployed.get());
+        book_assets + self.strategy_total_value() + self.total_interest_earned.get()
+    }
+
+    /// (health factor, collateral, remaining debt, expected paid to date) for an active loan
+    fn loan_health(&self, circle: Address) -> (U256, U256, U256, U256) {
+        let principal = self.loan_principal.get(circle);
+        let rate = self.loan_interest_rate.get(circle);
+        let start_time = self.loan_start_time.get(circle);
+        let duration = self.loan_duration.get(circle);
+        let paid = self.loan_paid.get(circle);
+
+        let remaining_debt = self.calculate_total_debt(circle).unwrap_or_default();
+
+        // Linear payment schedule over the loan term, full-term interest included
+        let now = U256::from(self.vm().block_timestamp());
+        let elapsed = if now > start_time { now - start_time } else { U256::ZERO };
+        let elapsed = elapsed.min(duration);
+        let seconds_per_year = U256::from(31536000);
+        let full_term_interest = (principal * rate * duration) / (U256::from(10000) * seconds_per_year);
+        let expected_paid = if duration > U256::ZERO {
+            ((principal + full_term_interest) * elapsed) / duration
+        } else {
+            principal + full_term_interest
+        };
+        let overdue = if expected_paid > paid { expected_paid - paid } else { U256::ZERO };
+
+        // Unreadable collateral counts as zero
+        let collateral = self
+            .static_call_contract(circle, &ICircle::totalCollateralCall {})
+            .map(|ret| ret._0)
+            .unwrap_or_default();
+
+        let at_risk_debt = remaining_debt + overdue;
+        let health = if at_risk_debt == U256::ZERO {
+            U256::MAX
+        } else {
+            (collateral * U256::from(10000)) / at_risk_debt
+        };
+
+        (health, collateral, remaining_debt, expected_paid)
+    }
+
+    fn update_loan_health(&mut self, circle: Address) -> U256 {
+        let (health, _, _, _) = self.loan_health(circle);
+        let below = health < self.health_warning_threshold_bps.get();
+        let warned = self.loan_health_warned.get(circle);
+
+        if below && !warned {
+            self.loan_health_warned.setter(circle).set(true);
+            log(self.vm(), LoanHealthWarning { circle, health_factor_bps: health });
+        } else if !below && warned {
+            self.loan_health_warned.setter(circle).set(false);
+            log(self.vm(), LoanHealthRecovered { circle, health_factor_bps: health });
+        }
+
+        health
+    }
+
+    /// Shared repayment path: pulls `amount` from `payer` and credits `circle`'s loan
+    fn apply_repayment(&mut self, circle: Address, payer: Address, amount: U256) -> Result<(), Vec<u8>> {
+        if !self.loan_is_active.get(circle) {
+            return Err(NoActiveLoan {}.abi_encode());
+        }
+
+        if amount == U256::ZERO {
+            return Err(InvalidAmount {}.abi_encode());
+        }
+
+        // Sold positions: cash flows go straight to the holder
+        let loan_id = self.loan_id.get(circle);
+        let sold = self.is_position_sold(loan_id);
+        let recipient = if sold {
+            self.position_holder.get(loan_id)
+        } else {
+            self.vm().contract_address()
+        };
+        let success = self.transfer_asset_from(payer, recipient, amount)?;
+        if !success {
+            return Err(TransferFailed {}.abi_encode());
+        }
+
+        // Per-payer contributions to this loan
+        let contributed = self.loan_contributions.getter(loan_id).get(payer);
+        if contributed == U256::ZERO {
+            self.loan_payers.setter(loan_id).push(payer);
+        }
+        self.loan_contributions
+            .setter(loan_id)
+            .setter(payer)
+            .set(contributed + amount);
+
+        // Update paid amount
+        let previously_paid = self.loan_paid.get(circle);
+        let paid = previously_paid + amount;
+        self.loan_paid.setter(circle).set(paid);
+
+        // Remaining debt (calculate_total_debt already nets payments)
+        let remaining_debt = self.calculate_total_debt(circle)?;
+
+        // Payments return the cash lent first; the rest, capitalized amounts included, is interest
+        let basis = self.loan_basis(circle);
+        if !sold {
+            let interest = paid.saturating_sub(basis) - previously_paid.saturating_sub(basis);
+            self.total_interest_earned.set(self.total_interest_earned.get() + interest);
+        }
+
+        // If fully paid, mark as inactive
+        if remaining_debt == U256::ZERO {
+            if !sold {
+                self.total_loaned.set(self.total_loaned.get() - basis);
+            }
+            self.close_loan(circle, LOAN_STATUS_REPAID);
+        }
+
+        if self.loan_is_active.get(circle) {
+            self.update_loan_health(circle);
+        }
+
+        log(self.vm(), LoanRepayment {
+            circle,
+            amount,
+            remaining_debt,
+        });
+        log(self.vm(), LoanRepaidBy {
+            payer,
+            circle,
+            loan_id,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Replaces the active loan with new terms starting now. As in `apply_repayment`, payments
+    /// return the cash lent first; unpaid interest and the extension fee become principal and
+    /// are booked as interest only when repaid.
+    fn restructure_loan(
+        &mut self,
+        circle: Address,
+        kind: u8,
+        new_rate_bps: U256,
+        extra_duration: U256,
+    ) -> Result<U256, Vec<u8>> {
+        if !self.loan_is_active.get(circle) {
+            return Err(NoActiveLoan {}.abi_encode());
+        }
+
+        if self.is_position_sold(self.loan_id.get(circle)) {
+            return Err(LoanPositionSoldAlready {}.abi_encode());
+        }
+
+        // Payment history: the health factor already penalizes missed scheduled payments
+        let (health, _, outstanding, _) = self.loan_health(circle);
+        let min_health_bps = self.restructure_min_health_bps.get();
+        if health < min_health_bps {
+            return Err(LoanHealthTooLow {
+                health_factor_bps: health,
+                min_health_bps,
+            }
+            .abi_encode());
+        }
+
+        let principal = self.loan_principal.get(circle);
+        let rate = self.loan_interest_rate.get(circle);
+        let start_time = self.loan_start_time.get(circle);
+        let duration = self.loan_duration.get(circle);
+        let paid = self.loan_paid.get(circle);
+        let now = U256::from(self.vm().block_timestamp());
+        let maturity = start_time + duration;
+        let remaining_time = if maturity > now { maturity - now } else { U256::ZERO };
+        let new_duration = remaining_time + extra_duration;
+        if new_duration == U256::ZERO {
+            return Err(LoanMatured {}.abi_encode());
+        }
+
+        let accrued_interest = (outstanding + paid).saturating_sub(principal);
+        let capitalized_interest = accrued_interest.saturating_sub(paid.saturating_sub(principal));
+        let fee = (outstanding * self.extension_fee_bps.get()) / U256::from(10000);
+        let new_principal = outstanding + fee;
+        if new_principal == U256::ZERO {
+            return Err(InvalidAmount {}.abi_encode());
+        }
+
+        // Fresh RiskOracle check on the new terms
+        let (max_loan, min_rate_bps) = self.oracle_loan_terms(circle)?;
+        if new_principal > max_loan {
+            return Err(LeverageExceeded {
+                requested: new_principal,
+                max_allowed: max_loan,
+            }
+            .abi_encode());
+        }
+        if new_rate_bps < min_rate_bps {
+            return Err(InterestRateTooLow {
+                offered_bps: new_rate_bps,
+                min_rate_bps,
+            }
+            .abi_encode());
+        }
+
+        // Record the replaced terms
+        let loan_id = self.loan_id.get(circle);
+        {
+            let mut revisions = self.loan_revisions.setter(loan_id);
+            let mut revision = revisions.grow();
+            revision.kind.set(U8::from(kind));
+            revision.principal.set(principal);
+            revision.interest_rate.set(rate);
+            revision.start_time.set(start_time);
+            revision.duration.set(duration);
+            revision.paid.set(paid);
+            revision.capitalized_interest.set(capitalized_interest);
+            revision.fee.set(fee);
+            revision.restructured_at.set(now);
+        }
+
+        // Only the cash still lent stays on the books; the rest of the new principal is capitalized
+        let basis = self.loan_basis(circle);
+        let new_basis = basis.saturating_sub(paid);
+        self.total_loaned.set(self.total_loaned.get() - basis + new_basis);
+        self.loan_capitalized.setter(circle).set(new_principal - new_basis);
+
+        self.loan_principal.setter(circle).set(new_principal);
+        self.loan_interest_rate.setter(circle).set(new_rate_bps);
+        self.loan_start_time.setter(circle).set(now);
+        self.loan_duration.setter(circle).set(new_duration);
+        self.loan_paid.setter(circle).set(U256::ZERO);
+        self.loan_health_warned.setter(circle).set(false);
+
+        log(self.vm(), LoanRestructured {
+            circle,
+            loan_id,
+            kind,
+            previous_principal: principal,
+            new_principal,
+            new_interest_rate: new_rate_bps,
+            new_duration,
+            capitalized_interest,
+            fee,
+        });
+
+        Ok(new_principal)
+    }
+
+    /// Cash lent on the active loan: principal without capitalized interest and fees
+    fn loan_basis(&self, circle: Address) -> U256 {
+        self.loan_principal.get(circle) - self.loan_capitalized.get(circle)
+    }
+
+    fn is_position_sold(&self, loan_id: U256) -> bool {
+        self.position_holder.get(loan_id) != Address::ZERO
+    }
+
+    /// Records the position and, once the ERC-721 is configured, mints it to the vault
+    fn mint_loan_position(&mut self, circle: Address, loan_id: U256) -> Result<(), Vec<u8>> {
+        self.position_circle.setter(loan_id).set(circle);
+
+        let nft = self.loan_position_nft.get();
+        if nft == Address::ZERO {
+            return Ok(());
+        }
+
+        let vault = self.vm().contract_address();
+        self.call_contract(nft, &ILoanPosition::mintCall { to: vault, tokenId: loan_id })
+            .ok_or_else(|| LoanPositionCallFailed {}.abi_encode())?;
+        self.position_nft.setter(loan_id).set(nft);
+        Ok(())
+    }
+
+    /// The position has a token on the current ERC-721
+    fn is_position_tokenized(&self, loan_id: U256) -> bool {
+        let nft = self.loan_position_nft.get();
+        nft != Address::ZERO && self.position_nft.get(loan_id) == nft
+    }
+
+    fn register_active_loan(&mut self, circle: Address) {
+        if !self.loan_registered.get(circle) {
+            self.loan_registered.setter(circle).set(true);
+            self.loan_circles.push(circle);
+        }
+
+        self.loan_status.setter(circle).set(U8::from(LOAN_STATUS_ACTIVE));
+        self.active_loans.push(circle);
+        let position = U256::from(self.active_loans.len());
+        self.active_loan_index.setter(circle).set(position);
+    }
+
+    /// Marks the loan inactive and swap-removes it from the active list
+    fn close_loan(&mut self, circle: Address, status: u8) {
+        self.loan_is_active.setter(circle).set(false);
+        self.loan_status.setter(circle).set(U8::from(status));
+
+        if status == LOAN_STATUS_REPAID {
+            self.repaid_loan_count.set(self.repaid_loan_count.get() + U256::from(1));
+        } else if status == LOAN_STATUS_LIQUIDATED {
+            self.liquidated_loan_count.set(self.liquidated_loan_count.get() + U256::from(1));
+        }
+
+        let position = self.active_loan_index.get(circle);
+        if position == U256::ZERO {
+            return;
+        }
+
+        let index: usize = (position - U256::from(1)).to::<usize>();
+        let last_index = self.active_loans.len() - 1;
+        if index != last_index {
+            if let Some(last) = self.active_loans.get(last_index) {
+                if let Some(mut slot) = self.active_loans.setter(index) {
+                    slot.set(last);
+                }
+                self.active_loan_index.setter(last).set(position);
+            }
+        }
+        self.active_loans.erase_last();
+        self.active_loan_index.setter(circle).set(U256::ZERO);
+    }
+
+    /// Checks leverage and rate against the oracle terms, then funds the loan
+    fn issue_loan(
+        &mut self,
+        circle: Address,
+        amount: U256,
+        duration_in_days: U256,
+        interest_rate_bps: U256,
+        max_loan: U256,
+        min_rate_bps: U256,
+    ) -> Result<U256, Vec<u8>> {
+        if amount > max_loan {
+            return Err(LeverageExceeded {
+                requested: amount,
+                max_allowed: max_loan,
+            }
+            .abi_encode());
+        }
+        if interest_rate_bps < min_rate_bps {
+            return Err(InterestRateTooLow {
+                offered_bps: interest_rate_bps,
+                min_rate_bps,
+            }
+            .abi_encode());
+        }
+
+        // Check liquidity
+        let available = self.available_liquidity();
+        if available < amount {
+            return Err(InsufficientLiquidity {}.abi_encode());
+        }
+
+        // Recall from strategy if idle cash is short
+        self.ensure_idle_liquidity(amount)?;
+
+        // Calculate origination fee
+        let fee_bps = self.origination_fee_bps.get();
+        let origination_fee = (amount * fee_bps) / U256::from(10000);
+        let net_amount = amount - origination_fee;
+
+        // Convert duration to seconds (optimized multiplication)
+        let duration_seconds = duration_in_days * U256::from(86400);
+
+        // Store loan data (packed storage)
+        self.loan_principal.setter(circle).set(amount);
+        self.loan_capitalized.setter(circle).set(U256::ZERO);
+        self.loan_interest_rate.setter(circle).set(interest_rate_bps);
+        let now = U256::from(self.vm().block_timestamp());
+        self.loan_start_time.setter(circle).set(now);
+        self.loan_duration.setter(circle).set(duration_seconds);
+        self.loan_paid.setter(circle).set(U256::ZERO);
+        self.loan_is_active.setter(circle).set(true);
+        self.loan_health_warned.setter(circle).set(false);
+        self.register_active_loan(circle);
+
+        let loan_id = self.next_loan_id.get() + U256::from(1);
+        self.next_loan_id.set(loan_id);
+        self.loan_id.setter(circle).set(loan_id);
+        self.mint_loan_position(circle, loan_id)?;
+
+        self.total_loaned.set(self.total_loaned.get() + amount);
+
+        // Transfer fee to treasury
+        if origination_fee > U256::ZERO {
+            let success = self.transfer_asset(self.treasury.get(), origination_fee)?;
+            if !success {
+                return Err(TransferFailed {}.abi_encode());
+            }
+        }
+
+        // Transfer net amount to circle
+        let success = self.transfer_asset(circle, net_amount)?;
+        if !success {
+            return Err(TransferFailed {}.abi_encode());
+        }
+
+        log(self.vm(), LoanIssued {
+            circle,
+            principal: amount,
+            interest_rate: interest_rate_bps,
+            duration: duration_seconds,
+        });
+
+        Ok(net_amount)
+    }
+
+    fn ensure_idle_liquidity(&mut self, amount: U256) -> Result<(), Vec<u8>> {
+        let idle = self.idle_liquidity();
+        if idle >= amount {
+            return Ok(());
+        }
+
+        if self.strategy.get() == Address::ZERO {
+            return Err(InsufficientLiquidity {}.abi_encode());
+        }
+
+        self.sync_strategy()?;
+        let idle = self.idle_liquidity();
+        if idle < amount {
+            self.recall_from_strategy(amount - idle)?;
+        }
+
+        if self.idle_liquidity() < amount {
+            return Err(InsufficientLiquidity {}.abi_encode());
+        }
+        Ok(())
+    }
+
+    fn calculate_flash_fee(&self, amount: U256) -> U256 {
+        (amount * self.flash_fee_bps.get()) / U256::from(10000)
+    }
+
+    fn calculate_shares_for_deposit(&self, amount: U256) -> Result<U256, Vec<u8>> {
+        let total_shares = self.total_shares.get();
+
+        if total_shares == U256::ZERO {
+            Ok(amount)
+        } else {
             let vault_value = self.get_vault_value();
             if vault_value == U256::ZERO {
-                return Err(InvalidAmount {}.encode());
+                return Err(InvalidAmount {}.abi_encode());
             }
             Ok((amount * total_shares) / vault_value)
         }
     }
 }
+
+// ========== EXTERNAL CALLS ==========
+
+impl KuyayVault {
+    /// Calls `target` through the host VM and decodes the typed return value
+    fn call_contract<C: SolCall>(&mut self, target: Address, call: &C) -> Option<C::Return> {
+        let returned = self
+            .vm()
+            .call(&Call::new(), target, &call.abi_encode())
+            .ok()?;
+        C::abi_decode_returns(&returned, true).ok()
+    }
+
+    /// Static-calls `target` through the host VM and decodes the typed return value
+    fn static_call_contract<C: SolCall>(&self, target: Address, call: &C) -> Option<C::Return> {
+        let returned = self
+            .vm()
+            .static_call(&Call::new(), target, &call.abi_encode())
+            .ok()?;
+        C::abi_decode_returns(&returned, true).ok()
+    }
+
+    /// keccak256(typeHash || fields), each field already ABI-encoded as a word
+    fn hash_struct(&self, type_string: &str, fields: &[B256]) -> B256 {
+        let mut encoded = Vec::with_capacity(32 * (fields.len() + 1));
+        encoded.extend_from_slice(self.vm().native_keccak256(type_string.as_bytes()).as_slice());
+        for field in fields {
+            encoded.extend_from_slice(field.as_slice());
+        }
+        self.vm().native_keccak256(&encoded)
+    }
+
+    /// Checks deadline and signer of an EIP-712 message, then burns the LP's nonce
+    fn use_signature(&mut self, lp: Address, struct_hash: B256, deadline: U256, signature: &[u8]) -> Result<(), Vec<u8>> {
+        if U256::from(self.vm().block_timestamp()) > deadline {
+            return Err(SignatureExpired { deadline }.abi_encode());
+        }
+
+        let mut message = Vec::with_capacity(66);
+        message.extend_from_slice(b"\x19\x01");
+        message.extend_from_slice(self.domain_separator().as_slice());
+        message.extend_from_slice(struct_hash.as_slice());
+        let digest = self.vm().native_keccak256(&message);
+
+        if lp == Address::ZERO || self.recover_signer(digest, signature) != Some(lp) {
+            return Err(InvalidSignature {}.abi_encode());
+        }
+
+        let nonce = self.nonces.get(lp);
+        self.nonces.setter(lp).set(nonce + U256::from(1));
+        Ok(())
+    }
+
+    /// ecrecover over a 65-byte (r, s, v) signature; rejects malleable high-s values
+    fn recover_signer(&self, digest: B256, signature: &[u8]) -> Option<Address> {
+        if signature.len() != 65 {
+            return None;
+        }
+
+        let s = U256::from_be_slice(&signature[32..64]);
+        if s > SECP256K1_HALF_N {
+            return None;
+        }
+        let v = match signature[64] {
+            0 | 1 => signature[64] + 27,
+            27 | 28 => signature[64],
+            _ => return None,
+        };
+
+        let mut input = Vec::with_capacity(128);
+        input.extend_from_slice(digest.as_slice());
+        input.extend_from_slice(U256::from(v).to_be_bytes::<32>().as_slice());
+        input.extend_from_slice(&signature[..64]);
+
+        let ret = self.vm().static_call(&Call::new(), ECRECOVER, &input).ok()?;
+        if ret.len() != 32 {
+            return None;
+        }
+        let signer = Address::from_slice(&ret[12..]);
+        (signer != Address::ZERO).then_some(signer)
+    }
+
+    fn transfer_asset(&mut self, to: Address, amount: U256) -> Result<bool, Vec<u8>> {
+        let call = IERC20::transferCall { to, amount };
+        self.call_contract(self.asset.get(), &call)
+            .map(|ret| ret._0)
+            .ok_or_else(|| TransferFailed {}.abi_encode())
+    }
+
+    fn transfer_asset_from(&mut self, from: Address, to: Address, amount: U256) -> Result<bool, Vec<u8>> {
+        let call = IERC20::transferFromCall { from, to, amount };
+        self.call_contract(self.asset.get(), &call)
+            .map(|ret| ret._0)
+            .ok_or_else(|| TransferFailed {}.abi_encode())
+    }
+
+    fn asset_balance(&self, account: Address) -> Result<U256, Vec<u8>> {
+        let call = IERC20::balanceOfCall { account };
+        self.static_call_contract(self.asset.get(), &call)
+            .map(|ret| ret._0)
+            .ok_or_else(|| TransferFailed {}.abi_encode())
+    }
+
+    /// Marks the strategy position to market, realizing gains or losses in `total_assets`
+    fn sync_strategy(&mut self) -> Result<(), Vec<u8>> {
+        let strategy = self.strategy.get();
+        if strategy == Address::ZERO {
+            return Ok(());
+        }
+
+        let current_value = self
+            .static_call_contract(strategy, &IYieldStrategy::totalValueCall {})
+            .map(|ret| ret._0)
+            .ok_or_else(|| StrategyCallFailed {}.abi_encode())?;
+        let previous_value = self.strategy_deployed.get();
+        if current_value == previous_value {
+            return Ok(());
+        }
+
+        let total = self.total_assets.get();
+        if current_value > previous_value {
+            self.total_assets.set(total + (current_value - previous_value));
+        } else {
+            let loss = previous_value - current_value;
+            self.total_assets.set(if total > loss { total - loss } else { U256::ZERO });
+        }
+        self.strategy_deployed.set(current_value);
+
+        log(self.vm(), StrategySynced { strategy, previous_value, current_value });
+        Ok(())
+    }
+
+    fn deploy_to_strategy(&mut self, amount: U256) -> Result<(), Vec<u8>> {
+        let strategy = self.strategy.get();
+
+        let success = self.transfer_asset(strategy, amount)?;
+        if !success {
+            return Err(TransferFailed {}.abi_encode());
+        }
+
+        self.call_contract(strategy, &IYieldStrategy::depositCall { amount })
+            .ok_or_else(|| StrategyCallFailed {}.abi_encode())?;
+
+        self.strategy_deployed.set(self.strategy_deployed.get() + amount);
+        log(self.vm(), StrategyDeployed { strategy, amount });
+        Ok(())
+    }
+
+    /// Pulls up to `requested` back from the strategy, measured by the vault's token balance
+    fn recall_from_strategy(&mut self, requested: U256) -> Result<(), Vec<u8>> {
+        let strategy = self.strategy.get();
+        let vault = self.vm().contract_address();
+
+        let balance_before = self.asset_balance(vault)?;
+        self.call_contract(strategy, &IYieldStrategy::withdrawCall { amount: requested })
+            .ok_or_else(|| StrategyCallFailed {}.abi_encode())?;
+        let balance_after = self.asset_balance(vault)?;
+
+        let received = if balance_after > balance_before {
+            balance_after - balance_before
+        } else {
+            U256::ZERO
+        };
+        let deployed = self.strategy_deployed.get();
+        self.strategy_deployed.set(if deployed > received { deployed - received } else { U256::ZERO });
+
+        log(self.vm(), StrategyRecalled { strategy, requested, received });
+        Ok(())
+    }
+
+    /// Max principal (collateral × (multiplier - 1x)) and min rate for `circle`, per the RiskOracle
+    fn oracle_loan_terms(&self, circle: Address) -> Result<(U256, U256), Vec<u8>> {
+        let oracle = self.risk_oracle.get();
+        if oracle == Address::ZERO {
+            return Err(RiskOracleNotSet {}.abi_encode());
+        }
+
+        let (members, collateral) = self.circle_members_and_collateral(circle)?;
+        let terms = self
+            .static_call_contract(oracle, &IRiskOracle::getLeverageLevelCall { members })
+            .ok_or_else(|| RiskCheckFailed {}.abi_encode())?;
+
+        let (multiplier, min_rate_bps) = (terms._0, terms._1);
+        Ok((max_loan_for(collateral, multiplier), min_rate_bps))
+    }
+
+    fn circle_members_and_collateral(&self, circle: Address) -> Result<(Vec<Address>, U256), Vec<u8>> {
+        let members = self
+            .static_call_contract(circle, &ICircle::getMembersCall {})
+            .map(|ret| ret._0)
+            .ok_or_else(|| RiskCheckFailed {}.abi_encode())?;
+        let collateral = self
+            .static_call_contract(circle, &ICircle::totalCollateralCall {})
+            .map(|ret| ret._0)
+            .ok_or_else(|| RiskCheckFailed {}.abi_encode())?;
+        Ok((members, collateral))
+    }
+}
+
+/// Max principal for `collateral` at a leverage `multiplier` (100 = 1x): collateral * (multiplier - 1x)
+fn max_loan_for(collateral: U256, multiplier: U256) -> U256 {
+    if multiplier > U256::from(100) {
+        (collateral * (multiplier - U256::from(100))) / U256::from(100)
+    } else {
+        U256::ZERO
+    }
+}
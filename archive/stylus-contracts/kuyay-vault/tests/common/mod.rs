@@ -0,0 +1,753 @@
+//! Stateful mock host for vault tests.
+//!
+//! `TestVM` only returns canned data for external calls, which is not enough to
+//! check token balances moving during a flash loan. `MockHost` wraps a `TestVM`
+//! and routes external calls to in-memory mock contracts (ERC20 asset, flash
+//! borrowers, yield strategies, circles, risk oracle) so the vault sees the
+//! same state changes it would on-chain.
+
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use alloy_sol_types::{sol, SolCall, SolInterface};
+use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
+use kuyay_vault::{KuyayVault, ECRECOVER, EIP712_DOMAIN_TYPE, FLASH_LOAN_CALLBACK_SUCCESS};
+use stylus_sdk::alloy_primitives::{keccak256, Address, B256, U256};
+use stylus_sdk::testing::deploy::DeploymentAccess;
+use stylus_sdk::testing::*;
+
+sol! {
+    interface IERC20 {
+        function transferFrom(address from, address to, uint256 amount) external returns (bool);
+        function transfer(address to, uint256 amount) external returns (bool);
+        function balanceOf(address account) external view returns (uint256);
+    }
+
+    interface IERC3156FlashBorrower {
+        function onFlashLoan(address initiator, address token, uint256 amount, uint256 fee, bytes calldata data) external returns (bytes32);
+    }
+
+    interface IYieldStrategy {
+        function deposit(uint256 amount) external returns (uint256);
+        function withdraw(uint256 amount) external returns (uint256);
+        function totalValue() external view returns (uint256);
+    }
+
+    interface ICircle {
+        function getMembers() external view returns (address[] memory);
+        function totalCollateral() external view returns (uint256);
+    }
+
+    interface IRiskOracle {
+        function getLeverageLevel(address[] calldata members) external view returns (uint256, uint256);
+        function consumeQuote(uint256 quoteId, address[] calldata members) external returns (uint256, uint256, uint256, uint256);
+    }
+
+    interface ILoanPosition {
+        function mint(address to, uint256 tokenId) external;
+        function transferFrom(address from, address to, uint256 tokenId) external;
+    }
+}
+
+pub const OWNER: Address = Address::new([0x01; 20]);
+pub const TREASURY: Address = Address::new([0x02; 20]);
+pub const ASSET: Address = Address::new([0x03; 20]);
+pub const VAULT: Address = Address::new([0x04; 20]);
+pub const LP: Address = Address::new([0x05; 20]);
+pub const CIRCLE: Address = Address::new([0x06; 20]);
+pub const BORROWER: Address = Address::new([0x07; 20]);
+pub const STRANGER: Address = Address::new([0x08; 20]);
+pub const STRATEGY: Address = Address::new([0x0a; 20]);
+pub const ORACLE: Address = Address::new([0x0c; 20]);
+pub const POSITION_NFT: Address = Address::new([0x0d; 20]);
+pub const MEMBERS: [Address; 3] = [
+    Address::new([0x21; 20]),
+    Address::new([0x22; 20]),
+    Address::new([0x23; 20]),
+];
+
+/// How a mock flash borrower behaves inside `onFlashLoan`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BorrowerMode {
+    /// Approves principal + fee and returns the ERC-3156 magic value
+    Repay,
+    /// Returns the magic value but never approves the vault
+    NoApproval,
+    /// Approves but returns a wrong callback value
+    WrongReturn,
+    /// Reverts inside the callback
+    Revert,
+}
+
+/// How a mock yield strategy treats the funds it receives
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StrategyMode {
+    /// Holds deposits 1:1; value is its token balance
+    Honest,
+    /// Burns `loss_bps` of every deposit
+    Lossy { loss_bps: u64 },
+    /// Every call reverts
+    Broken,
+}
+
+/// Code a mock borrower runs against the vault from inside its callback
+pub type Reentry = Rc<dyn Fn(&MockHost)>;
+
+/// In-memory state shared by all mock contracts
+#[derive(Default)]
+pub struct World {
+    pub balances: HashMap<Address, U256>,
+    pub allowances: HashMap<(Address, Address), U256>,
+    pub borrowers: HashMap<Address, BorrowerMode>,
+    pub strategies: HashMap<Address, StrategyMode>,
+    /// Circle address -> (members, total collateral)
+    pub circles: HashMap<Address, (Vec<Address>, U256)>,
+    /// `getLeverageLevel` answer as (multiplier, rate bps); `None` reverts
+    pub oracle_terms: Option<(U256, U256)>,
+    /// Member lists the oracle was queried with
+    pub oracle_queries: Vec<Vec<Address>>,
+    /// Unused quotes by id: (members, multiplier, rate bps, amount, days)
+    pub oracle_quotes: HashMap<U256, (Vec<Address>, U256, U256, U256, U256)>,
+    /// Flash loans seen by borrowers: (initiator, amount, fee, data)
+    pub flash_callbacks: Vec<(Address, U256, U256, Vec<u8>)>,
+    /// When set, `transferFrom` reports success without moving funds
+    pub skim_transfer_from: bool,
+    /// When set, `transfer` and `transferFrom` return false without moving funds
+    pub refuse_transfers: bool,
+    /// Loan position NFT owners by token id
+    pub positions: HashMap<U256, Address>,
+    /// Runs inside `onFlashLoan`, as a borrower calling back into the vault would
+    pub flash_reentry: Option<Reentry>,
+}
+
+impl World {
+    pub fn balance(&self, account: Address) -> U256 {
+        self.balances.get(&account).copied().unwrap_or_default()
+    }
+
+    pub fn mint(&mut self, account: Address, amount: U256) {
+        let balance = self.balance(account);
+        self.balances.insert(account, balance + amount);
+    }
+
+    pub fn burn(&mut self, account: Address, amount: U256) {
+        let balance = self.balance(account);
+        self.balances.insert(account, balance - amount);
+    }
+
+    pub fn approve(&mut self, owner: Address, spender: Address, amount: U256) {
+        self.allowances.insert((owner, spender), amount);
+    }
+
+    fn move_tokens(&mut self, from: Address, to: Address, amount: U256) -> Result<(), Vec<u8>> {
+        let from_balance = self.balance(from);
+        if from_balance < amount {
+            return Err(b"ERC20: insufficient balance".to_vec());
+        }
+        self.balances.insert(from, from_balance - amount);
+        let to_balance = self.balance(to);
+        self.balances.insert(to, to_balance + amount);
+        Ok(())
+    }
+
+    fn call_token(&mut self, caller: Address, data: &[u8]) -> Result<Vec<u8>, Vec<u8>> {
+        match IERC20::IERC20Calls::abi_decode(data, true).map_err(|_| Vec::new())? {
+            IERC20::IERC20Calls::transfer(_) if self.refuse_transfers => {
+                Ok(IERC20::transferCall::abi_encode_returns(&(false,)))
+            }
+            IERC20::IERC20Calls::transferFrom(_) if self.refuse_transfers => {
+                Ok(IERC20::transferFromCall::abi_encode_returns(&(false,)))
+            }
+            IERC20::IERC20Calls::transfer(call) => {
+                self.move_tokens(caller, call.to, call.amount)?;
+                Ok(IERC20::transferCall::abi_encode_returns(&(true,)))
+            }
+            IERC20::IERC20Calls::transferFrom(call) => {
+                if self.skim_transfer_from {
+                    return Ok(IERC20::transferFromCall::abi_encode_returns(&(true,)));
+                }
+                let allowance = self
+                    .allowances
+                    .get(&(call.from, caller))
+                    .copied()
+                    .unwrap_or_default();
+                if allowance < call.amount {
+                    return Err(b"ERC20: insufficient allowance".to_vec());
+                }
+                self.move_tokens(call.from, call.to, call.amount)?;
+                if allowance != U256::MAX {
+                    self.allowances.insert((call.from, caller), allowance - call.amount);
+                }
+                Ok(IERC20::transferFromCall::abi_encode_returns(&(true,)))
+            }
+            IERC20::IERC20Calls::balanceOf(call) => {
+                Ok(IERC20::balanceOfCall::abi_encode_returns(&(self.balance(call.account),)))
+            }
+        }
+    }
+
+    fn call_borrower(
+        &mut self,
+        borrower: Address,
+        lender: Address,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Vec<u8>> {
+        let call = IERC3156FlashBorrower::onFlashLoanCall::abi_decode(data, true)
+            .map_err(|_| Vec::new())?;
+        let mode = self.borrowers[&borrower];
+        self.flash_callbacks
+            .push((call.initiator, call.amount, call.fee, call.data.to_vec()));
+
+        let repayment = call.amount + call.fee;
+        let magic = match mode {
+            BorrowerMode::Repay => {
+                self.approve(borrower, lender, repayment);
+                FLASH_LOAN_CALLBACK_SUCCESS
+            }
+            BorrowerMode::NoApproval => FLASH_LOAN_CALLBACK_SUCCESS,
+            BorrowerMode::WrongReturn => {
+                self.approve(borrower, lender, repayment);
+                B256::ZERO
+            }
+            BorrowerMode::Revert => return Err(b"borrower reverted".to_vec()),
+        };
+        Ok(IERC3156FlashBorrower::onFlashLoanCall::abi_encode_returns(&(magic,)))
+    }
+
+    fn call_strategy(
+        &mut self,
+        strategy: Address,
+        caller: Address,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Vec<u8>> {
+        let mode = self.strategies[&strategy];
+        if mode == StrategyMode::Broken {
+            return Err(b"strategy broken".to_vec());
+        }
+
+        match IYieldStrategy::IYieldStrategyCalls::abi_decode(data, true).map_err(|_| Vec::new())? {
+            IYieldStrategy::IYieldStrategyCalls::deposit(call) => {
+                if let StrategyMode::Lossy { loss_bps } = mode {
+                    let loss = call.amount * U256::from(loss_bps) / U256::from(10000);
+                    self.burn(strategy, loss);
+                }
+                Ok(IYieldStrategy::depositCall::abi_encode_returns(&(call.amount,)))
+            }
+            IYieldStrategy::IYieldStrategyCalls::withdraw(call) => {
+                let sent = call.amount.min(self.balance(strategy));
+                self.move_tokens(strategy, caller, sent)?;
+                Ok(IYieldStrategy::withdrawCall::abi_encode_returns(&(sent,)))
+            }
+            IYieldStrategy::IYieldStrategyCalls::totalValue(_) => {
+                Ok(IYieldStrategy::totalValueCall::abi_encode_returns(&(self.balance(strategy),)))
+            }
+        }
+    }
+
+    fn dispatch(&mut self, caller: Address, to: Address, data: &[u8]) -> Result<Vec<u8>, Vec<u8>> {
+        if to == ASSET {
+            return self.call_token(caller, data);
+        }
+        if self.borrowers.contains_key(&to) {
+            return self.call_borrower(to, caller, data);
+        }
+        if self.strategies.contains_key(&to) {
+            return self.call_strategy(to, caller, data);
+        }
+        if let Some((members, collateral)) = self.circles.get(&to) {
+            return match ICircle::ICircleCalls::abi_decode(data, true).map_err(|_| Vec::new())? {
+                ICircle::ICircleCalls::getMembers(_) => {
+                    Ok(ICircle::getMembersCall::abi_encode_returns(&(members.clone(),)))
+                }
+                ICircle::ICircleCalls::totalCollateral(_) => {
+                    Ok(ICircle::totalCollateralCall::abi_encode_returns(&(*collateral,)))
+                }
+            };
+        }
+        if to == ORACLE {
+            return self.call_oracle(data);
+        }
+        if to == POSITION_NFT {
+            return self.call_position_nft(caller, data);
+        }
+        if to == ECRECOVER {
+            return Ok(ecrecover(data));
+        }
+        Err(b"no mock contract at address".to_vec())
+    }
+
+    /// `getLeverageLevel` answers `oracle_terms`; a quote is consumed once, for its own members
+    fn call_oracle(&mut self, data: &[u8]) -> Result<Vec<u8>, Vec<u8>> {
+        match IRiskOracle::IRiskOracleCalls::abi_decode(data, true).map_err(|_| Vec::new())? {
+            IRiskOracle::IRiskOracleCalls::getLeverageLevel(call) => {
+                self.oracle_queries.push(call.members);
+                let (multiplier, rate) = self.oracle_terms.ok_or_else(|| b"oracle reverted".to_vec())?;
+                Ok(IRiskOracle::getLeverageLevelCall::abi_encode_returns(&(multiplier, rate)))
+            }
+            IRiskOracle::IRiskOracleCalls::consumeQuote(call) => {
+                let quote = self.oracle_quotes.get(&call.quoteId).ok_or_else(|| b"unknown quote".to_vec())?;
+                if quote.0 != call.members {
+                    return Err(b"members mismatch".to_vec());
+                }
+                let (_, multiplier, rate, amount, days) = self.oracle_quotes.remove(&call.quoteId).unwrap();
+                Ok(IRiskOracle::consumeQuoteCall::abi_encode_returns(&(multiplier, rate, amount, days)))
+            }
+        }
+    }
+
+    /// Minimal ERC-721: only the owner moves a token, mint rejects existing ids
+    fn call_position_nft(&mut self, caller: Address, data: &[u8]) -> Result<Vec<u8>, Vec<u8>> {
+        match ILoanPosition::ILoanPositionCalls::abi_decode(data, true).map_err(|_| Vec::new())? {
+            ILoanPosition::ILoanPositionCalls::mint(call) => {
+                if self.positions.contains_key(&call.tokenId) {
+                    return Err(b"token exists".to_vec());
+                }
+                self.positions.insert(call.tokenId, call.to);
+            }
+            ILoanPosition::ILoanPositionCalls::transferFrom(call) => {
+                if self.positions.get(&call.tokenId) != Some(&call.from) || caller != call.from {
+                    return Err(b"not owner".to_vec());
+                }
+                self.positions.insert(call.tokenId, call.to);
+            }
+        }
+        Ok(Vec::new())
+    }
+}
+
+/// `TestVM` plus a mock world that answers the vault's external calls
+#[derive(Clone)]
+pub struct MockHost {
+    pub vm: TestVM,
+    pub world: Rc<RefCell<World>>,
+}
+
+impl MockHost {
+    pub fn new() -> Self {
+        let vm = TestVM::new();
+        vm.set_contract_address(VAULT);
+        Self {
+            vm,
+            world: Rc::new(RefCell::new(World::default())),
+        }
+    }
+
+    pub fn balance(&self, account: Address) -> U256 {
+        self.world.borrow().balance(account)
+    }
+
+    pub fn mint(&self, account: Address, amount: U256) {
+        self.world.borrow_mut().mint(account, amount);
+    }
+
+    pub fn burn(&self, account: Address, amount: U256) {
+        self.world.borrow_mut().burn(account, amount);
+    }
+
+    pub fn approve(&self, owner: Address, spender: Address, amount: U256) {
+        self.world.borrow_mut().approve(owner, spender, amount);
+    }
+
+    pub fn add_borrower(&self, borrower: Address, mode: BorrowerMode) {
+        self.world.borrow_mut().borrowers.insert(borrower, mode);
+    }
+
+    pub fn add_strategy(&self, strategy: Address, mode: StrategyMode) {
+        self.world.borrow_mut().strategies.insert(strategy, mode);
+    }
+
+    pub fn on_flash_loan(&self, reenter: impl Fn(&MockHost) + 'static) {
+        self.world.borrow_mut().flash_reentry = Some(Rc::new(reenter));
+    }
+}
+
+/// ecrecover precompile: empty output when the signature does not recover
+fn ecrecover(input: &[u8]) -> Vec<u8> {
+    if input.len() != 128 || input[32..63].iter().any(|b| *b != 0) {
+        return Vec::new();
+    }
+    let recovery_id = match input[63] {
+        27 => RecoveryId::from_byte(0),
+        28 => RecoveryId::from_byte(1),
+        _ => None,
+    };
+    let (Some(recovery_id), Ok(signature)) = (recovery_id, Signature::from_slice(&input[64..])) else {
+        return Vec::new();
+    };
+    match VerifyingKey::recover_from_prehash(&input[..32], &signature, recovery_id) {
+        Ok(key) => key_address(&key).into_word().to_vec(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn key_address(key: &VerifyingKey) -> Address {
+    let point = key.to_encoded_point(false);
+    Address::from_slice(&keccak256(&point.as_bytes()[1..])[12..])
+}
+
+/// Deterministic test signer and its address
+pub fn signer(seed: u8) -> (SigningKey, Address) {
+    let key = SigningKey::from_slice(&[seed; 32]).unwrap();
+    let address = key_address(key.verifying_key());
+    (key, address)
+}
+
+/// EIP-712 domain separator computed independently of the vault
+pub fn domain_separator(host: &MockHost) -> B256 {
+    let mut encoded = Vec::new();
+    encoded.extend_from_slice(keccak256(EIP712_DOMAIN_TYPE).as_slice());
+    encoded.extend_from_slice(keccak256("KuyayVault").as_slice());
+    encoded.extend_from_slice(keccak256("1").as_slice());
+    encoded.extend_from_slice(&U256::from(host.vm.chain_id()).to_be_bytes::<32>());
+    encoded.extend_from_slice(VAULT.into_word().as_slice());
+    keccak256(encoded)
+}
+
+/// Signs `type_string` + ABI words under the vault's domain, as (r, s, v)
+pub fn sign_typed(host: &MockHost, key: &SigningKey, type_string: &str, fields: &[B256]) -> Vec<u8> {
+    let mut encoded = keccak256(type_string).to_vec();
+    for field in fields {
+        encoded.extend_from_slice(field.as_slice());
+    }
+    let struct_hash = keccak256(encoded);
+
+    let mut message = b"\x19\x01".to_vec();
+    message.extend_from_slice(domain_separator(host).as_slice());
+    message.extend_from_slice(struct_hash.as_slice());
+    let digest = keccak256(message);
+
+    let (signature, recovery_id) = key.sign_prehash_recoverable(digest.as_slice()).unwrap();
+    let mut bytes = signature.to_bytes().to_vec();
+    bytes.push(27 + recovery_id.to_byte());
+    bytes
+}
+
+/// Deploys and initializes a vault owned by `OWNER`
+pub fn setup_vault() -> (MockHost, KuyayVault) {
+    let host = MockHost::new();
+    let mut vault = KuyayVault::from(&host);
+    host.vm.set_sender(OWNER);
+    vault.initialize(ASSET, TREASURY).unwrap();
+    (host, vault)
+}
+
+/// Registers `CIRCLE` with `collateral` posted and an oracle quoting `(multiplier, rate_bps)`
+pub fn setup_circle(
+    host: &MockHost,
+    vault: &mut KuyayVault,
+    collateral: U256,
+    multiplier: u64,
+    rate_bps: u64,
+) {
+    {
+        let mut world = host.world.borrow_mut();
+        world.circles.insert(CIRCLE, (MEMBERS.to_vec(), collateral));
+        world.oracle_terms = Some((U256::from(multiplier), U256::from(rate_bps)));
+    }
+    host.vm.set_sender(OWNER);
+    vault.authorize_circle(CIRCLE).unwrap();
+    vault.set_risk_oracle(ORACLE).unwrap();
+}
+
+/// Registers and authorizes another circle sharing `MEMBERS`, for multi-loan tests
+pub fn add_circle(host: &MockHost, vault: &mut KuyayVault, circle: Address, collateral: U256) {
+    host.world
+        .borrow_mut()
+        .circles
+        .insert(circle, (MEMBERS.to_vec(), collateral));
+    host.vm.set_sender(OWNER);
+    vault.authorize_circle(circle).unwrap();
+}
+
+/// Mints `amount` to `lp`, approves the vault and deposits it
+pub fn deposit_as(host: &MockHost, vault: &mut KuyayVault, lp: Address, amount: U256) -> U256 {
+    host.mint(lp, amount);
+    host.approve(lp, VAULT, U256::MAX);
+    host.vm.set_sender(lp);
+    vault.deposit(amount).unwrap()
+}
+
+/// Vault with 100k deposited by `LP`
+pub fn funded_vault() -> (MockHost, KuyayVault) {
+    let (host, mut vault) = setup_vault();
+    deposit_as(&host, &mut vault, LP, U256::from(100_000));
+    (host, vault)
+}
+
+/// Block time at which `vault_with_loan` lends
+pub const START: u64 = 1_000_000;
+
+/// Funded vault where `CIRCLE` borrowed 20k for a year at 10% against `collateral`, at `START`
+pub fn vault_with_loan(collateral: u64) -> (MockHost, KuyayVault) {
+    let (host, mut vault) = funded_vault();
+    setup_circle(&host, &mut vault, U256::from(collateral), 300, 1000);
+
+    host.vm.set_block_timestamp(START);
+    borrow(&host, &mut vault, CIRCLE);
+    (host, vault)
+}
+
+/// `circle` borrows 20k for a year at 10%
+pub fn borrow(host: &MockHost, vault: &mut KuyayVault, circle: Address) {
+    host.vm.set_sender(circle);
+    vault
+        .request_loan(U256::from(20_000), U256::from(365), U256::from(1000))
+        .unwrap();
+}
+
+/// Mints `amount` to `circle`, approves the vault and repays it
+pub fn repay_as(host: &MockHost, vault: &mut KuyayVault, circle: Address, amount: u64) {
+    host.mint(circle, U256::from(amount));
+    host.approve(circle, VAULT, U256::MAX);
+    host.vm.set_sender(circle);
+    vault.repay_loan(U256::from(amount)).unwrap();
+}
+
+impl Host for MockHost {}
+
+impl CryptographyAccess for MockHost {
+    fn native_keccak256(&self, input: &[u8]) -> B256 {
+        self.vm.native_keccak256(input)
+    }
+}
+
+impl CalldataAccess for MockHost {
+    fn read_args(&self, len: usize) -> Vec<u8> {
+        self.vm.read_args(len)
+    }
+    fn read_return_data(&self, offset: usize, size: Option<usize>) -> Vec<u8> {
+        self.vm.read_return_data(offset, size)
+    }
+    fn return_data_size(&self) -> usize {
+        self.vm.return_data_size()
+    }
+    fn write_result(&self, data: &[u8]) {
+        self.vm.write_result(data)
+    }
+}
+
+unsafe impl UnsafeDeploymentAccess for MockHost {
+    unsafe fn create1(
+        &self,
+        code: *const u8,
+        code_len: usize,
+        endowment: *const u8,
+        contract: *mut u8,
+        revert_data_len: *mut usize,
+    ) {
+        self.vm
+            .create1(code, code_len, endowment, contract, revert_data_len)
+    }
+    unsafe fn create2(
+        &self,
+        code: *const u8,
+        code_len: usize,
+        endowment: *const u8,
+        salt: *const u8,
+        contract: *mut u8,
+        revert_data_len: *mut usize,
+    ) {
+        self.vm
+            .create2(code, code_len, endowment, salt, contract, revert_data_len)
+    }
+}
+
+impl StorageAccess for MockHost {
+    fn storage_load_bytes32(&self, key: U256) -> B256 {
+        self.vm.storage_load_bytes32(key)
+    }
+    unsafe fn storage_cache_bytes32(&self, key: U256, value: B256) {
+        self.vm.storage_cache_bytes32(key, value)
+    }
+    fn flush_cache(&self, clear: bool) {
+        self.vm.flush_cache(clear)
+    }
+}
+
+unsafe impl UnsafeCallAccess for MockHost {
+    unsafe fn call_contract(
+        &self,
+        to: *const u8,
+        data: *const u8,
+        data_len: usize,
+        value: *const u8,
+        gas: u64,
+        outs_len: &mut usize,
+    ) -> u8 {
+        self.vm
+            .call_contract(to, data, data_len, value, gas, outs_len)
+    }
+    unsafe fn static_call_contract(
+        &self,
+        to: *const u8,
+        data: *const u8,
+        data_len: usize,
+        gas: u64,
+        outs_len: &mut usize,
+    ) -> u8 {
+        self.vm
+            .static_call_contract(to, data, data_len, gas, outs_len)
+    }
+    unsafe fn delegate_call_contract(
+        &self,
+        to: *const u8,
+        data: *const u8,
+        data_len: usize,
+        gas: u64,
+        outs_len: &mut usize,
+    ) -> u8 {
+        self.vm
+            .delegate_call_contract(to, data, data_len, gas, outs_len)
+    }
+}
+
+impl BlockAccess for MockHost {
+    fn block_basefee(&self) -> U256 {
+        self.vm.block_basefee()
+    }
+    fn block_coinbase(&self) -> Address {
+        self.vm.block_coinbase()
+    }
+    fn block_number(&self) -> u64 {
+        self.vm.block_number()
+    }
+    fn block_timestamp(&self) -> u64 {
+        self.vm.block_timestamp()
+    }
+    fn block_gas_limit(&self) -> u64 {
+        self.vm.block_gas_limit()
+    }
+}
+
+impl ChainAccess for MockHost {
+    fn chain_id(&self) -> u64 {
+        self.vm.chain_id()
+    }
+}
+
+impl AccountAccess for MockHost {
+    fn balance(&self, account: Address) -> U256 {
+        self.vm.balance(account)
+    }
+    fn contract_address(&self) -> Address {
+        self.vm.contract_address()
+    }
+    fn code(&self, account: Address) -> Vec<u8> {
+        self.vm.code(account)
+    }
+    fn code_size(&self, account: Address) -> usize {
+        self.vm.code_size(account)
+    }
+    fn code_hash(&self, account: Address) -> B256 {
+        self.vm.code_hash(account)
+    }
+}
+
+impl MemoryAccess for MockHost {
+    fn pay_for_memory_grow(&self, pages: u16) {
+        self.vm.pay_for_memory_grow(pages)
+    }
+}
+
+impl MessageAccess for MockHost {
+    fn msg_sender(&self) -> Address {
+        self.vm.msg_sender()
+    }
+    fn msg_reentrant(&self) -> bool {
+        self.vm.msg_reentrant()
+    }
+    fn msg_value(&self) -> U256 {
+        self.vm.msg_value()
+    }
+    fn tx_origin(&self) -> Address {
+        self.vm.tx_origin()
+    }
+}
+
+impl MeteringAccess for MockHost {
+    fn evm_gas_left(&self) -> u64 {
+        self.vm.evm_gas_left()
+    }
+    fn evm_ink_left(&self) -> u64 {
+        self.vm.evm_ink_left()
+    }
+    fn tx_gas_price(&self) -> U256 {
+        self.vm.tx_gas_price()
+    }
+    fn tx_ink_price(&self) -> u32 {
+        self.vm.tx_ink_price()
+    }
+}
+
+impl CallAccess for MockHost {
+    fn static_call(
+        &self,
+        _context: &dyn StaticCallContext,
+        to: Address,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let caller = self.vm.contract_address();
+        self.world
+            .borrow_mut()
+            .dispatch(caller, to, data)
+            .map_err(Error::Revert)
+    }
+
+    unsafe fn delegate_call(
+        &self,
+        context: &dyn MutatingCallContext,
+        to: Address,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        self.vm.delegate_call(context, to, data)
+    }
+
+    fn call(
+        &self,
+        _context: &dyn MutatingCallContext,
+        to: Address,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let caller = self.vm.contract_address();
+        let reentry = {
+            let world = self.world.borrow();
+            world.flash_reentry.clone().filter(|_| world.borrowers.contains_key(&to))
+        };
+        if let Some(reenter) = reentry {
+            reenter(self);
+        }
+        self.world
+            .borrow_mut()
+            .dispatch(caller, to, data)
+            .map_err(Error::Revert)
+    }
+}
+
+impl DeploymentAccess for MockHost {
+    unsafe fn deploy(
+        &self,
+        code: &[u8],
+        endowment: U256,
+        salt: Option<B256>,
+    ) -> Result<Address, Vec<u8>> {
+        self.vm.deploy(code, endowment, salt)
+    }
+}
+
+impl LogAccess for MockHost {
+    fn emit_log(&self, input: &[u8], num_topics: usize) {
+        self.vm.emit_log(input, num_topics)
+    }
+    fn raw_log(&self, topics: &[B256], data: &[u8]) -> Result<(), &'static str> {
+        self.vm.raw_log(topics, data)
+    }
+}
+
+impl ValueTransfer for MockHost {
+    fn transfer_eth(&self, to: Address, amount: U256) -> Result<(), Vec<u8>> {
+        self.vm.transfer_eth(to, amount)
+    }
+}
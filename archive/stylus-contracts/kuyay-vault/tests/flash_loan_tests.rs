@@ -0,0 +1,223 @@
+//! ERC-3156 flash loan tests against mock borrower contracts.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::{SolError, SolEvent};
+    use kuyay_vault::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use stylus_sdk::abi::Bytes;
+    use stylus_sdk::alloy_primitives::{keccak256, Address, U256};
+
+    #[test]
+    fn test_callback_constant_matches_spec() {
+        assert_eq!(
+            FLASH_LOAN_CALLBACK_SUCCESS,
+            keccak256("ERC3156FlashBorrower.onFlashLoan")
+        );
+    }
+
+    #[test]
+    fn test_max_flash_loan_excludes_circle_loans() {
+        let (host, mut vault) = funded_vault();
+        assert_eq!(vault.max_flash_loan(ASSET), U256::from(100_000));
+        assert_eq!(vault.max_flash_loan(STRANGER), U256::ZERO);
+
+        setup_circle(&host, &mut vault, U256::from(20_000), 300, 1000);
+        host.vm.set_sender(CIRCLE);
+        vault
+            .request_loan(U256::from(40_000), U256::from(30), U256::from(1000))
+            .unwrap();
+
+        assert_eq!(vault.max_flash_loan(ASSET), U256::from(60_000));
+    }
+
+    #[test]
+    fn test_flash_fee() {
+        let (_host, vault) = funded_vault();
+        // Default fee is 9 bps
+        assert_eq!(vault.flash_fee(ASSET, U256::from(100_000)).unwrap(), U256::from(90));
+        assert_eq!(
+            vault.flash_fee(STRANGER, U256::from(100_000)),
+            Err(UnsupportedToken {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_flash_loan_repaid_with_fee() {
+        let (host, mut vault) = funded_vault();
+        host.add_borrower(BORROWER, BorrowerMode::Repay);
+        host.mint(BORROWER, U256::from(90)); // enough to cover the fee
+
+        let lp_balance_before = vault.balance_of(LP);
+
+        host.vm.set_sender(STRANGER);
+        let result = vault.flash_loan(
+            BORROWER,
+            ASSET,
+            U256::from(100_000),
+            Bytes::from(vec![0xab, 0xcd]),
+        );
+        assert_eq!(result, Ok(true));
+
+        // Callback saw the initiator, amount, fee and data
+        let callbacks = host.world.borrow().flash_callbacks.clone();
+        assert_eq!(
+            callbacks,
+            vec![(STRANGER, U256::from(100_000), U256::from(90), vec![0xab, 0xcd])]
+        );
+
+        // Vault holds principal + fee, borrower is back to zero
+        assert_eq!(host.balance(VAULT), U256::from(100_090));
+        assert_eq!(host.balance(BORROWER), U256::ZERO);
+
+        // Fee accrues to LPs
+        assert_eq!(vault.total_assets(), U256::from(100_090));
+        assert_eq!(vault.total_flash_fees_earned(), U256::from(90));
+        assert!(vault.balance_of(LP) > lp_balance_before);
+    }
+
+    #[test]
+    fn test_flash_loan_emits_event() {
+        let (host, mut vault) = funded_vault();
+        host.add_borrower(BORROWER, BorrowerMode::Repay);
+        host.mint(BORROWER, U256::from(9));
+
+        host.vm.set_sender(STRANGER);
+        vault
+            .flash_loan(BORROWER, ASSET, U256::from(10_000), Bytes::from(vec![]))
+            .unwrap();
+
+        let logs = host.vm.get_emitted_logs();
+        let (topics, data) = logs.last().unwrap();
+        assert_eq!(topics[0], FlashLoan::SIGNATURE_HASH);
+        assert_eq!(topics[1], BORROWER.into_word());
+        assert_eq!(topics[2], ASSET.into_word());
+        let event = FlashLoan::decode_raw_log(topics.iter().copied(), data, true).unwrap();
+        assert_eq!(event.amount, U256::from(10_000));
+        assert_eq!(event.fee, U256::from(9));
+    }
+
+    #[test]
+    fn test_flash_loan_without_repayment_fails() {
+        let (host, mut vault) = funded_vault();
+        host.add_borrower(BORROWER, BorrowerMode::NoApproval);
+
+        host.vm.set_sender(STRANGER);
+        let result = vault.flash_loan(BORROWER, ASSET, U256::from(1_000), Bytes::from(vec![]));
+        assert_eq!(result, Err(FlashLoanNotRepaid {}.abi_encode()));
+    }
+
+    #[test]
+    fn test_flash_loan_without_fee_fails() {
+        let (host, mut vault) = funded_vault();
+        // Approves principal + fee but only holds the principal
+        host.add_borrower(BORROWER, BorrowerMode::Repay);
+
+        host.vm.set_sender(STRANGER);
+        let result = vault.flash_loan(BORROWER, ASSET, U256::from(100_000), Bytes::from(vec![]));
+        assert_eq!(result, Err(FlashLoanNotRepaid {}.abi_encode()));
+    }
+
+    #[test]
+    fn test_balance_check_catches_false_transfer() {
+        let (host, mut vault) = funded_vault();
+        host.add_borrower(BORROWER, BorrowerMode::Repay);
+        host.mint(BORROWER, U256::from(90));
+        host.world.borrow_mut().skim_transfer_from = true;
+
+        host.vm.set_sender(STRANGER);
+        let result = vault.flash_loan(BORROWER, ASSET, U256::from(100_000), Bytes::from(vec![]));
+        assert_eq!(result, Err(FlashLoanNotRepaid {}.abi_encode()));
+    }
+
+    #[test]
+    fn test_flash_loan_bad_callback() {
+        let (host, mut vault) = funded_vault();
+        host.add_borrower(BORROWER, BorrowerMode::WrongReturn);
+        let other = Address::new([0x09; 20]);
+        host.add_borrower(other, BorrowerMode::Revert);
+
+        host.vm.set_sender(STRANGER);
+        let result = vault.flash_loan(BORROWER, ASSET, U256::from(1_000), Bytes::from(vec![]));
+        assert_eq!(result, Err(FlashLoanCallbackFailed {}.abi_encode()));
+
+        let result = vault.flash_loan(other, ASSET, U256::from(1_000), Bytes::from(vec![]));
+        assert_eq!(result, Err(FlashLoanCallbackFailed {}.abi_encode()));
+    }
+
+    #[test]
+    fn test_borrower_cannot_reenter() {
+        let (host, mut vault) = funded_vault();
+        host.add_borrower(BORROWER, BorrowerMode::Repay);
+        host.mint(BORROWER, U256::from(90));
+
+        // Shares minted inside the callback would be priced without the fee
+        let results = Rc::new(RefCell::new(vec![]));
+        let seen = results.clone();
+        host.on_flash_loan(move |host| {
+            let mut vault = KuyayVault::from(host);
+            host.approve(BORROWER, VAULT, U256::MAX);
+            host.vm.set_sender(BORROWER);
+            seen.borrow_mut().push(vault.deposit(U256::from(50_000)).map(|_| ()));
+            seen.borrow_mut().push(vault.flash_loan(BORROWER, ASSET, U256::from(1), Bytes::from(vec![])).map(|_| ()));
+            host.vm.set_sender(LP);
+            seen.borrow_mut().push(vault.withdraw(U256::from(1_000)));
+        });
+
+        host.vm.set_sender(STRANGER);
+        assert_eq!(vault.flash_loan(BORROWER, ASSET, U256::from(50_000), Bytes::from(vec![])), Ok(true));
+        assert_eq!(*results.borrow(), vec![Err(FlashLoanInProgress {}.abi_encode()); 3]);
+        assert_eq!(vault.total_shares(), U256::from(100_000));
+
+        // The guard is released afterwards, also when the loan fails
+        host.world.borrow_mut().flash_reentry = None;
+        host.add_borrower(BORROWER, BorrowerMode::NoApproval);
+        host.vm.set_sender(STRANGER);
+        assert_eq!(
+            vault.flash_loan(BORROWER, ASSET, U256::from(1_000), Bytes::from(vec![])),
+            Err(FlashLoanNotRepaid {}.abi_encode())
+        );
+        deposit_as(&host, &mut vault, LP, U256::from(1_000));
+    }
+
+    #[test]
+    fn test_flash_loan_limits() {
+        let (host, mut vault) = funded_vault();
+        host.add_borrower(BORROWER, BorrowerMode::Repay);
+
+        host.vm.set_sender(STRANGER);
+        let result = vault.flash_loan(BORROWER, ASSET, U256::from(100_001), Bytes::from(vec![]));
+        assert_eq!(result, Err(InsufficientLiquidity {}.abi_encode()));
+
+        let result = vault.flash_loan(BORROWER, STRANGER, U256::from(1_000), Bytes::from(vec![]));
+        assert_eq!(result, Err(UnsupportedToken {}.abi_encode()));
+
+        let result = vault.flash_loan(BORROWER, ASSET, U256::ZERO, Bytes::from(vec![]));
+        assert_eq!(result, Err(InvalidAmount {}.abi_encode()));
+    }
+
+    #[test]
+    fn test_set_flash_fee() {
+        let (host, mut vault) = funded_vault();
+
+        host.vm.set_sender(STRANGER);
+        assert_eq!(
+            vault.set_flash_fee(U256::from(50)),
+            Err(Unauthorized {}.abi_encode())
+        );
+
+        host.vm.set_sender(OWNER);
+        assert_eq!(
+            vault.set_flash_fee(U256::from(1001)),
+            Err(InvalidParameter {}.abi_encode())
+        );
+
+        vault.set_flash_fee(U256::from(50)).unwrap();
+        assert_eq!(vault.flash_fee_bps(), U256::from(50));
+        assert_eq!(vault.flash_fee(ASSET, U256::from(10_000)).unwrap(), U256::from(50));
+    }
+}
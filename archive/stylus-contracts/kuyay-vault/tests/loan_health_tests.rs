@@ -0,0 +1,156 @@
+//! Loan health factor and early-warning tests.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::{SolError, SolEvent};
+    use kuyay_vault::*;
+    use stylus_sdk::alloy_primitives::{B256, U256};
+
+    const HALF_YEAR: u64 = 31_536_000 / 2;
+
+    fn count_logs(host: &MockHost, signature: B256) -> usize {
+        host.vm
+            .get_emitted_logs()
+            .iter()
+            .filter(|(topics, _)| topics[0] == signature)
+            .count()
+    }
+
+    #[test]
+    fn test_health_factor_at_origination() {
+        let (_host, vault) = vault_with_loan(10_000);
+        // 10k collateral against 20k debt, nothing overdue yet
+        assert_eq!(vault.health_factor(CIRCLE).unwrap(), U256::from(5000));
+
+        let (health, collateral, remaining, expected, paid, to_maturity) =
+            vault.get_loan_health(CIRCLE).unwrap();
+        assert_eq!(health, U256::from(5000));
+        assert_eq!(collateral, U256::from(10_000));
+        assert_eq!(remaining, U256::from(20_000));
+        assert_eq!(expected, U256::ZERO);
+        assert_eq!(paid, U256::ZERO);
+        assert_eq!(to_maturity, U256::from(365 * 86400));
+    }
+
+    #[test]
+    fn test_missed_schedule_lowers_health() {
+        let (host, vault) = vault_with_loan(10_000);
+        host.vm.set_block_timestamp(START + HALF_YEAR);
+
+        // Debt 21k plus 11k behind schedule
+        let (health, _, remaining, expected, _, to_maturity) =
+            vault.get_loan_health(CIRCLE).unwrap();
+        assert_eq!(remaining, U256::from(21_000));
+        assert_eq!(expected, U256::from(11_000));
+        assert_eq!(health, U256::from(3125));
+        assert_eq!(to_maturity, U256::from(HALF_YEAR));
+    }
+
+    #[test]
+    fn test_health_factor_requires_active_loan() {
+        let (_host, vault) = vault_with_loan(10_000);
+        assert_eq!(
+            vault.health_factor(STRANGER),
+            Err(NoActiveLoan {}.abi_encode())
+        );
+        assert_eq!(
+            vault.get_loan_health(STRANGER).map(|h| h.0),
+            Err(NoActiveLoan {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_at_risk_loans() {
+        let (host, vault) = vault_with_loan(10_000);
+        assert_eq!(vault.get_loan_count(), U256::from(1));
+
+        let (circles, _) = vault.at_risk_loans(U256::from(5000), U256::ZERO, U256::from(10));
+        assert!(circles.is_empty());
+
+        host.vm.set_block_timestamp(START + HALF_YEAR);
+        let (circles, factors) =
+            vault.at_risk_loans(U256::from(5000), U256::ZERO, U256::from(10));
+        assert_eq!(circles, vec![CIRCLE]);
+        assert_eq!(factors, vec![U256::from(3125)]);
+
+        // Past the end of the registry
+        let (circles, _) = vault.at_risk_loans(U256::from(5000), U256::from(1), U256::from(10));
+        assert!(circles.is_empty());
+        let (circles, _) = vault.at_risk_loans(U256::from(5000), U256::ZERO, U256::ZERO);
+        assert!(circles.is_empty());
+    }
+
+    #[test]
+    fn test_warning_emitted_once_on_crossing() {
+        let (host, mut vault) = vault_with_loan(10_000);
+
+        host.vm.set_sender(STRANGER);
+        vault.check_loan_health(CIRCLE).unwrap();
+        assert_eq!(count_logs(&host, LoanHealthWarning::SIGNATURE_HASH), 0);
+
+        host.vm.set_block_timestamp(START + HALF_YEAR);
+        assert_eq!(vault.check_loan_health(CIRCLE).unwrap(), U256::from(3125));
+        vault.check_loan_health(CIRCLE).unwrap();
+
+        assert_eq!(count_logs(&host, LoanHealthWarning::SIGNATURE_HASH), 1);
+        let logs = host.vm.get_emitted_logs();
+        let (topics, data) = logs
+            .iter()
+            .find(|(topics, _)| topics[0] == LoanHealthWarning::SIGNATURE_HASH)
+            .unwrap();
+        let event = LoanHealthWarning::decode_raw_log(topics.iter().copied(), data, true).unwrap();
+        assert_eq!(event.circle, CIRCLE);
+        assert_eq!(event.health_factor_bps, U256::from(3125));
+    }
+
+    #[test]
+    fn test_repayment_recovers_health() {
+        let (host, mut vault) = vault_with_loan(10_000);
+        host.vm.set_block_timestamp(START + HALF_YEAR);
+        host.vm.set_sender(STRANGER);
+        vault.check_loan_health(CIRCLE).unwrap();
+
+        host.mint(CIRCLE, U256::from(6_000));
+        host.approve(CIRCLE, VAULT, U256::MAX);
+        host.vm.set_sender(CIRCLE);
+        vault.repay_loan(U256::from(6_000)).unwrap();
+
+        // Back on schedule: 15k remaining + 5k overdue
+        assert_eq!(vault.health_factor(CIRCLE).unwrap(), U256::from(5000));
+        assert_eq!(count_logs(&host, LoanHealthRecovered::SIGNATURE_HASH), 1);
+    }
+
+    #[test]
+    fn test_unreadable_collateral_counts_as_zero() {
+        let (host, vault) = vault_with_loan(10_000);
+        host.world.borrow_mut().circles.remove(&CIRCLE);
+        assert_eq!(vault.health_factor(CIRCLE).unwrap(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_set_health_warning_threshold() {
+        let (host, mut vault) = vault_with_loan(10_000);
+        assert_eq!(vault.health_warning_threshold_bps(), U256::from(5000));
+
+        host.vm.set_sender(STRANGER);
+        assert_eq!(
+            vault.set_health_warning_threshold(U256::from(12_000)),
+            Err(Unauthorized {}.abi_encode())
+        );
+
+        host.vm.set_sender(OWNER);
+        assert_eq!(
+            vault.set_health_warning_threshold(U256::ZERO),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        vault.set_health_warning_threshold(U256::from(12_000)).unwrap();
+        assert_eq!(vault.health_warning_threshold_bps(), U256::from(12_000));
+
+        // A healthy-looking loan now sits under the stricter threshold
+        vault.check_loan_health(CIRCLE).unwrap();
+        assert_eq!(count_logs(&host, LoanHealthWarning::SIGNATURE_HASH), 1);
+    }
+}
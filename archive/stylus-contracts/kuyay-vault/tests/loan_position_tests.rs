@@ -0,0 +1,249 @@
+//! Loan position NFT sale and cash-flow routing tests.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::{SolError, SolEvent};
+    use kuyay_vault::*;
+    use stylus_sdk::alloy_primitives::{Address, U256};
+
+    const BUYER: Address = Address::new([0x41; 20]);
+
+    /// 20k loan to `CIRCLE` with the position NFT configured
+    fn vault_with_position() -> (MockHost, KuyayVault) {
+        let (host, mut vault) = funded_vault();
+        setup_circle(&host, &mut vault, U256::from(20_000), 300, 1000);
+        vault.set_loan_position_nft(POSITION_NFT).unwrap();
+
+        borrow(&host, &mut vault, CIRCLE);
+        (host, vault)
+    }
+
+    fn sell_position(host: &MockHost, vault: &mut KuyayVault, price: u64) {
+        host.vm.set_sender(OWNER);
+        vault.list_loan_position(U256::from(1), U256::from(price)).unwrap();
+
+        host.mint(BUYER, U256::from(price));
+        host.approve(BUYER, VAULT, U256::MAX);
+        host.vm.set_sender(BUYER);
+        vault
+            .buy_loan_position(U256::from(1), U256::from(price))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_position_minted_to_vault() {
+        let (host, vault) = vault_with_position();
+        assert_eq!(host.world.borrow().positions.get(&U256::from(1)), Some(&VAULT));
+        assert_eq!(
+            vault.get_loan_position(U256::from(1)),
+            (CIRCLE, VAULT, false, U256::ZERO)
+        );
+        assert_eq!(vault.get_cash_flow_route(CIRCLE), (U256::from(1), VAULT));
+    }
+
+    #[test]
+    fn test_sale_moves_loan_off_balance_sheet() {
+        let (host, mut vault) = vault_with_position();
+        sell_position(&host, &mut vault, 19_000);
+
+        assert_eq!(host.world.borrow().positions.get(&U256::from(1)), Some(&BUYER));
+        assert_eq!(
+            vault.get_loan_position(U256::from(1)),
+            (CIRCLE, BUYER, true, U256::ZERO)
+        );
+        assert_eq!(vault.get_cash_flow_route(CIRCLE), (U256::from(1), BUYER));
+
+        // Sold at a 1k discount to book
+        assert_eq!(vault.total_loaned(), U256::ZERO);
+        assert_eq!(vault.total_assets(), U256::from(99_000));
+        assert_eq!(host.balance(VAULT), U256::from(99_000));
+        assert_eq!(vault.idle_liquidity(), U256::from(99_000));
+
+        let logs = host.vm.get_emitted_logs();
+        let (topics, data) = logs.last().unwrap();
+        assert_eq!(topics[0], LoanPositionSold::SIGNATURE_HASH);
+        let event = LoanPositionSold::decode_raw_log(topics.iter().copied(), data, true).unwrap();
+        assert_eq!(event.buyer, BUYER);
+        assert_eq!(event.price, U256::from(19_000));
+        assert_eq!(event.book_value, U256::from(20_000));
+    }
+
+    #[test]
+    fn test_repayments_route_to_holder() {
+        let (host, mut vault) = vault_with_position();
+        sell_position(&host, &mut vault, 19_000);
+
+        repay_as(&host, &mut vault, CIRCLE, 5_000);
+        assert_eq!(host.balance(BUYER), U256::from(5_000));
+        assert_eq!(host.balance(VAULT), U256::from(99_000));
+
+        // Secondary transfer reported by the NFT's hook
+        host.vm.set_sender(POSITION_NFT);
+        vault
+            .on_loan_position_transfer(BUYER, BORROWER, U256::from(1))
+            .unwrap();
+        assert_eq!(vault.get_cash_flow_route(CIRCLE), (U256::from(1), BORROWER));
+
+        repay_as(&host, &mut vault, CIRCLE, 15_000);
+        assert_eq!(host.balance(BORROWER), U256::from(15_000));
+        assert_eq!(vault.get_loan_status(CIRCLE), LOAN_STATUS_REPAID);
+        assert_eq!(vault.total_loaned(), U256::ZERO);
+        assert_eq!(vault.total_assets(), U256::from(99_000));
+    }
+
+    #[test]
+    fn test_sold_loan_default_does_not_hit_vault() {
+        let (host, mut vault) = vault_with_position();
+        sell_position(&host, &mut vault, 19_000);
+
+        host.vm.set_sender(OWNER);
+        vault.liquidate_circle(CIRCLE, U256::ZERO).unwrap();
+        assert_eq!(vault.total_assets(), U256::from(99_000));
+        assert_eq!(vault.total_loaned(), U256::ZERO);
+
+        // Restructuring a sold loan is not allowed either
+        let (host, mut vault) = vault_with_position();
+        sell_position(&host, &mut vault, 19_000);
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.extend_loan(U256::from(30)),
+            Err(LoanPositionSoldAlready {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_liquidation_recoveries_go_to_holder() {
+        let (host, mut vault) = vault_with_position();
+        sell_position(&host, &mut vault, 19_000);
+        let vault_balance = host.balance(VAULT);
+
+        // Seized collateral reaches the vault before the liquidation is recorded
+        host.mint(VAULT, U256::from(25_000));
+        host.vm.set_sender(OWNER);
+        vault.liquidate_circle(CIRCLE, U256::from(25_000)).unwrap();
+
+        // The holder gets up to the 20k owed, the vault's books are untouched
+        assert_eq!(host.balance(BUYER), U256::from(20_000));
+        assert_eq!(host.balance(VAULT), vault_balance + U256::from(5_000));
+        assert_eq!(vault.total_assets(), U256::from(99_000));
+        assert_eq!(vault.get_loan_status(CIRCLE), LOAN_STATUS_LIQUIDATED);
+    }
+
+    #[test]
+    fn test_transfer_hook_guards() {
+        let (host, mut vault) = vault_with_position();
+
+        host.vm.set_sender(STRANGER);
+        assert_eq!(
+            vault.on_loan_position_transfer(VAULT, STRANGER, U256::from(1)),
+            Err(Unauthorized {}.abi_encode())
+        );
+
+        // Vault-held positions only leave through a sale
+        host.vm.set_sender(POSITION_NFT);
+        assert_eq!(
+            vault.on_loan_position_transfer(VAULT, STRANGER, U256::from(1)),
+            Err(LoanPositionUnavailable {}.abi_encode())
+        );
+
+        sell_position(&host, &mut vault, 19_000);
+        host.vm.set_sender(POSITION_NFT);
+        assert_eq!(
+            vault.on_loan_position_transfer(BUYER, VAULT, U256::from(1)),
+            Err(InvalidAddress {}.abi_encode())
+        );
+        assert_eq!(
+            vault.on_loan_position_transfer(STRANGER, BORROWER, U256::from(1)),
+            Err(LoanPositionUnavailable {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_listing_and_purchase_validation() {
+        let (host, mut vault) = vault_with_position();
+
+        host.vm.set_sender(BUYER);
+        assert_eq!(
+            vault.buy_loan_position(U256::from(1), U256::MAX),
+            Err(LoanPositionUnavailable {}.abi_encode())
+        );
+        assert_eq!(
+            vault.list_loan_position(U256::from(1), U256::from(19_000)),
+            Err(Unauthorized {}.abi_encode())
+        );
+
+        host.vm.set_sender(OWNER);
+        assert_eq!(
+            vault.list_loan_position(U256::from(2), U256::from(19_000)),
+            Err(LoanPositionUnavailable {}.abi_encode())
+        );
+        vault.list_loan_position(U256::from(1), U256::from(19_000)).unwrap();
+        assert_eq!(vault.get_loan_position(U256::from(1)).3, U256::from(19_000));
+
+        host.vm.set_sender(BUYER);
+        assert_eq!(
+            vault.buy_loan_position(U256::from(1), U256::from(18_000)),
+            Err(InvalidAmount {}.abi_encode())
+        );
+        // No funds approved
+        assert_eq!(
+            vault.buy_loan_position(U256::from(1), U256::from(19_000)),
+            Err(TransferFailed {}.abi_encode())
+        );
+
+        // The asset answers false instead of reverting
+        host.mint(BUYER, U256::from(19_000));
+        host.approve(BUYER, VAULT, U256::MAX);
+        host.world.borrow_mut().refuse_transfers = true;
+        assert_eq!(
+            vault.buy_loan_position(U256::from(1), U256::from(19_000)),
+            Err(TransferFailed {}.abi_encode())
+        );
+        host.world.borrow_mut().refuse_transfers = false;
+        assert_eq!(host.world.borrow().positions.get(&U256::from(1)), Some(&VAULT));
+        assert_eq!(vault.get_cash_flow_route(CIRCLE), (U256::from(1), VAULT));
+
+        // Delisting
+        host.vm.set_sender(OWNER);
+        vault.list_loan_position(U256::from(1), U256::ZERO).unwrap();
+        host.vm.set_sender(BUYER);
+        assert_eq!(
+            vault.buy_loan_position(U256::from(1), U256::MAX),
+            Err(LoanPositionUnavailable {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_positions_require_nft() {
+        let (host, mut vault) = vault_with_loan(20_000);
+        assert!(host.world.borrow().positions.is_empty());
+        assert_eq!(vault.get_cash_flow_route(CIRCLE), (U256::from(1), VAULT));
+
+        host.vm.set_sender(OWNER);
+        assert_eq!(
+            vault.list_loan_position(U256::from(1), U256::from(19_000)),
+            Err(LoanPositionUnavailable {}.abi_encode())
+        );
+        assert_eq!(
+            vault.set_loan_position_nft(Address::ZERO),
+            Err(InvalidAddress {}.abi_encode())
+        );
+
+        // Configuring the NFT later does not give the existing loan a token
+        vault.set_loan_position_nft(POSITION_NFT).unwrap();
+        assert_eq!(
+            vault.list_loan_position(U256::from(1), U256::from(19_000)),
+            Err(LoanPositionUnavailable {}.abi_encode())
+        );
+
+        // The next loan is minted and can be sold
+        repay_as(&host, &mut vault, CIRCLE, 20_000);
+        borrow(&host, &mut vault, CIRCLE);
+        assert_eq!(host.world.borrow().positions.get(&U256::from(2)), Some(&VAULT));
+        host.vm.set_sender(OWNER);
+        vault.list_loan_position(U256::from(2), U256::from(19_000)).unwrap();
+    }
+}
@@ -0,0 +1,128 @@
+//! RiskOracle-priced loan tests against mock circle and oracle contracts.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::SolError;
+    use kuyay_vault::*;
+    use stylus_sdk::alloy_primitives::{Address, U256};
+
+    #[test]
+    fn test_loan_within_oracle_terms() {
+        let (host, mut vault) = funded_vault();
+        // 10k collateral at 3x => up to 20k borrowed, at least 10%
+        setup_circle(&host, &mut vault, U256::from(10_000), 300, 1000);
+
+        assert_eq!(
+            vault.get_loan_terms(CIRCLE).unwrap(),
+            (U256::from(20_000), U256::from(1000))
+        );
+
+        host.vm.set_sender(CIRCLE);
+        vault
+            .request_loan(U256::from(20_000), U256::from(365), U256::from(1200))
+            .unwrap();
+
+        // Oracle was asked about the circle's members
+        assert_eq!(
+            host.world.borrow().oracle_queries.last(),
+            Some(&MEMBERS.to_vec())
+        );
+        let (principal, rate, _, _, _, active) = vault.get_loan(CIRCLE);
+        assert_eq!(principal, U256::from(20_000));
+        assert_eq!(rate, U256::from(1200));
+        assert!(active);
+    }
+
+    #[test]
+    fn test_loan_above_leverage_rejected() {
+        let (host, mut vault) = funded_vault();
+        setup_circle(&host, &mut vault, U256::from(10_000), 300, 1000);
+
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.request_loan(U256::from(20_001), U256::from(365), U256::from(1000)),
+            Err(LeverageExceeded {
+                requested: U256::from(20_001),
+                max_allowed: U256::from(20_000),
+            }
+            .abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_no_leverage_at_1x() {
+        let (host, mut vault) = funded_vault();
+        setup_circle(&host, &mut vault, U256::from(10_000), 100, 1000);
+
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.request_loan(U256::from(1), U256::from(365), U256::from(1000)),
+            Err(LeverageExceeded {
+                requested: U256::from(1),
+                max_allowed: U256::ZERO,
+            }
+            .abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_rate_below_oracle_minimum_rejected() {
+        let (host, mut vault) = funded_vault();
+        setup_circle(&host, &mut vault, U256::from(10_000), 300, 1000);
+
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.request_loan(U256::from(10_000), U256::from(365), U256::from(999)),
+            Err(InterestRateTooLow {
+                offered_bps: U256::from(999),
+                min_rate_bps: U256::from(1000),
+            }
+            .abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_oracle_required() {
+        let (host, mut vault) = funded_vault();
+        host.vm.set_sender(OWNER);
+        vault.authorize_circle(CIRCLE).unwrap();
+
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.request_loan(U256::from(1_000), U256::from(365), U256::from(1000)),
+            Err(RiskOracleNotSet {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_oracle_failure_rejects_loan() {
+        let (host, mut vault) = funded_vault();
+        setup_circle(&host, &mut vault, U256::from(10_000), 300, 1000);
+        host.world.borrow_mut().oracle_terms = None;
+
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.request_loan(U256::from(1_000), U256::from(365), U256::from(1000)),
+            Err(RiskCheckFailed {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_set_risk_oracle() {
+        let (host, mut vault) = funded_vault();
+
+        host.vm.set_sender(STRANGER);
+        assert_eq!(vault.set_risk_oracle(ORACLE), Err(Unauthorized {}.abi_encode()));
+
+        host.vm.set_sender(OWNER);
+        assert_eq!(
+            vault.set_risk_oracle(Address::ZERO),
+            Err(InvalidAddress {}.abi_encode())
+        );
+        vault.set_risk_oracle(ORACLE).unwrap();
+        assert_eq!(vault.risk_oracle(), ORACLE);
+    }
+}
@@ -0,0 +1,121 @@
+//! Loan registry enumeration and counter tests.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::SolError;
+    use kuyay_vault::*;
+    use stylus_sdk::alloy_primitives::{Address, U256};
+
+    const CIRCLE_B: Address = Address::new([0x31; 20]);
+    const CIRCLE_C: Address = Address::new([0x32; 20]);
+
+    /// Three circles with 10k collateral each, 20k borrowed by each
+    fn vault_with_loans() -> (MockHost, KuyayVault) {
+        let (host, mut vault) = funded_vault();
+        setup_circle(&host, &mut vault, U256::from(10_000), 300, 1000);
+        add_circle(&host, &mut vault, CIRCLE_B, U256::from(10_000));
+        add_circle(&host, &mut vault, CIRCLE_C, U256::from(10_000));
+
+        for circle in [CIRCLE, CIRCLE_B, CIRCLE_C] {
+            borrow(&host, &mut vault, circle);
+        }
+        (host, vault)
+    }
+
+    #[test]
+    fn test_active_loans_pagination() {
+        let (_host, vault) = vault_with_loans();
+
+        assert_eq!(
+            vault.get_active_loans(U256::ZERO, U256::from(10)),
+            vec![CIRCLE, CIRCLE_B, CIRCLE_C]
+        );
+        assert_eq!(
+            vault.get_active_loans(U256::from(1), U256::from(1)),
+            vec![CIRCLE_B]
+        );
+        assert!(vault.get_active_loans(U256::from(3), U256::from(10)).is_empty());
+        assert!(vault.get_active_loans(U256::MAX, U256::MAX).is_empty());
+    }
+
+    #[test]
+    fn test_repay_and_liquidate_update_registry() {
+        let (host, mut vault) = vault_with_loans();
+
+        repay_as(&host, &mut vault, CIRCLE, 20_000);
+        host.vm.set_sender(OWNER);
+        vault.liquidate_circle(CIRCLE_B, U256::from(20_000)).unwrap();
+
+        // Last entry swapped into the freed slot
+        assert_eq!(
+            vault.get_active_loans(U256::ZERO, U256::from(10)),
+            vec![CIRCLE_C]
+        );
+        assert_eq!(vault.get_loan_status(CIRCLE), LOAN_STATUS_REPAID);
+        assert_eq!(vault.get_loan_status(CIRCLE_B), LOAN_STATUS_LIQUIDATED);
+        assert_eq!(vault.get_loan_status(CIRCLE_C), LOAN_STATUS_ACTIVE);
+        assert_eq!(vault.get_loan_status(STRANGER), LOAN_STATUS_NONE);
+        assert_eq!(
+            vault.get_loan_stats(),
+            (U256::from(1), U256::from(1), U256::from(1), U256::from(3))
+        );
+    }
+
+    #[test]
+    fn test_loans_by_status() {
+        let (host, mut vault) = vault_with_loans();
+        repay_as(&host, &mut vault, CIRCLE_B, 20_000);
+
+        assert_eq!(
+            vault
+                .get_loans_by_status(LOAN_STATUS_ACTIVE, U256::ZERO, U256::from(10))
+                .unwrap(),
+            vec![CIRCLE, CIRCLE_C]
+        );
+        assert_eq!(
+            vault
+                .get_loans_by_status(LOAN_STATUS_ACTIVE, U256::from(1), U256::from(10))
+                .unwrap(),
+            vec![CIRCLE_C]
+        );
+        assert_eq!(
+            vault
+                .get_loans_by_status(LOAN_STATUS_REPAID, U256::ZERO, U256::from(10))
+                .unwrap(),
+            vec![CIRCLE_B]
+        );
+        assert!(vault
+            .get_loans_by_status(LOAN_STATUS_LIQUIDATED, U256::ZERO, U256::from(10))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            vault.get_loans_by_status(LOAN_STATUS_NONE, U256::ZERO, U256::from(10)),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(
+            vault.get_loans_by_status(4, U256::ZERO, U256::from(10)),
+            Err(InvalidParameter {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_circle_borrowing_again_is_registered_once() {
+        let (host, mut vault) = vault_with_loans();
+        repay_as(&host, &mut vault, CIRCLE, 20_000);
+        borrow(&host, &mut vault, CIRCLE);
+
+        assert_eq!(vault.get_loan_count(), U256::from(3));
+        assert_eq!(vault.get_loan_status(CIRCLE), LOAN_STATUS_ACTIVE);
+        assert_eq!(
+            vault.get_active_loans(U256::ZERO, U256::from(10)),
+            vec![CIRCLE_C, CIRCLE_B, CIRCLE]
+        );
+        assert_eq!(
+            vault.get_loan_stats(),
+            (U256::from(3), U256::from(1), U256::ZERO, U256::from(3))
+        );
+    }
+}
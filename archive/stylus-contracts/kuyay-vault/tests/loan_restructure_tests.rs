@@ -0,0 +1,257 @@
+//! Loan extension and refinancing tests.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::{SolError, SolEvent};
+    use kuyay_vault::*;
+    use stylus_sdk::alloy_primitives::U256;
+
+    const HALF_YEAR: u64 = 31_536_000 / 2;
+
+    #[test]
+    fn test_extend_capitalizes_unpaid_interest() {
+        let (host, mut vault) = vault_with_loan(20_000);
+        host.vm.set_block_timestamp(START + HALF_YEAR);
+
+        // 21k outstanding (1k unpaid interest) + 1% fee
+        host.vm.set_sender(CIRCLE);
+        let new_principal = vault.extend_loan(U256::from(180)).unwrap();
+        assert_eq!(new_principal, U256::from(21_210));
+
+        let (principal, rate, start, duration, paid, active) = vault.get_loan(CIRCLE);
+        assert_eq!(principal, U256::from(21_210));
+        assert_eq!(rate, U256::from(1000));
+        assert_eq!(start, U256::from(START + HALF_YEAR));
+        assert_eq!(duration, U256::from(HALF_YEAR + 180 * 86400));
+        assert_eq!(paid, U256::ZERO);
+        assert!(active);
+
+        // Capitalized amounts are not booked until repaid; idle cash is unchanged
+        assert_eq!(vault.total_loaned(), U256::from(20_000));
+        assert_eq!(vault.total_assets(), U256::from(100_000));
+        assert_eq!(vault.total_interest_earned(), U256::ZERO);
+        assert_eq!(vault.idle_liquidity(), U256::from(80_000));
+
+        let logs = host.vm.get_emitted_logs();
+        let (topics, data) = logs.last().unwrap();
+        assert_eq!(topics[0], LoanRestructured::SIGNATURE_HASH);
+        let event = LoanRestructured::decode_raw_log(topics.iter().copied(), data, true).unwrap();
+        assert_eq!(event.circle, CIRCLE);
+        assert_eq!(event.loan_id, U256::from(1));
+        assert_eq!(event.kind, RESTRUCTURE_EXTENSION);
+        assert_eq!(event.previous_principal, U256::from(20_000));
+        assert_eq!(event.capitalized_interest, U256::from(1_000));
+        assert_eq!(event.fee, U256::from(210));
+    }
+
+    #[test]
+    fn test_refinance_after_partial_repayment() {
+        let (host, mut vault) = vault_with_loan(20_000);
+        host.vm.set_block_timestamp(START + HALF_YEAR);
+        repay_as(&host, &mut vault, CIRCLE, 11_000);
+
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.refinance_loan(U256::from(800)),
+            Err(InterestRateTooLow {
+                offered_bps: U256::from(800),
+                min_rate_bps: U256::from(1000)
+            }
+            .abi_encode())
+        );
+
+        // Oracle now quotes a lower floor
+        host.world.borrow_mut().oracle_terms = Some((U256::from(300), U256::from(800)));
+        let new_principal = vault.refinance_loan(U256::from(800)).unwrap();
+
+        // Payments return the cash lent first: 9k of it and the 1k interest left + 1% fee
+        assert_eq!(new_principal, U256::from(10_100));
+        let (_, rate, _, duration, _, _) = vault.get_loan(CIRCLE);
+        assert_eq!(rate, U256::from(800));
+        assert_eq!(duration, U256::from(HALF_YEAR));
+        assert_eq!(vault.total_interest_earned(), U256::ZERO);
+        assert_eq!(vault.total_loaned(), U256::from(9_000));
+        assert_eq!(vault.idle_liquidity(), U256::from(91_000));
+        assert_eq!(vault.idle_liquidity(), host.balance(VAULT));
+    }
+
+    #[test]
+    fn test_capitalized_amounts_booked_when_repaid() {
+        let (host, mut vault) = vault_with_loan(20_000);
+        host.vm.set_block_timestamp(START + HALF_YEAR);
+        host.vm.set_sender(CIRCLE);
+        vault.extend_loan(U256::from(180)).unwrap();
+
+        // Each payment books only its own part above the 20k lent
+        repay_as(&host, &mut vault, CIRCLE, 20_500);
+        assert_eq!(vault.total_interest_earned(), U256::from(500));
+        repay_as(&host, &mut vault, CIRCLE, 710);
+        assert_eq!(vault.total_interest_earned(), U256::from(1_210));
+
+        assert_eq!(vault.get_loan_status(CIRCLE), LOAN_STATUS_REPAID);
+        assert_eq!(vault.total_loaned(), U256::ZERO);
+        assert_eq!(vault.balance_of(LP), host.balance(VAULT));
+    }
+
+    #[test]
+    fn test_lineage_records_replaced_terms() {
+        let (host, mut vault) = vault_with_loan(20_000);
+        let loan_id = vault.get_loan_id(CIRCLE);
+        assert_eq!(loan_id, U256::from(1));
+        assert_eq!(vault.get_loan_revision_count(loan_id), U256::ZERO);
+
+        host.vm.set_block_timestamp(START + HALF_YEAR);
+        host.vm.set_sender(CIRCLE);
+        vault.extend_loan(U256::from(180)).unwrap();
+        vault.refinance_loan(U256::from(1100)).unwrap();
+
+        assert_eq!(vault.get_loan_revision_count(loan_id), U256::from(2));
+        assert_eq!(
+            vault.get_loan_revision(loan_id, U256::ZERO).unwrap(),
+            (
+                RESTRUCTURE_EXTENSION,
+                U256::from(20_000),
+                U256::from(1000),
+                U256::from(START),
+                U256::from(365 * 86400),
+                U256::ZERO,
+                U256::from(1_000),
+                U256::from(210),
+                U256::from(START + HALF_YEAR),
+            )
+        );
+        let (kind, principal, rate, ..) = vault.get_loan_revision(loan_id, U256::from(1)).unwrap();
+        assert_eq!(kind, RESTRUCTURE_REFINANCE);
+        assert_eq!(principal, U256::from(21_210));
+        assert_eq!(rate, U256::from(1000));
+        assert_eq!(
+            vault.get_loan_revision(loan_id, U256::from(2)),
+            Err(InvalidParameter {}.abi_encode())
+        );
+
+        // Restructured loan still closes normally and the next loan gets a fresh id
+        let (principal, ..) = vault.get_loan(CIRCLE);
+        repay_as(&host, &mut vault, CIRCLE, principal.to::<u64>() + 1);
+        assert_eq!(vault.get_loan_status(CIRCLE), LOAN_STATUS_REPAID);
+
+        host.vm.set_sender(CIRCLE);
+        vault
+            .request_loan(U256::from(1_000), U256::from(30), U256::from(1000))
+            .unwrap();
+        assert_eq!(vault.get_loan_id(CIRCLE), U256::from(2));
+        assert_eq!(vault.get_loan_revision_count(U256::from(2)), U256::ZERO);
+    }
+
+    #[test]
+    fn test_unhealthy_loan_cannot_restructure() {
+        let (host, mut vault) = vault_with_loan(10_000);
+        host.vm.set_block_timestamp(START + HALF_YEAR);
+
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.extend_loan(U256::from(30)),
+            Err(LoanHealthTooLow {
+                health_factor_bps: U256::from(3125),
+                min_health_bps: U256::from(5000)
+            }
+            .abi_encode())
+        );
+
+        // The gate has its own setting, apart from the early-warning threshold
+        host.vm.set_sender(OWNER);
+        vault.set_health_warning_threshold(U256::from(3000)).unwrap();
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.extend_loan(U256::from(30)),
+            Err(LoanHealthTooLow {
+                health_factor_bps: U256::from(3125),
+                min_health_bps: U256::from(5000)
+            }
+            .abi_encode())
+        );
+
+        host.vm.set_sender(STRANGER);
+        assert_eq!(
+            vault.set_restructure_min_health(U256::from(3000)),
+            Err(Unauthorized {}.abi_encode())
+        );
+        host.vm.set_sender(OWNER);
+        vault.set_restructure_min_health(U256::from(3000)).unwrap();
+        assert_eq!(vault.restructure_min_health_bps(), U256::from(3000));
+
+        // Past the gate, the oracle's leverage check still applies
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.extend_loan(U256::from(30)),
+            Err(LeverageExceeded {
+                requested: U256::from(21_210),
+                max_allowed: U256::from(20_000)
+            }
+            .abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_restructure_checks_oracle_leverage() {
+        let (host, mut vault) = vault_with_loan(10_000);
+
+        // 20k + 1% fee exceeds the 20k the oracle allows
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.extend_loan(U256::from(30)),
+            Err(LeverageExceeded {
+                requested: U256::from(20_200),
+                max_allowed: U256::from(20_000)
+            }
+            .abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_restructure_validation() {
+        let (host, mut vault) = vault_with_loan(40_000);
+
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.extend_loan(U256::ZERO),
+            Err(InvalidParameter {}.abi_encode())
+        );
+
+        host.vm.set_block_timestamp(START + 2 * HALF_YEAR);
+        assert_eq!(
+            vault.refinance_loan(U256::from(1000)),
+            Err(LoanMatured {}.abi_encode())
+        );
+
+        host.vm.set_sender(STRANGER);
+        assert_eq!(
+            vault.extend_loan(U256::from(30)),
+            Err(NotAuthorizedCircle {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_set_extension_fee() {
+        let (host, mut vault) = vault_with_loan(20_000);
+        assert_eq!(vault.extension_fee_bps(), U256::from(100));
+
+        host.vm.set_sender(STRANGER);
+        assert_eq!(
+            vault.set_extension_fee(U256::from(50)),
+            Err(Unauthorized {}.abi_encode())
+        );
+
+        host.vm.set_sender(OWNER);
+        assert_eq!(
+            vault.set_extension_fee(U256::from(1001)),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        vault.set_extension_fee(U256::ZERO).unwrap();
+
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(vault.extend_loan(U256::from(30)).unwrap(), U256::from(20_000));
+    }
+}
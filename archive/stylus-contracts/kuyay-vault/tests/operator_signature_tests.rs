@@ -0,0 +1,218 @@
+//! Operator delegation and EIP-712 signed deposit/withdraw/claim tests.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::SolError;
+    use kuyay_vault::*;
+    use stylus_sdk::abi::Bytes;
+    use stylus_sdk::alloy_primitives::{Address, B256, U256};
+
+    const RELAYER: Address = Address::new([0x51; 20]);
+    const OPERATOR: Address = Address::new([0x52; 20]);
+    const DEADLINE: u64 = 2_000_000;
+
+    fn word(value: u64) -> B256 {
+        U256::from(value).into()
+    }
+
+    fn sign_deposit(host: &MockHost, seed: u8, lp: Address, amount: u64, nonce: u64, deadline: u64) -> Bytes {
+        let (key, _) = signer(seed);
+        let fields = [lp.into_word(), word(amount), word(nonce), word(deadline)];
+        Bytes::from(sign_typed(host, &key, DEPOSIT_TYPE, &fields))
+    }
+
+    fn sign_withdraw(host: &MockHost, seed: u8, lp: Address, amount: u64, nonce: u64, deadline: u64) -> Bytes {
+        let (key, _) = signer(seed);
+        let fields = [lp.into_word(), word(amount), word(nonce), word(deadline)];
+        Bytes::from(sign_typed(host, &key, WITHDRAW_TYPE, &fields))
+    }
+
+    /// Vault where the signer-controlled LP has funds approved to the vault
+    fn setup() -> (MockHost, KuyayVault, Address) {
+        let (host, vault) = setup_vault();
+        host.vm.set_block_timestamp(1_000_000);
+        let (_, lp) = signer(7);
+        host.mint(lp, U256::from(50_000));
+        host.approve(lp, VAULT, U256::MAX);
+        (host, vault, lp)
+    }
+
+    #[test]
+    fn test_domain_separator() {
+        let (host, vault, _) = setup();
+        assert_eq!(vault.domain_separator(), domain_separator(&host));
+    }
+
+    #[test]
+    fn test_operator_deposits_and_withdraws_for_lp() {
+        let (host, mut vault, lp) = setup();
+
+        host.vm.set_sender(OPERATOR);
+        assert_eq!(
+            vault.deposit_for(lp, U256::from(10_000)),
+            Err(NotOperator {}.abi_encode())
+        );
+
+        host.vm.set_sender(lp);
+        vault.set_operator(OPERATOR, true).unwrap();
+        assert!(vault.is_operator(lp, OPERATOR));
+
+        host.vm.set_sender(OPERATOR);
+        vault.deposit_for(lp, U256::from(10_000)).unwrap();
+        assert_eq!(vault.balance_of(lp), U256::from(10_000));
+        assert_eq!(vault.balance_of(OPERATOR), U256::ZERO);
+
+        // Funds always return to the LP, never to the operator
+        vault.withdraw_for(lp, U256::from(4_000)).unwrap();
+        assert_eq!(host.balance(lp), U256::from(44_000));
+        assert_eq!(host.balance(OPERATOR), U256::ZERO);
+
+        host.vm.set_sender(lp);
+        vault.set_operator(OPERATOR, false).unwrap();
+        host.vm.set_sender(OPERATOR);
+        assert_eq!(
+            vault.withdraw_for(lp, U256::from(1_000)),
+            Err(NotOperator {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_signed_deposit_and_withdraw() {
+        let (host, mut vault, lp) = setup();
+
+        let signature = sign_deposit(&host, 7, lp, 10_000, 0, DEADLINE);
+        host.vm.set_sender(RELAYER);
+        vault
+            .deposit_with_sig(lp, U256::from(10_000), U256::from(DEADLINE), signature)
+            .unwrap();
+        assert_eq!(vault.balance_of(lp), U256::from(10_000));
+        assert_eq!(vault.nonces(lp), U256::from(1));
+
+        let signature = sign_withdraw(&host, 7, lp, 3_000, 1, DEADLINE);
+        vault
+            .withdraw_with_sig(lp, U256::from(3_000), U256::from(DEADLINE), signature)
+            .unwrap();
+        assert_eq!(host.balance(lp), U256::from(43_000));
+        assert_eq!(host.balance(RELAYER), U256::ZERO);
+        assert_eq!(vault.nonces(lp), U256::from(2));
+    }
+
+    #[test]
+    fn test_signature_replay_rejected() {
+        let (host, mut vault, lp) = setup();
+
+        let signature = sign_deposit(&host, 7, lp, 10_000, 0, DEADLINE);
+        host.vm.set_sender(RELAYER);
+        vault
+            .deposit_with_sig(lp, U256::from(10_000), U256::from(DEADLINE), signature.clone())
+            .unwrap();
+        assert_eq!(
+            vault.deposit_with_sig(lp, U256::from(10_000), U256::from(DEADLINE), signature),
+            Err(InvalidSignature {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_expired_signature_rejected() {
+        let (host, mut vault, lp) = setup();
+
+        let signature = sign_deposit(&host, 7, lp, 10_000, 0, DEADLINE);
+        host.vm.set_block_timestamp(DEADLINE + 1);
+        host.vm.set_sender(RELAYER);
+        assert_eq!(
+            vault.deposit_with_sig(lp, U256::from(10_000), U256::from(DEADLINE), signature),
+            Err(SignatureExpired {
+                deadline: U256::from(DEADLINE)
+            }
+            .abi_encode())
+        );
+        assert_eq!(vault.nonces(lp), U256::ZERO);
+    }
+
+    #[test]
+    fn test_wrong_signer_rejected() {
+        let (host, mut vault, lp) = setup();
+        host.vm.set_sender(RELAYER);
+
+        // Signed by another key
+        let signature = sign_deposit(&host, 8, lp, 10_000, 0, DEADLINE);
+        assert_eq!(
+            vault.deposit_with_sig(lp, U256::from(10_000), U256::from(DEADLINE), signature),
+            Err(InvalidSignature {}.abi_encode())
+        );
+
+        // Right key, tampered amount
+        let signature = sign_deposit(&host, 7, lp, 10_000, 0, DEADLINE);
+        assert_eq!(
+            vault.deposit_with_sig(lp, U256::from(20_000), U256::from(DEADLINE), signature),
+            Err(InvalidSignature {}.abi_encode())
+        );
+
+        // A deposit signature cannot authorize a withdrawal
+        let signature = sign_deposit(&host, 7, lp, 10_000, 0, DEADLINE);
+        assert_eq!(
+            vault.withdraw_with_sig(lp, U256::from(10_000), U256::from(DEADLINE), signature),
+            Err(InvalidSignature {}.abi_encode())
+        );
+
+        assert_eq!(
+            vault.deposit_with_sig(lp, U256::from(10_000), U256::from(DEADLINE), Bytes::from(vec![0u8; 64])),
+            Err(InvalidSignature {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_claim_pays_out_yield_only() {
+        let (host, mut vault, lp) = setup();
+        host.vm.set_sender(lp);
+        vault.deposit(U256::from(50_000)).unwrap();
+        assert_eq!(
+            vault.claim(),
+            Err(NothingToClaim {}.abi_encode())
+        );
+
+        // Flash loan fee accrues to the LP
+        host.add_borrower(BORROWER, BorrowerMode::Repay);
+        host.mint(BORROWER, U256::from(45));
+        host.vm.set_sender(STRANGER);
+        vault
+            .flash_loan(BORROWER, ASSET, U256::from(50_000), Bytes::from(vec![]))
+            .unwrap();
+        assert_eq!(vault.claimable(lp), U256::from(45));
+
+        // Signed claim submitted by a relayer
+        let (key, _) = signer(7);
+        let fields = [lp.into_word(), word(0), word(DEADLINE)];
+        let signature = Bytes::from(sign_typed(&host, &key, CLAIM_TYPE, &fields));
+        host.vm.set_sender(RELAYER);
+        assert_eq!(
+            vault.claim_with_sig(lp, U256::from(DEADLINE), signature).unwrap(),
+            U256::from(45)
+        );
+        assert_eq!(host.balance(lp), U256::from(45));
+        assert_eq!(vault.principal_of(lp), U256::from(50_000));
+        assert_eq!(vault.claimable(lp), U256::ZERO);
+    }
+
+    #[test]
+    fn test_withdrawals_draw_yield_before_principal() {
+        let (host, mut vault, lp) = setup();
+        host.vm.set_sender(lp);
+        vault.deposit(U256::from(50_000)).unwrap();
+
+        host.add_borrower(BORROWER, BorrowerMode::Repay);
+        host.mint(BORROWER, U256::from(45));
+        host.vm.set_sender(STRANGER);
+        vault
+            .flash_loan(BORROWER, ASSET, U256::from(50_000), Bytes::from(vec![]))
+            .unwrap();
+
+        host.vm.set_sender(lp);
+        vault.withdraw(U256::from(1_045)).unwrap();
+        assert_eq!(vault.principal_of(lp), U256::from(49_000));
+        assert_eq!(vault.claimable(lp), U256::ZERO);
+    }
+}
@@ -0,0 +1,165 @@
+//! Loans priced from a consumed RiskOracle quote instead of live oracle terms.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::SolError;
+    use kuyay_vault::*;
+    use stylus_sdk::alloy_primitives::{Address, U256};
+
+    const QUOTE_ID: U256 = U256::from_limbs([7, 0, 0, 0]);
+    const OTHER_CIRCLE: Address = Address::new([0x16; 20]);
+
+    /// Quotes 3x at 10% for up to `amount` over 365 days. The mock host does not roll back
+    /// on revert, so tests re-issue the quote before each further attempt
+    fn issue_quote(host: &MockHost, amount: u64) {
+        host.world.borrow_mut().oracle_quotes.insert(
+            QUOTE_ID,
+            (
+                MEMBERS.to_vec(),
+                U256::from(300),
+                U256::from(1000),
+                U256::from(amount),
+                U256::from(365),
+            ),
+        );
+    }
+
+    /// Funded vault whose live oracle terms (2x, 15%) are worse than the quote
+    fn quoted_vault() -> (MockHost, KuyayVault) {
+        let (host, mut vault) = setup_vault();
+        deposit_as(&host, &mut vault, LP, U256::from(100_000));
+        setup_circle(&host, &mut vault, U256::from(10_000), 200, 1500);
+        issue_quote(&host, 20_000);
+        (host, vault)
+    }
+
+    #[test]
+    fn test_loan_at_quoted_terms() {
+        let (host, mut vault) = quoted_vault();
+
+        host.vm.set_sender(CIRCLE);
+        vault
+            .request_loan_with_quote(U256::from(20_000), U256::from(365), U256::from(1000), QUOTE_ID)
+            .unwrap();
+
+        let (principal, rate, _, _, _, active) = vault.get_loan(CIRCLE);
+        assert_eq!(principal, U256::from(20_000));
+        assert_eq!(rate, U256::from(1000));
+        assert!(active);
+        assert!(host.world.borrow().oracle_quotes.is_empty());
+        // Live terms were never consulted
+        assert!(host.world.borrow().oracle_queries.is_empty());
+    }
+
+    #[test]
+    fn test_quote_cannot_be_reused() {
+        let (host, mut vault) = quoted_vault();
+        add_circle(&host, &mut vault, OTHER_CIRCLE, U256::from(10_000));
+
+        host.vm.set_sender(CIRCLE);
+        vault
+            .request_loan_with_quote(U256::from(10_000), U256::from(365), U256::from(1000), QUOTE_ID)
+            .unwrap();
+
+        host.vm.set_sender(OTHER_CIRCLE);
+        assert_eq!(
+            vault.request_loan_with_quote(U256::from(10_000), U256::from(365), U256::from(1000), QUOTE_ID),
+            Err(QuoteRejected { quote_id: QUOTE_ID }.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_quote_for_other_members_rejected() {
+        let (host, mut vault) = quoted_vault();
+        host.world.borrow_mut().oracle_quotes.get_mut(&QUOTE_ID).unwrap().0 = vec![STRANGER];
+
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.request_loan_with_quote(U256::from(10_000), U256::from(365), U256::from(1000), QUOTE_ID),
+            Err(QuoteRejected { quote_id: QUOTE_ID }.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_request_beyond_quote_rejected() {
+        let (host, mut vault) = quoted_vault();
+
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.request_loan_with_quote(U256::from(20_001), U256::from(365), U256::from(1000), QUOTE_ID),
+            Err(QuoteTermsExceeded { quote_id: QUOTE_ID }.abi_encode())
+        );
+        issue_quote(&host, 20_000);
+        assert_eq!(
+            vault.request_loan_with_quote(U256::from(20_000), U256::from(366), U256::from(1000), QUOTE_ID),
+            Err(QuoteTermsExceeded { quote_id: QUOTE_ID }.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_quoted_leverage_and_rate_enforced() {
+        let (host, mut vault) = quoted_vault();
+        issue_quote(&host, 30_000);
+
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.request_loan_with_quote(U256::from(25_000), U256::from(365), U256::from(1000), QUOTE_ID),
+            Err(LeverageExceeded {
+                requested: U256::from(25_000),
+                max_allowed: U256::from(20_000),
+            }
+            .abi_encode())
+        );
+        issue_quote(&host, 30_000);
+        assert_eq!(
+            vault.request_loan_with_quote(U256::from(20_000), U256::from(365), U256::from(999), QUOTE_ID),
+            Err(InterestRateTooLow {
+                offered_bps: U256::from(999),
+                min_rate_bps: U256::from(1000),
+            }
+            .abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_quoted_loan_access() {
+        let (host, mut vault) = setup_vault();
+        deposit_as(&host, &mut vault, LP, U256::from(100_000));
+
+        host.vm.set_sender(STRANGER);
+        assert_eq!(
+            vault.request_loan_with_quote(U256::from(1), U256::from(365), U256::from(1000), QUOTE_ID),
+            Err(NotAuthorizedCircle {}.abi_encode())
+        );
+
+        host.vm.set_sender(OWNER);
+        vault.authorize_circle(CIRCLE).unwrap();
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.request_loan_with_quote(U256::ZERO, U256::from(365), U256::from(1000), QUOTE_ID),
+            Err(InvalidAmount {}.abi_encode())
+        );
+        assert_eq!(
+            vault.request_loan_with_quote(U256::from(1), U256::from(365), U256::from(1000), QUOTE_ID),
+            Err(RiskOracleNotSet {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_refused_disbursement_fails_the_loan() {
+        let (host, mut vault) = quoted_vault();
+        host.world.borrow_mut().refuse_transfers = true;
+
+        // The asset answers false instead of reverting
+        host.vm.set_sender(CIRCLE);
+        assert_eq!(
+            vault.request_loan_with_quote(U256::from(20_000), U256::from(365), U256::from(1000), QUOTE_ID),
+            Err(TransferFailed {}.abi_encode())
+        );
+        assert_eq!(host.balance(CIRCLE), U256::ZERO);
+        assert_eq!(host.balance(TREASURY), U256::ZERO);
+    }
+}
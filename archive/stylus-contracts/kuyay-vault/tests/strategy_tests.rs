@@ -0,0 +1,203 @@
+//! Idle-liquidity yield strategy tests against mock strategies.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::SolError;
+    use kuyay_vault::*;
+    use stylus_sdk::abi::Bytes;
+    use stylus_sdk::alloy_primitives::{Address, U256};
+
+    /// Vault with 100k deposited and `mode` strategy installed and rebalanced
+    fn vault_with_strategy(mode: StrategyMode) -> (MockHost, KuyayVault) {
+        let (host, mut vault) = setup_vault();
+        deposit_as(&host, &mut vault, LP, U256::from(100_000));
+        host.add_strategy(STRATEGY, mode);
+
+        host.vm.set_sender(OWNER);
+        vault.set_strategy(STRATEGY).unwrap();
+        vault.rebalance_strategy().unwrap();
+        (host, vault)
+    }
+
+    #[test]
+    fn test_rebalance_keeps_buffer_idle() {
+        let (host, vault) = vault_with_strategy(StrategyMode::Honest);
+
+        // Default buffer is 20% of liquid assets
+        assert_eq!(vault.liquidity_buffer_bps(), U256::from(2000));
+        assert_eq!(host.balance(STRATEGY), U256::from(80_000));
+        assert_eq!(host.balance(VAULT), U256::from(20_000));
+        assert_eq!(vault.strategy_deployed(), U256::from(80_000));
+        assert_eq!(vault.idle_liquidity(), U256::from(20_000));
+        assert_eq!(vault.available_liquidity(), U256::from(100_000));
+        assert_eq!(vault.balance_of(LP), U256::from(100_000));
+    }
+
+    #[test]
+    fn test_rebalance_recalls_when_buffer_raised() {
+        let (host, mut vault) = vault_with_strategy(StrategyMode::Honest);
+
+        host.vm.set_sender(OWNER);
+        vault.set_liquidity_buffer(U256::from(5000)).unwrap();
+        vault.rebalance_strategy().unwrap();
+
+        assert_eq!(host.balance(VAULT), U256::from(50_000));
+        assert_eq!(host.balance(STRATEGY), U256::from(50_000));
+        assert_eq!(vault.strategy_deployed(), U256::from(50_000));
+    }
+
+    #[test]
+    fn test_strategy_yield_counts_in_vault_value() {
+        let (host, mut vault) = vault_with_strategy(StrategyMode::Honest);
+
+        // Strategy earns 8k
+        host.mint(STRATEGY, U256::from(8_000));
+        assert_eq!(vault.strategy_total_value(), U256::from(88_000));
+        assert_eq!(vault.balance_of(LP), U256::from(108_000));
+
+        // Syncing on rebalance realizes the gain in total_assets
+        host.vm.set_sender(OWNER);
+        vault.rebalance_strategy().unwrap();
+        assert_eq!(vault.total_assets(), U256::from(108_000));
+        assert_eq!(vault.balance_of(LP), U256::from(108_000));
+    }
+
+    #[test]
+    fn test_withdraw_recalls_from_strategy() {
+        let (host, mut vault) = vault_with_strategy(StrategyMode::Honest);
+
+        host.vm.set_sender(LP);
+        vault.withdraw(U256::from(50_000)).unwrap();
+
+        assert_eq!(host.balance(LP), U256::from(50_000));
+        assert_eq!(host.balance(STRATEGY), U256::from(50_000));
+        assert_eq!(vault.strategy_deployed(), U256::from(50_000));
+        assert_eq!(vault.total_assets(), U256::from(50_000));
+    }
+
+    #[test]
+    fn test_request_loan_recalls_from_strategy() {
+        let (host, mut vault) = vault_with_strategy(StrategyMode::Honest);
+
+        setup_circle(&host, &mut vault, U256::from(30_000), 300, 1000);
+
+        host.vm.set_sender(CIRCLE);
+        let net = vault
+            .request_loan(U256::from(60_000), U256::from(30), U256::from(1000))
+            .unwrap();
+
+        // 3% origination fee goes to treasury
+        assert_eq!(net, U256::from(58_200));
+        assert_eq!(host.balance(CIRCLE), U256::from(58_200));
+        assert_eq!(host.balance(TREASURY), U256::from(1_800));
+        assert_eq!(vault.strategy_deployed(), U256::from(40_000));
+        assert_eq!(vault.available_liquidity(), U256::from(40_000));
+    }
+
+    #[test]
+    fn test_lossy_strategy_reduces_lp_value() {
+        // Strategy burns 10% of what it receives
+        let (host, mut vault) = vault_with_strategy(StrategyMode::Lossy { loss_bps: 1000 });
+
+        assert_eq!(vault.strategy_total_value(), U256::from(72_000));
+        assert_eq!(vault.balance_of(LP), U256::from(92_000));
+        assert_eq!(vault.available_liquidity(), U256::from(92_000));
+
+        // LP cannot take out more than what is left
+        host.vm.set_sender(LP);
+        assert_eq!(
+            vault.withdraw(U256::from(92_001)),
+            Err(InsufficientBalance {}.abi_encode())
+        );
+
+        vault.withdraw(U256::from(92_000)).unwrap();
+        assert_eq!(host.balance(LP), U256::from(92_000));
+        assert_eq!(vault.total_assets(), U256::ZERO);
+        assert_eq!(vault.strategy_deployed(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_set_strategy_migrates_funds() {
+        let (host, mut vault) = vault_with_strategy(StrategyMode::Honest);
+        let next = Address::new([0x0b; 20]);
+        host.add_strategy(next, StrategyMode::Honest);
+
+        host.vm.set_sender(OWNER);
+        vault.set_strategy(next).unwrap();
+
+        assert_eq!(host.balance(STRATEGY), U256::ZERO);
+        assert_eq!(host.balance(VAULT), U256::from(100_000));
+        assert_eq!(vault.strategy(), next);
+        assert_eq!(vault.strategy_deployed(), U256::ZERO);
+
+        vault.rebalance_strategy().unwrap();
+        assert_eq!(host.balance(next), U256::from(80_000));
+    }
+
+    #[test]
+    fn test_removing_lossy_strategy_writes_off_loss() {
+        let (host, mut vault) = vault_with_strategy(StrategyMode::Honest);
+        host.burn(STRATEGY, U256::from(30_000));
+
+        host.vm.set_sender(OWNER);
+        vault.set_strategy(Address::ZERO).unwrap();
+
+        assert_eq!(host.balance(VAULT), U256::from(70_000));
+        assert_eq!(vault.total_assets(), U256::from(70_000));
+        assert_eq!(vault.balance_of(LP), U256::from(70_000));
+    }
+
+    #[test]
+    fn test_flash_loans_only_use_idle_cash() {
+        let (host, mut vault) = vault_with_strategy(StrategyMode::Honest);
+        host.add_borrower(BORROWER, BorrowerMode::Repay);
+
+        assert_eq!(vault.max_flash_loan(ASSET), U256::from(20_000));
+
+        host.vm.set_sender(STRANGER);
+        assert_eq!(
+            vault.flash_loan(BORROWER, ASSET, U256::from(20_001), Bytes::from(vec![])),
+            Err(InsufficientLiquidity {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_broken_strategy_blocks_recall() {
+        let (host, mut vault) = vault_with_strategy(StrategyMode::Honest);
+        host.add_strategy(STRATEGY, StrategyMode::Broken);
+
+        // Views fall back to the last synced value
+        assert_eq!(vault.strategy_total_value(), U256::from(80_000));
+
+        host.vm.set_sender(LP);
+        assert_eq!(
+            vault.withdraw(U256::from(50_000)),
+            Err(StrategyCallFailed {}.abi_encode())
+        );
+        // Idle cash is still withdrawable
+        vault.withdraw(U256::from(20_000)).unwrap();
+    }
+
+    #[test]
+    fn test_strategy_admin_only_owner() {
+        let (host, mut vault) = setup_vault();
+        host.vm.set_sender(STRANGER);
+
+        assert_eq!(vault.set_strategy(STRATEGY), Err(Unauthorized {}.abi_encode()));
+        assert_eq!(
+            vault.set_liquidity_buffer(U256::from(1000)),
+            Err(Unauthorized {}.abi_encode())
+        );
+        assert_eq!(vault.rebalance_strategy(), Err(Unauthorized {}.abi_encode()));
+
+        host.vm.set_sender(OWNER);
+        assert_eq!(
+            vault.set_liquidity_buffer(U256::from(10001)),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(vault.rebalance_strategy(), Err(InvalidAddress {}.abi_encode()));
+    }
+}
@@ -0,0 +1,124 @@
+//! Repayments made on a circle's behalf by members, sponsors or agents.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::{SolError, SolEvent};
+    use kuyay_vault::*;
+    use stylus_sdk::alloy_primitives::{Address, U256};
+
+    fn fund(host: &MockHost, payer: Address, amount: u64) {
+        host.mint(payer, U256::from(amount));
+        host.approve(payer, VAULT, U256::MAX);
+    }
+
+    #[test]
+    fn test_member_repays_for_circle() {
+        let (host, mut vault) = vault_with_loan(10_000);
+        let member = MEMBERS[0];
+        fund(&host, member, 5_000);
+
+        host.vm.set_sender(member);
+        vault.repay_loan_for(CIRCLE, U256::from(5_000)).unwrap();
+
+        // Funds came from the member, debt belongs to the circle
+        assert_eq!(host.balance(member), U256::ZERO);
+        let (_, _, _, _, paid, active) = vault.get_loan(CIRCLE);
+        assert_eq!(paid, U256::from(5_000));
+        assert!(active);
+        assert_eq!(vault.calculate_total_debt(CIRCLE).unwrap(), U256::from(15_000));
+
+        let loan_id = vault.get_loan_id(CIRCLE);
+        assert_eq!(vault.get_contribution(loan_id, member), U256::from(5_000));
+        assert_eq!(vault.get_contribution(loan_id, CIRCLE), U256::ZERO);
+    }
+
+    #[test]
+    fn test_repayment_events_name_payer_and_circle() {
+        let (host, mut vault) = vault_with_loan(10_000);
+        fund(&host, STRANGER, 1_000);
+
+        host.vm.set_sender(STRANGER);
+        vault.repay_loan_for(CIRCLE, U256::from(1_000)).unwrap();
+
+        let logs = host.vm.get_emitted_logs();
+        let (topics, data) = logs.last().unwrap();
+        assert_eq!(topics[0], LoanRepaidBy::SIGNATURE_HASH);
+        assert_eq!(topics[1], STRANGER.into_word());
+        assert_eq!(topics[2], CIRCLE.into_word());
+        let event = LoanRepaidBy::decode_raw_log(topics.iter().copied(), data, true).unwrap();
+        assert_eq!(event.loan_id, U256::from(1));
+        assert_eq!(event.amount, U256::from(1_000));
+
+        // Circle-side event is still emitted
+        let (topics, _) = &logs[logs.len() - 2];
+        assert_eq!(topics[0], LoanRepayment::SIGNATURE_HASH);
+        assert_eq!(topics[1], CIRCLE.into_word());
+    }
+
+    #[test]
+    fn test_mixed_payers_close_the_loan() {
+        let (host, mut vault) = vault_with_loan(10_000);
+        fund(&host, CIRCLE, 8_000);
+        fund(&host, MEMBERS[1], 7_000);
+        fund(&host, STRANGER, 5_000);
+
+        host.vm.set_sender(CIRCLE);
+        vault.repay_loan(U256::from(8_000)).unwrap();
+        host.vm.set_sender(MEMBERS[1]);
+        vault.repay_loan_for(CIRCLE, U256::from(3_000)).unwrap();
+        vault.repay_loan_for(CIRCLE, U256::from(4_000)).unwrap();
+        host.vm.set_sender(STRANGER);
+        vault.repay_loan_for(CIRCLE, U256::from(5_000)).unwrap();
+
+        assert_eq!(vault.get_loan_status(CIRCLE), LOAN_STATUS_REPAID);
+        assert_eq!(vault.total_loaned(), U256::ZERO);
+        assert_eq!(host.balance(VAULT), U256::from(100_000));
+
+        let (payers, amounts) = vault.get_loan_payers(U256::from(1));
+        assert_eq!(payers, vec![CIRCLE, MEMBERS[1], STRANGER]);
+        assert_eq!(
+            amounts,
+            vec![U256::from(8_000), U256::from(7_000), U256::from(5_000)]
+        );
+    }
+
+    #[test]
+    fn test_repay_for_requires_active_loan_and_funds() {
+        let (host, mut vault) = vault_with_loan(10_000);
+
+        host.vm.set_sender(STRANGER);
+        assert_eq!(
+            vault.repay_loan_for(BORROWER, U256::from(1_000)),
+            Err(NoActiveLoan {}.abi_encode())
+        );
+        assert_eq!(
+            vault.repay_loan_for(CIRCLE, U256::ZERO),
+            Err(InvalidAmount {}.abi_encode())
+        );
+        // No approval from the payer
+        assert_eq!(
+            vault.repay_loan_for(CIRCLE, U256::from(1_000)),
+            Err(TransferFailed {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_refused_transfer_does_not_repay() {
+        let (host, mut vault) = vault_with_loan(10_000);
+        fund(&host, STRANGER, 1_000);
+        host.world.borrow_mut().refuse_transfers = true;
+        let debt = vault.calculate_total_debt(CIRCLE).unwrap();
+
+        // The asset answers false instead of reverting
+        host.vm.set_sender(STRANGER);
+        assert_eq!(
+            vault.repay_loan_for(CIRCLE, U256::from(1_000)),
+            Err(TransferFailed {}.abi_encode())
+        );
+        assert_eq!(vault.calculate_total_debt(CIRCLE).unwrap(), debt);
+        assert_eq!(vault.get_contribution(vault.get_loan_id(CIRCLE), STRANGER), U256::ZERO);
+    }
+}
@@ -8,22 +8,26 @@
 
 #![cfg_attr(not(any(test, feature = "export-abi")), no_main)]
 #![cfg_attr(not(any(test, feature = "export-abi")), no_std)]
+#![recursion_limit = "256"]
 
 extern crate alloc;
+use alloc::collections::BTreeMap;
 use alloc::vec;
 use alloc::vec::Vec;
 
-use alloy_sol_types::sol;
+use alloy_sol_types::{sol, SolCall, SolError};
 use stylus_sdk::{
-    alloy_primitives::{Address, U256, U8},
+    alloy_primitives::{Address, B256, U256, U8},
     prelude::*,
-    storage::{StorageU256, StorageVec},
+    storage::{StorageAddress, StorageB256, StorageBool, StorageMap, StorageU256, StorageU8, StorageVec},
+    stylus_core::calls::context::Call,
 };
 
 // Re-export for macros
 use stylus_sdk::alloy_primitives;
 
-// Define struct for AguayoMetadata
+// Interfaz para llamar al contrato AguayoSBT (Solidity)
+// Las llamadas pasan por `self.vm()` para que funcionen igual en WASM y en stylus-test
 sol! {
     struct AguayoMetadata {
         uint8 level;
@@ -33,10 +37,7 @@ sol! {
         uint48 lastActivityTimestamp;
         bool isStained;
     }
-}
 
-// Interfaz para llamar al contrato AguayoSBT (Solidity)
-sol_interface! {
     interface IAguayoSBT {
         function userToAguayo(address user) external view returns (uint256);
         function isEligibleForCredit(uint256 tokenId) external view returns (bool);
@@ -44,14 +45,65 @@ sol_interface! {
         function isAguayoStained(uint256 tokenId) external view returns (bool);
         function getAguayoMetadata(uint256 tokenId) external view returns (AguayoMetadata memory);
     }
+
+    // Contrato genérico de atestaciones: score 0-10000 por sujeto
+    interface IReputationAttestation {
+        function scoreOf(address subject) external view returns (uint256);
+    }
+
+    interface IKuyayStaking {
+        function stakedBalanceOf(address account) external view returns (uint256);
+    }
 }
 
-// Leverage Tier structure
+/// Adaptador con el que se lee cada proveedor de reputación
+pub const PROVIDER_AGUAYO_SBT: u8 = 0;
+pub const PROVIDER_ATTESTATION: u8 = 1;
+pub const PROVIDER_STAKING: u8 = 2;
+
+/// Cómo se pondera a cada miembro en el sorteo VRF
+pub const DRAW_UNIFORM: u8 = 0;
+pub const DRAW_LINEAR: u8 = 1;
+pub const DRAW_QUADRATIC: u8 = 2;
+pub const DRAW_STAIN_PENALIZED: u8 = 3;
+pub const DRAW_REPUTATION: u8 = 4;
+
+/// Parámetros con límite de paso e intervalo mínimo entre cambios
+pub const PARAM_MAX_LEVERAGE: u8 = 0;
+pub const PARAM_BASE_RATE: u8 = 1;
+pub const PARAM_RISK_PREMIUM: u8 = 2;
+pub const PARAM_TIER_MULTIPLIER: u8 = 3;
+pub const PARAM_TIER_RATE: u8 = 4;
+
+// Saturación del credit score: desde aquí cada componente vale el máximo
+const THREADS_FOR_FULL_SCORE: u32 = 120;
+const CIRCLES_FOR_FULL_SCORE: u16 = 10;
+const MAX_SCORE: u64 = 10000;
+// Un nivel de tier equivale a 1000 puntos de score
+const SCORE_PER_LEVEL: u64 = 1000;
+// 10000 >> 14 == 0: a partir de aquí el decaimiento ya no deja score
+const MAX_HALVINGS: u64 = 14;
+// Círculos reportados que necesita un tier antes de sugerir un nuevo spread
+const MIN_OUTCOMES_FOR_RECALIBRATION: u64 = 5;
+// Tiers que un grupo puede estar por encima del que alcanza su miembro más débil
+const MAX_TIERS_ABOVE_WEAKEST: usize = 1;
+// Vigencia máxima configurable de una cotización
+const MAX_QUOTE_VALIDITY: u64 = 7 * 86400;
+// Miembros totales (con repeticiones) que acepta `evaluate_groups` por llamada
+pub const MAX_BATCH_MEMBERS: u64 = 100;
+
+// (min_average_level, multiplier, interest_rate_bps)
+type Tier = (u8, U256, U256);
+// (address, kind, weight_bps, full_score_stake)
+type Provider = (Address, u8, U256, U256);
+
+// Leverage Tier structure (layout de referencia; el storage usa vectores por campo)
+#[allow(dead_code)]
 #[derive(Default)]
 pub struct LeverageTier {
     min_average_level: u8,
     multiplier: U256,      // 100 = 1x, 500 = 5x
-    interest_rate_bps: U256, // basis points (1000 = 10%)
+    interest_rate_bps: U256, // spread sobre la tasa base, basis points (200 = 2%)
 }
 
 sol_storage! {
@@ -69,6 +121,75 @@ sol_storage! {
         StorageVec<StorageU256> tier_min_levels;
         StorageVec<StorageU256> tier_multipliers;
         StorageVec<StorageU256> tier_interest_rates;
+
+        // Credit score weights (bps; threads + circles + history = 10000)
+        uint256 score_weight_threads;
+        uint256 score_weight_circles;
+        uint256 score_weight_history;
+        uint256 stain_penalty_bps;
+        uint256 score_decay_half_life;
+
+        // Penalización de leverage por dispersión del grupo (bps)
+        uint256 variance_penalty_bps;
+        uint256 new_member_penalty_bps;
+
+        // Resultados reportados, por nivel de Aguayo y por índice de tier al momento del reporte
+        StorageMap<Address, StorageBool> outcome_reporters;
+        StorageMap<U256, StorageU256> tier_circle_outcomes;
+        StorageMap<U256, StorageU256> tier_member_outcomes;
+        StorageMap<U256, StorageU256> tier_member_defaults;
+        StorageMap<U256, StorageU256> tier_loss_bps_total;
+        StorageMap<U256, StorageU256> level_member_outcomes;
+        StorageMap<U256, StorageU256> level_member_defaults;
+
+        // Proveedores de reputación: [address, kind (PROVIDER_*), weight, stake para score máximo]
+        StorageVec<StorageAddress> provider_addresses;
+        StorageVec<StorageU256> provider_kinds;
+        StorageVec<StorageU256> provider_weights;
+        StorageVec<StorageU256> provider_full_score_stakes;
+
+        // Cotizaciones (por id, desde 1) y quién puede consumirlas
+        uint256 quote_validity;
+        uint256 next_quote_id;
+        StorageMap<Address, StorageBool> quote_consumers;
+        StorageMap<U256, StorageB256> quote_member_hash;
+        StorageMap<U256, StorageU256> quote_amount;
+        StorageMap<U256, StorageU256> quote_duration;
+        StorageMap<U256, StorageU256> quote_multiplier;
+        StorageMap<U256, StorageU256> quote_interest_rate;
+        StorageMap<U256, StorageU256> quote_expiry;
+        StorageMap<U256, StorageBool> quote_used;
+
+        // Sorteo VRF (DRAW_*)
+        uint8 draw_weight_mode;
+
+        // Denylist administrada por risk managers (el owner también lo es)
+        StorageMap<Address, StorageBool> risk_managers;
+        StorageVec<StorageAddress> denylist;
+        StorageMap<Address, StorageU256> denylist_index;  // posición + 1, 0 = ausente
+        StorageMap<Address, StorageU8> deny_reason;
+        StorageMap<Address, StorageU256> deny_expiry;     // 0 = sin vencimiento
+
+        // Límites de cambio por parámetro (PARAM_*); 0 = sin límite
+        StorageMap<U256, StorageU256> param_max_step;
+        StorageMap<U256, StorageU256> param_min_interval;
+        StorageMap<U256, StorageU256> param_last_change;
+        StorageMap<U256, StorageU256> tier_last_change;   // por clave de tier, para PARAM_TIER_*
+
+        // Clave estable de cada tier (paralela a tier_min_levels, desde 1): los resultados
+        // reportados se guardan por clave y no se mezclan al quitar o reemplazar tiers
+        StorageVec<StorageU256> tier_keys;
+        uint256 next_tier_key;
+        StorageMap<U256, StorageU256> quote_tier_key;
+        StorageMap<U256, StorageBool> quote_outcome_recorded;
+
+        // Límites relajados que rigen desde pending_limits_at (0 = nada programado)
+        StorageMap<U256, StorageU256> pending_max_step;
+        StorageMap<U256, StorageU256> pending_min_interval;
+        StorageMap<U256, StorageU256> pending_limits_at;
+
+        // Nivel de cada miembro al consumirse la cotización, para los resultados por nivel
+        StorageMap<U256, StorageMap<Address, StorageU8>> quote_member_levels;
     }
 }
 
@@ -76,18 +197,106 @@ sol_storage! {
 sol! {
     event LeverageTierAdded(uint8 indexed min_average_level, uint256 multiplier, uint256 interest_rate);
     event LeverageTierUpdated(uint256 indexed tier_id, uint8 min_average_level, uint256 multiplier, uint256 interest_rate);
+    event LeverageTierRemoved(uint256 indexed tier_id);
+    event LeverageTiersReplaced(uint256 tier_count);
     event MinLevelForCreditUpdated(uint8 new_min_level);
     event MaxLeverageUpdated(uint256 new_max_multiplier);
     event BaseInterestRateUpdated(uint256 new_rate_bps);
     event RiskPremiumUpdated(uint256 new_premium_bps);
     event OwnershipTransferred(address indexed previous_owner, address indexed new_owner);
+    event ScoreWeightsUpdated(uint256 threads_weight, uint256 circles_weight, uint256 history_weight, uint256 stain_penalty_bps, uint256 decay_half_life);
+    event DispersionPenaltyUpdated(uint256 variance_penalty_bps, uint256 new_member_penalty_bps);
+    event OutcomeReporterUpdated(address indexed reporter, bool authorized);
+    event CircleOutcomeRecorded(address indexed reporter, uint256 indexed quote_id, uint256 indexed tier_key, uint256 member_count, uint256 defaulted_count, uint256 loss_bps, uint256 annual_loss_bps);
+    event TierRatesRecalibrated(uint256[] spreads);
+    event ReputationProviderAdded(uint256 indexed provider_id, address indexed provider, uint8 kind, uint256 weight);
+    event ReputationProviderUpdated(uint256 indexed provider_id, uint256 weight, uint256 full_score_stake);
+    event ReputationProviderRemoved(uint256 indexed provider_id);
+    event QuoteIssued(uint256 indexed quote_id, address indexed requester, bytes32 member_hash, uint256 amount, uint256 duration_in_days, uint256 multiplier, uint256 interest_rate, uint256 expiry);
+    event QuoteConsumed(uint256 indexed quote_id, address indexed consumer);
+    event QuoteConsumerUpdated(address indexed consumer, bool authorized);
+    event QuoteValidityUpdated(uint256 validity);
+    event DrawWeightModeUpdated(uint8 mode);
+    event RiskManagerUpdated(address indexed manager, bool authorized);
+    event MemberDenylisted(address indexed member, uint8 indexed reason, uint256 expiry, address indexed manager);
+    event MemberRehabilitated(address indexed member, address indexed manager);
+    event ParameterLimitsUpdated(uint8 indexed parameter, uint256 max_step, uint256 min_interval);
+    event ParameterLimitsScheduled(uint8 indexed parameter, uint256 max_step, uint256 min_interval, uint256 effective_at);
 
     error Unauthorized();
+    error AlreadyInitialized();
     error InvalidAddress();
     error InvalidParameter();
     error MemberNotEligible(address member);
     error InvalidTierId();
+    error TierOrderViolation(uint256 tier_id);
     error EmptyMemberList();
+    error NoTiersConfigured();
+    error AguayoCallFailed(address member);
+    error RecalibrationMismatch();
+    error InvalidProviderId();
+    error NoProvidersConfigured();
+    error QuoteNotFound(uint256 quote_id);
+    error QuoteExpired(uint256 quote_id, uint256 expiry);
+    error QuoteAlreadyUsed(uint256 quote_id);
+    error QuoteMembersMismatch(uint256 quote_id);
+    error MemberNotDenylisted(address member);
+    error QuoteNotConsumed(uint256 quote_id);
+    error OutcomeAlreadyRecorded(uint256 quote_id);
+    error BatchTooLarge(uint256 member_count, uint256 max_members);
+    error ParameterStepExceeded(uint8 parameter, uint256 current, uint256 requested, uint256 max_step);
+    error ParameterChangeTooSoon(uint8 parameter, uint256 next_change_at);
+}
+
+sol! {
+    /// Desglose de cómo se llegó al leverage y la tasa de un grupo
+    #[derive(AbiType)]
+    struct RiskReport {
+        uint256 tierId;
+        uint256 averageLevelX100;
+        uint8 minLevel;
+        bool minLevelCapped;
+        uint256 levelVarianceX100;
+        uint256 newMemberShareBps;
+        uint256 groupScore;
+        uint8[] memberLevels;
+        bool[] memberStained;
+        uint256[] memberScores;
+        uint256[] memberConfidence;
+        uint256 tierMultiplier;
+        uint256 stainLeverageReduction;
+        uint256 dispersionPenaltyBps;
+        uint256 dispersionLeverageReduction;
+        bool leverageCapped;
+        uint256 multiplier;
+        uint256 baseRateBps;
+        uint256 tierSpreadBps;
+        uint256 stainPremiumBps;
+        bool rateCapped;
+        uint256 interestRateBps;
+    }
+}
+
+// export-abi necesita la declaración del struct para imprimir la interfaz
+#[cfg(feature = "export-abi")]
+impl stylus_sdk::abi::export::internal::InnerTypes for RiskReport {
+    fn inner_types() -> Vec<stylus_sdk::abi::export::internal::InnerType> {
+        use alloy_sol_types::SolStruct;
+
+        // "RiskReport(uint256 tierId,...)" -> "struct RiskReport { uint256 tierId; ... }"
+        let encoded = RiskReport::eip712_root_type();
+        let fields = encoded
+            .trim_start_matches("RiskReport(")
+            .trim_end_matches(')')
+            .split(',')
+            .map(|field| alloc::format!("        {field};\n"))
+            .collect::<alloc::string::String>();
+
+        vec![stylus_sdk::abi::export::internal::InnerType {
+            name: alloc::format!("struct RiskReport {{\n{fields}    }}"),
+            id: core::any::TypeId::of::<RiskReport>(),
+        }]
+    }
 }
 
 #[public]
@@ -96,37 +305,66 @@ impl RiskOracle {
     pub fn initialize(&mut self, aguayo_sbt_address: Address) -> Result<(), Vec<u8>> {
         // Check if already initialized
         if self.owner.get() != Address::ZERO {
-            return Err(b"Already initialized".to_vec());
+            return Err(AlreadyInitialized {}.abi_encode());
         }
 
         if aguayo_sbt_address == Address::ZERO {
-            return Err(b"Invalid address".to_vec());
+            return Err(InvalidAddress {}.abi_encode());
         }
 
         self.aguayo_sbt.set(aguayo_sbt_address);
-        // Note: msg::sender() is deprecated in SDK 0.8.4
-        // In production, use .vm() method from contract
-        self.owner.set(Address::ZERO); // TODO: Set from deployment
+        let deployer = self.vm().msg_sender();
+        self.owner.set(deployer);
         self.min_level_for_credit.set(U8::from(1));
         self.max_leverage_multiplier.set(U256::from(500)); // 5x
-        self.base_interest_rate_bps.set(U256::from(1000)); // 10%
+        self.base_interest_rate_bps.set(U256::from(800)); // 8%
         self.risk_premium_per_stain_bps.set(U256::from(200)); // 2%
 
-        // Initialize default tiers
-        // Tier 1: Level 1+, 1.5x leverage, 12% interest
+        // Initialize default tiers (la tasa de cada tier es un spread sobre la base)
+        // Tier 1: Level 1+, 1.5x leverage, +4% (12% interest)
         self.tier_min_levels.push(U256::from(1));
         self.tier_multipliers.push(U256::from(150));
-        self.tier_interest_rates.push(U256::from(1200));
+        self.tier_interest_rates.push(U256::from(400));
 
-        // Tier 2: Level 3+, 3x leverage, 10% interest
+        // Tier 2: Level 3+, 3x leverage, +2% (10% interest)
         self.tier_min_levels.push(U256::from(3));
         self.tier_multipliers.push(U256::from(300));
-        self.tier_interest_rates.push(U256::from(1000));
+        self.tier_interest_rates.push(U256::from(200));
 
-        // Tier 3: Level 5+, 5x leverage, 8% interest
+        // Tier 3: Level 5+, 5x leverage, +0% (8% interest)
         self.tier_min_levels.push(U256::from(5));
         self.tier_multipliers.push(U256::from(500));
-        self.tier_interest_rates.push(U256::from(800));
+        self.tier_interest_rates.push(U256::ZERO);
+
+        for _ in 0..3 {
+            let key = self.new_tier_key();
+            self.tier_keys.push(key);
+        }
+
+        // Credit score: 30% hilos, 40% círculos, 30% historial de pagos
+        self.score_weight_threads.set(U256::from(3000));
+        self.score_weight_circles.set(U256::from(4000));
+        self.score_weight_history.set(U256::from(3000));
+        self.stain_penalty_bps.set(U256::from(1500));
+        self.score_decay_half_life.set(U256::from(180 * 86400));
+
+        // Dispersión: -5% de leverage por unidad de varianza de niveles, -20% si todos son nuevos
+        self.variance_penalty_bps.set(U256::from(500));
+        self.new_member_penalty_bps.set(U256::from(2000));
+
+        // El AguayoSBT es el único proveedor de reputación hasta que el owner registre otros
+        self.provider_addresses.push(aguayo_sbt_address);
+        self.provider_kinds.push(U256::from(PROVIDER_AGUAYO_SBT));
+        self.provider_weights.push(U256::from(MAX_SCORE));
+        self.provider_full_score_stakes.push(U256::ZERO);
+
+        self.quote_validity.set(U256::from(3600)); // 1 hora
+        self.draw_weight_mode.set(U8::from(DRAW_LINEAR));
+
+        log(self.vm(), OwnershipTransferred {
+            previous_owner: Address::ZERO,
+            new_owner: deployer,
+        });
 
         Ok(())
     }
@@ -137,27 +375,8 @@ impl RiskOracle {
             return Ok(false);
         }
 
-        let aguayo_sbt_addr = self.aguayo_sbt.get();
-        let aguayo_sbt = IAguayoSBT::new(aguayo_sbt_addr);
-
         for member in members.iter() {
-            // Get token ID for member
-            let token_id = match aguayo_sbt.user_to_aguayo(self, *member) {
-                Ok(id) => id,
-                Err(_) => return Ok(false),
-            };
-
-            if token_id == U256::ZERO {
-                return Ok(false);
-            }
-
-            // Check if eligible for credit
-            let is_eligible = match aguayo_sbt.is_eligible_for_credit(self, token_id) {
-                Ok(eligible) => eligible,
-                Err(_) => return Ok(false),
-            };
-
-            if !is_eligible {
+            if self.credit_eligible(&self.snapshot(*member, true)) != Some(true) {
                 return Ok(false);
             }
         }
@@ -165,102 +384,553 @@ impl RiskOracle {
         Ok(true)
     }
 
-    /// Calcula leverage permitido e interés basado en el nivel promedio del grupo
+    /// Calcula leverage permitido e interés basado en la distribución de scores del grupo
     pub fn get_leverage_level(&self, members: Vec<Address>) -> Result<(U256, U256), Vec<u8>> {
         if members.is_empty() {
-            return Err(b"Empty member list".to_vec());
+            return Err(EmptyMemberList {}.abi_encode());
         }
+        self.reject_denylisted(&members)?;
 
-        let (avg_level, stained_count) = self.get_group_stats(members)?;
-        let (mut multiplier, mut interest_rate_bps) = self.get_tier_for_average_level(avg_level)?;
+        let snapshots: Vec<MemberSnapshot> =
+            members.iter().map(|member| self.snapshot(*member, false)).collect();
+        let pricing = self.price_group(&snapshots)?;
 
-        // Apply stain penalty
-        if stained_count > U256::ZERO {
-            // Reduce leverage by 10% per stained member
-            let leverage_reduction = (multiplier * stained_count * U256::from(10)) / U256::from(100);
-            multiplier = if multiplier > leverage_reduction {
-                multiplier - leverage_reduction
-            } else {
-                U256::from(100) // Minimum 1x
-            };
+        Ok((pricing.multiplier, pricing.interest_rate_bps))
+    }
 
-            // Increase interest rate
-            let additional_interest = stained_count * self.risk_premium_per_stain_bps.get();
-            interest_rate_bps = interest_rate_bps + additional_interest;
-            if interest_rate_bps > U256::from(10000) {
-                interest_rate_bps = U256::from(10000); // Cap at 100%
-            }
+    /// Probabilidades del sorteo VRF en bps; suman exactamente 10000
+    pub fn get_weighted_probabilities(&self, members: Vec<Address>) -> Result<Vec<U256>, Vec<u8>> {
+        if members.is_empty() {
+            return Err(EmptyMemberList {}.abi_encode());
         }
 
-        // Cap leverage at max
-        let max_leverage = self.max_leverage_multiplier.get();
-        if multiplier > max_leverage {
-            multiplier = max_leverage;
+        let snapshots: Vec<MemberSnapshot> =
+            members.iter().map(|member| self.snapshot(*member, false)).collect();
+        Ok(normalize_bps(&self.draw_weights(&snapshots)?))
+    }
+
+    /// (miembro, peso sin normalizar, probabilidad en bps) con el modo de sorteo actual
+    pub fn preview_draw_odds(&self, members: Vec<Address>) -> Result<Vec<(Address, U256, U256)>, Vec<u8>> {
+        if members.is_empty() {
+            return Err(EmptyMemberList {}.abi_encode());
         }
 
-        Ok((multiplier, interest_rate_bps))
+        let snapshots: Vec<MemberSnapshot> =
+            members.iter().map(|member| self.snapshot(*member, false)).collect();
+        let weights = self.draw_weights(&snapshots)?;
+        let odds = normalize_bps(&weights);
+
+        Ok(members
+            .into_iter()
+            .zip(weights)
+            .zip(odds)
+            .map(|((member, weight), odds)| (member, weight, odds))
+            .collect())
     }
 
-    /// Calcula probabilidades ponderadas para el sorteo VRF
-    pub fn get_weighted_probabilities(&self, members: Vec<Address>) -> Result<Vec<U256>, Vec<u8>> {
+    /// Valida si un miembro específico califica para un modo
+    pub fn is_member_eligible(&self, member: Address, is_credit_mode: bool) -> Result<bool, Vec<u8>> {
+        if self.is_denylisted(member) {
+            return Ok(false);
+        }
+        if !is_credit_mode {
+            return Ok(self.aguayo_token(member).is_some_and(|token_id| token_id != U256::ZERO));
+        }
+
+        self.credit_eligible(&self.snapshot(member, true))
+            .ok_or_else(|| AguayoCallFailed { member }.abi_encode())
+    }
+
+    /// Evalúa el grupo consultando a cada miembro una sola vez:
+    /// (todos elegibles, pesos del sorteo, score del grupo, multiplicador, tasa)
+    #[allow(clippy::type_complexity)]
+    pub fn evaluate_group(
+        &self,
+        members: Vec<Address>,
+    ) -> Result<(bool, Vec<U256>, U256, U256, U256), Vec<u8>> {
         if members.is_empty() {
-            return Err(b"Empty member list".to_vec());
+            return Err(EmptyMemberList {}.abi_encode());
         }
 
-        let aguayo_sbt_addr = self.aguayo_sbt.get();
-        let aguayo_sbt = IAguayoSBT::new(aguayo_sbt_addr);
-        let mut weights = Vec::new();
+        let snapshots: Vec<MemberSnapshot> =
+            members.iter().map(|member| self.snapshot(*member, true)).collect();
+
+        let all_eligible = snapshots
+            .iter()
+            .all(|snapshot| self.credit_eligible(snapshot) == Some(true));
+        let pricing = self.price_group(&snapshots)?;
+        let weights = normalize_bps(&self.draw_weights(&snapshots)?);
+
+        Ok((
+            all_eligible,
+            weights,
+            pricing.stats.group_score,
+            pricing.multiplier,
+            pricing.interest_rate_bps,
+        ))
+    }
 
-        for member in members.iter() {
-            let token_id = match aguayo_sbt.user_to_aguayo(self, *member) {
-                Ok(id) => id,
-                Err(_) => {
-                    weights.push(U256::ZERO);
-                    continue;
-                }
-            };
+    /// (elegible, multiplicador, tasa) de cada grupo candidato. Cada miembro se consulta una
+    /// sola vez aunque aparezca en varios grupos; un grupo que no se puede tasar (miembro sin
+    /// Aguayo o ilegible) se reporta como (false, 0, 0) en vez de revertir todo el lote
+    pub fn evaluate_groups(&self, groups: Vec<Vec<Address>>) -> Result<Vec<(bool, U256, U256)>, Vec<u8>> {
+        if groups.is_empty() || groups.iter().any(|group| group.is_empty()) {
+            return Err(EmptyMemberList {}.abi_encode());
+        }
+        let member_count: usize = groups.iter().map(|group| group.len()).sum();
+        if member_count as u64 > MAX_BATCH_MEMBERS {
+            return Err(BatchTooLarge {
+                member_count: U256::from(member_count),
+                max_members: U256::from(MAX_BATCH_MEMBERS),
+            }
+            .abi_encode());
+        }
+
+        let mut cache: BTreeMap<Address, MemberSnapshot> = BTreeMap::new();
+        let mut results = Vec::with_capacity(groups.len());
+        for group in groups {
+            let mut snapshots = Vec::with_capacity(group.len());
+            for member in group {
+                let snapshot = cache.entry(member).or_insert_with(|| {
+                    let mut snapshot = self.snapshot(member, true);
+                    if snapshot.token_id != Some(U256::ZERO) {
+                        snapshot.reputation = Some(self.member_reputation(&snapshot));
+                    }
+                    snapshot
+                });
+                snapshots.push(snapshot.clone());
+            }
 
-            if token_id == U256::ZERO {
-                weights.push(U256::ZERO);
+            if snapshots.iter().any(|snapshot| self.member_profile(snapshot).is_err()) {
+                results.push((false, U256::ZERO, U256::ZERO));
                 continue;
             }
+            let eligible = snapshots
+                .iter()
+                .all(|snapshot| self.credit_eligible(snapshot) == Some(true));
+            let pricing = self.price_group(&snapshots)?;
+            results.push((eligible, pricing.multiplier, pricing.interest_rate_bps));
+        }
 
-            let level = match aguayo_sbt.get_level(self, token_id) {
-                Ok(lvl) => lvl,
-                Err(_) => {
-                    weights.push(U256::ZERO);
-                    continue;
-                }
-            };
+        Ok(results)
+    }
 
-            // Weight = 10 + level
-            let weight = U256::from(10) + U256::from(level);
-            weights.push(weight);
+    /// Explica el resultado de `get_leverage_level` para un grupo propuesto
+    pub fn get_risk_report(&self, members: Vec<Address>) -> Result<RiskReport, Vec<u8>> {
+        if members.is_empty() {
+            return Err(EmptyMemberList {}.abi_encode());
         }
 
-        Ok(weights)
+        let snapshots: Vec<MemberSnapshot> =
+            members.iter().map(|member| self.snapshot(*member, false)).collect();
+        let pricing = self.price_group(&snapshots)?;
+
+        let mut member_levels = Vec::with_capacity(snapshots.len());
+        let mut member_stained = Vec::with_capacity(snapshots.len());
+        for snapshot in snapshots.iter() {
+            let metadata = self.member_profile(snapshot)?;
+            member_levels.push(metadata.level);
+            member_stained.push(metadata.isStained);
+        }
+
+        let stats = pricing.stats;
+        Ok(RiskReport {
+            tierId: U256::from(pricing.tier),
+            averageLevelX100: stats.mean_level_x100,
+            minLevel: stats.min_level,
+            minLevelCapped: pricing.min_level_capped,
+            levelVarianceX100: stats.level_variance_x100,
+            newMemberShareBps: stats.new_member_bps,
+            groupScore: stats.group_score,
+            memberLevels: member_levels,
+            memberStained: member_stained,
+            memberScores: stats.member_scores,
+            memberConfidence: stats.member_confidence,
+            tierMultiplier: pricing.tier_multiplier,
+            stainLeverageReduction: pricing.stain_leverage_reduction,
+            dispersionPenaltyBps: pricing.dispersion_penalty_bps,
+            dispersionLeverageReduction: pricing.dispersion_leverage_reduction,
+            leverageCapped: pricing.leverage_capped,
+            multiplier: pricing.multiplier,
+            baseRateBps: pricing.base_rate_bps,
+            tierSpreadBps: pricing.tier_spread_bps,
+            stainPremiumBps: pricing.stain_premium_bps,
+            rateCapped: pricing.rate_capped,
+            interestRateBps: pricing.interest_rate_bps,
+        })
     }
 
-    /// Valida si un miembro específico califica para un modo
-    pub fn is_member_eligible(&self, member: Address, is_credit_mode: bool) -> Result<bool, Vec<u8>> {
-        let aguayo_sbt_addr = self.aguayo_sbt.get();
-        let aguayo_sbt = IAguayoSBT::new(aguayo_sbt_addr);
+    /// Credit score continuo (0-10000) de un miembro, combinando todos los proveedores
+    pub fn get_member_score(&self, member: Address) -> Result<U256, Vec<u8>> {
+        self.get_member_reputation(member).map(|(score, _)| score)
+    }
 
-        let token_id = match aguayo_sbt.user_to_aguayo(self, member) {
-            Ok(id) => id,
-            Err(_) => return Ok(false),
-        };
+    /// (score combinado, confianza en bps = peso de los proveedores que respondieron)
+    pub fn get_member_reputation(&self, member: Address) -> Result<(U256, U256), Vec<u8>> {
+        let snapshot = self.snapshot(member, false);
+        let reputation = self.member_reputation(&snapshot);
+        snapshot.pricing_metadata(|| reputation)?;
+        Ok(reputation)
+    }
 
-        if token_id == U256::ZERO {
-            return Ok(false);
+    /// Reporta cómo terminó el préstamo de la cotización `quote_id`, ya consumida. El tier es
+    /// el que el oracle tasó al cotizar y los niveles los de cada miembro al consumirla;
+    /// `loss_bps` es la pérdida sobre el préstamo y se anualiza con la duración cotizada
+    pub fn record_circle_outcome(
+        &mut self,
+        members: Vec<Address>,
+        quote_id: U256,
+        defaulted_members: Vec<Address>,
+        loss_bps: U256,
+    ) -> Result<(), Vec<u8>> {
+        let reporter = self.vm().msg_sender();
+        if !self.outcome_reporters.get(reporter) {
+            return Err(Unauthorized {}.abi_encode());
+        }
+        if members.is_empty() {
+            return Err(EmptyMemberList {}.abi_encode());
+        }
+        if loss_bps > U256::from(MAX_SCORE) {
+            return Err(InvalidParameter {}.abi_encode());
         }
 
-        if !is_credit_mode {
-            return Ok(true);
+        let (member_hash, _, duration_in_days, _, _, _, used) = self.get_quote(quote_id)?;
+        if !used {
+            return Err(QuoteNotConsumed { quote_id }.abi_encode());
+        }
+        if self.quote_outcome_recorded.get(quote_id) {
+            return Err(OutcomeAlreadyRecorded { quote_id }.abi_encode());
         }
+        if self.get_member_set_hash(members.clone()) != member_hash {
+            return Err(QuoteMembersMismatch { quote_id }.abi_encode());
+        }
+        self.reject_denylisted(&members)?;
+
+        // Cada moroso debe ser miembro y aparecer una sola vez
+        let mut defaulted = vec![false; members.len()];
+        for member in defaulted_members.iter() {
+            match members.iter().position(|m| m == member) {
+                Some(i) if !defaulted[i] => defaulted[i] = true,
+                _ => return Err(InvalidParameter {}.abi_encode()),
+            }
+        }
+
+        for (member, defaulted) in members.iter().zip(defaulted) {
+            let level = U256::from(self.quote_member_levels.getter(quote_id).get(*member).to::<u8>());
+            let count = self.level_member_outcomes.get(level);
+            self.level_member_outcomes.insert(level, count + U256::from(1));
+            if defaulted {
+                let count = self.level_member_defaults.get(level);
+                self.level_member_defaults.insert(level, count + U256::from(1));
+            }
+        }
+
+        // Los spreads son anuales: una pérdida en 90 días pesa cuatro veces más por año
+        let annual_loss_bps = (loss_bps * U256::from(365) / duration_in_days).min(U256::from(MAX_SCORE));
+        let tier_key = self.quote_tier_key.get(quote_id);
+        let member_count = U256::from(members.len());
+        let defaulted_count = U256::from(defaulted_members.len());
+        let circles = self.tier_circle_outcomes.get(tier_key);
+        self.tier_circle_outcomes.insert(tier_key, circles + U256::from(1));
+        let outcomes = self.tier_member_outcomes.get(tier_key);
+        self.tier_member_outcomes.insert(tier_key, outcomes + member_count);
+        let defaults = self.tier_member_defaults.get(tier_key);
+        self.tier_member_defaults.insert(tier_key, defaults + defaulted_count);
+        let losses = self.tier_loss_bps_total.get(tier_key);
+        self.tier_loss_bps_total.insert(tier_key, losses + annual_loss_bps);
+        self.quote_outcome_recorded.insert(quote_id, true);
+
+        log(self.vm(), CircleOutcomeRecorded {
+            reporter,
+            quote_id,
+            tier_key,
+            member_count,
+            defaulted_count,
+            loss_bps,
+            annual_loss_bps,
+        });
+        Ok(())
+    }
+
+    /// (círculos, miembros, morosos, tasa de default en bps, pérdida anual promedio en bps) de un tier
+    pub fn get_tier_outcomes(&self, tier_id: U256) -> (U256, U256, U256, U256, U256) {
+        let key = self.get_tier_key(tier_id).unwrap_or_default();
+        let circles = self.tier_circle_outcomes.get(key);
+        let members = self.tier_member_outcomes.get(key);
+        let defaults = self.tier_member_defaults.get(key);
+        (
+            circles,
+            members,
+            defaults,
+            ratio_bps(defaults, members),
+            self.tier_loss_bps_total.get(key).checked_div(circles).unwrap_or_default(),
+        )
+    }
+
+    /// Clave estable del tier en la posición `tier_id`
+    pub fn get_tier_key(&self, tier_id: U256) -> Result<U256, Vec<u8>> {
+        let index: usize = tier_id.try_into().map_err(|_| InvalidTierId {}.abi_encode())?;
+        self.tier_keys.get(index).ok_or_else(|| InvalidTierId {}.abi_encode())
+    }
+
+    /// (miembros, morosos, tasa de default en bps) de un nivel de Aguayo
+    pub fn get_level_outcomes(&self, level: u8) -> (U256, U256, U256) {
+        let members = self.level_member_outcomes.get(U256::from(level));
+        let defaults = self.level_member_defaults.get(U256::from(level));
+        (members, defaults, ratio_bps(defaults, members))
+    }
 
-        aguayo_sbt.is_eligible_for_credit(self, token_id)
-            .map_err(|_| b"AguayoSBT call failed".to_vec())
+    /// Spread sugerido por tier: la pérdida anual promedio observada, sin bajar de la del tier superior;
+    /// los tiers con pocos círculos reportados mantienen su spread actual
+    pub fn suggest_tier_rates(&self) -> Vec<U256> {
+        let tiers = self.load_tiers();
+        let mut spreads: Vec<U256> = tiers.iter().map(|&(_, _, spread)| spread).collect();
+
+        let mut floor = U256::ZERO;
+        for (i, spread) in spreads.iter_mut().enumerate().rev() {
+            let (circles, _, _, _, average_loss) = self.get_tier_outcomes(U256::from(i));
+            if circles >= U256::from(MIN_OUTCOMES_FOR_RECALIBRATION) {
+                *spread = average_loss;
+            }
+            *spread = (*spread).max(floor);
+            floor = *spread;
+        }
+        spreads
+    }
+
+    pub fn is_outcome_reporter(&self, reporter: Address) -> bool {
+        self.outcome_reporters.get(reporter)
+    }
+
+    pub fn get_reputation_provider_count(&self) -> U256 {
+        U256::from(self.provider_addresses.len())
+    }
+
+    /// (address, kind, weight, full_score_stake)
+    pub fn get_reputation_provider(&self, provider_id: U256) -> Result<(Address, u8, U256, U256), Vec<u8>> {
+        let index: usize = provider_id.try_into().map_err(|_| InvalidProviderId {}.abi_encode())?;
+        self.load_providers()
+            .get(index)
+            .copied()
+            .ok_or_else(|| InvalidProviderId {}.abi_encode())
+    }
+
+    /// Cotiza leverage y tasa para el grupo y los guarda bajo un id hasta que expiren o se consuman
+    pub fn quote(&mut self, members: Vec<Address>, amount: U256, duration_in_days: U256) -> Result<U256, Vec<u8>> {
+        if amount.is_zero() || duration_in_days.is_zero() {
+            return Err(InvalidParameter {}.abi_encode());
+        }
+        if members.is_empty() {
+            return Err(EmptyMemberList {}.abi_encode());
+        }
+        self.reject_denylisted(&members)?;
+        let snapshots: Vec<MemberSnapshot> =
+            members.iter().map(|member| self.snapshot(*member, false)).collect();
+        let pricing = self.price_group(&snapshots)?;
+        let (multiplier, interest_rate) = (pricing.multiplier, pricing.interest_rate_bps);
+
+        let quote_id = self.next_quote_id.get() + U256::from(1);
+        self.next_quote_id.set(quote_id);
+
+        let member_hash = self.get_member_set_hash(members);
+        let expiry = U256::from(self.vm().block_timestamp()) + self.quote_validity.get();
+        self.quote_member_hash.insert(quote_id, member_hash);
+        self.quote_amount.insert(quote_id, amount);
+        self.quote_duration.insert(quote_id, duration_in_days);
+        self.quote_multiplier.insert(quote_id, multiplier);
+        self.quote_interest_rate.insert(quote_id, interest_rate);
+        self.quote_expiry.insert(quote_id, expiry);
+        self.quote_tier_key.insert(quote_id, self.tier_keys.get(pricing.tier).unwrap_or_default());
+
+        log(self.vm(), QuoteIssued {
+            quote_id,
+            requester: self.vm().msg_sender(),
+            member_hash,
+            amount,
+            duration_in_days,
+            multiplier,
+            interest_rate,
+            expiry,
+        });
+        Ok(quote_id)
+    }
+
+    /// Marca la cotización como usada y devuelve (multiplicador, tasa, monto, duración en días)
+    pub fn consume_quote(
+        &mut self,
+        quote_id: U256,
+        members: Vec<Address>,
+    ) -> Result<(U256, U256, U256, U256), Vec<u8>> {
+        let consumer = self.vm().msg_sender();
+        if !self.quote_consumers.get(consumer) {
+            return Err(Unauthorized {}.abi_encode());
+        }
+
+        let (member_hash, amount, duration_in_days, multiplier, interest_rate, expiry, used) =
+            self.get_quote(quote_id)?;
+        if used {
+            return Err(QuoteAlreadyUsed { quote_id }.abi_encode());
+        }
+        if U256::from(self.vm().block_timestamp()) > expiry {
+            return Err(QuoteExpired { quote_id, expiry }.abi_encode());
+        }
+        if self.get_member_set_hash(members.clone()) != member_hash {
+            return Err(QuoteMembersMismatch { quote_id }.abi_encode());
+        }
+        self.reject_denylisted(&members)?;
+
+        // Los resultados se cuentan con el nivel de origen aunque cambie durante el préstamo
+        for member in members {
+            let level = self.member_profile(&self.snapshot(member, false))?.level;
+            self.quote_member_levels.setter(quote_id).insert(member, U8::from(level));
+        }
+
+        self.quote_used.insert(quote_id, true);
+        log(self.vm(), QuoteConsumed { quote_id, consumer });
+        Ok((multiplier, interest_rate, amount, duration_in_days))
+    }
+
+    /// (hash de miembros, monto, duración en días, multiplicador, tasa, expiración, usada)
+    #[allow(clippy::type_complexity)]
+    pub fn get_quote(&self, quote_id: U256) -> Result<(B256, U256, U256, U256, U256, U256, bool), Vec<u8>> {
+        if quote_id.is_zero() || quote_id > self.next_quote_id.get() {
+            return Err(QuoteNotFound { quote_id }.abi_encode());
+        }
+        Ok((
+            self.quote_member_hash.get(quote_id),
+            self.quote_amount.get(quote_id),
+            self.quote_duration.get(quote_id),
+            self.quote_multiplier.get(quote_id),
+            self.quote_interest_rate.get(quote_id),
+            self.quote_expiry.get(quote_id),
+            self.quote_used.get(quote_id),
+        ))
+    }
+
+    /// keccak256 de los miembros ordenados: el orden en que se listan no cambia el hash
+    pub fn get_member_set_hash(&self, members: Vec<Address>) -> B256 {
+        let mut members = members;
+        members.sort();
+        let encoded: Vec<u8> = members.iter().flat_map(|member| member.into_word().0).collect();
+        self.vm().native_keccak256(&encoded)
+    }
+
+    pub fn quote_validity(&self) -> U256 {
+        self.quote_validity.get()
+    }
+
+    pub fn is_quote_consumer(&self, consumer: Address) -> bool {
+        self.quote_consumers.get(consumer)
+    }
+
+    pub fn draw_weight_mode(&self) -> u8 {
+        self.draw_weight_mode.get().to::<u8>()
+    }
+
+    /// En la denylist y sin vencer
+    pub fn is_denylisted(&self, member: Address) -> bool {
+        if self.denylist_index.get(member).is_zero() {
+            return false;
+        }
+        let expiry = self.deny_expiry.get(member);
+        expiry.is_zero() || U256::from(self.vm().block_timestamp()) < expiry
+    }
+
+    /// (listado, código de motivo, vencimiento, vigente)
+    pub fn get_denylist_entry(&self, member: Address) -> (bool, u8, U256, bool) {
+        (
+            !self.denylist_index.get(member).is_zero(),
+            self.deny_reason.get(member).to::<u8>(),
+            self.deny_expiry.get(member),
+            self.is_denylisted(member),
+        )
+    }
+
+    pub fn get_denylist_count(&self) -> U256 {
+        U256::from(self.denylist.len())
+    }
+
+    /// Página de la denylist como (miembro, motivo, vencimiento); incluye entradas vencidas
+    pub fn get_denylist(&self, offset: U256, limit: U256) -> Vec<(Address, u8, U256)> {
+        let total = self.denylist.len();
+        let start: usize = offset.try_into().unwrap_or(total).min(total);
+        let count: usize = limit.try_into().unwrap_or(total);
+        let end = start.saturating_add(count).min(total);
+
+        (start..end)
+            .filter_map(|i| self.denylist.get(i))
+            .map(|member| {
+                (
+                    member,
+                    self.deny_reason.get(member).to::<u8>(),
+                    self.deny_expiry.get(member),
+                )
+            })
+            .collect()
+    }
+
+    pub fn is_risk_manager(&self, manager: Address) -> bool {
+        self.risk_managers.get(manager)
+    }
+
+    // ========== RISK MANAGER FUNCTIONS ==========
+
+    /// Agrega o actualiza una entrada; `expiry` 0 la deja vigente hasta la rehabilitación
+    pub fn deny_member(&mut self, member: Address, reason: u8, expiry: U256) -> Result<(), Vec<u8>> {
+        self.only_risk_manager()?;
+
+        if member == Address::ZERO {
+            return Err(InvalidAddress {}.abi_encode());
+        }
+        if !expiry.is_zero() && expiry <= U256::from(self.vm().block_timestamp()) {
+            return Err(InvalidParameter {}.abi_encode());
+        }
+
+        if self.denylist_index.get(member).is_zero() {
+            self.denylist.push(member);
+            self.denylist_index.insert(member, U256::from(self.denylist.len()));
+        }
+        self.deny_reason.insert(member, U8::from(reason));
+        self.deny_expiry.insert(member, expiry);
+
+        log(self.vm(), MemberDenylisted {
+            member,
+            reason,
+            expiry,
+            manager: self.vm().msg_sender(),
+        });
+        Ok(())
+    }
+
+    /// Saca al miembro de la denylist, vencida o no
+    pub fn rehabilitate_member(&mut self, member: Address) -> Result<(), Vec<u8>> {
+        self.only_risk_manager()?;
+
+        let position = self.denylist_index.get(member);
+        if position.is_zero() {
+            return Err(MemberNotDenylisted { member }.abi_encode());
+        }
+
+        let index = (position - U256::from(1)).to::<usize>();
+        let last_index = self.denylist.len() - 1;
+        if index != last_index {
+            if let Some(last) = self.denylist.get(last_index) {
+                if let Some(mut slot) = self.denylist.setter(index) {
+                    slot.set(last);
+                }
+                self.denylist_index.insert(last, position);
+            }
+        }
+        self.denylist.erase_last();
+        self.denylist_index.delete(member);
+        self.deny_reason.delete(member);
+        self.deny_expiry.delete(member);
+
+        log(self.vm(), MemberRehabilitated {
+            member,
+            manager: self.vm().msg_sender(),
+        });
+        Ok(())
     }
 
     // ========== VIEW FUNCTIONS ==========
@@ -271,24 +941,29 @@ impl RiskOracle {
 
     pub fn get_leverage_tier(&self, tier_id: U256) -> Result<(u8, U256, U256), Vec<u8>> {
         let tier_count = self.tier_min_levels.len();
-        let tier_id_usize: usize = tier_id.try_into().map_err(|_| b"Invalid tier ID".to_vec())?;
+        let tier_id_usize: usize = tier_id.try_into().map_err(|_| InvalidTierId {}.abi_encode())?;
 
         if tier_id_usize >= tier_count {
-            return Err(b"Invalid tier ID".to_vec());
+            return Err(InvalidTierId {}.abi_encode());
         }
 
         let min_level: u8 = self.tier_min_levels.get(tier_id_usize)
-            .ok_or(b"Invalid tier ID".to_vec())?
+            .ok_or_else(|| InvalidTierId {}.abi_encode())?
             .try_into()
-            .map_err(|_| b"Invalid tier ID".to_vec())?;
+            .map_err(|_| InvalidTierId {}.abi_encode())?;
         let multiplier = self.tier_multipliers.get(tier_id_usize)
-            .ok_or(b"Invalid tier ID".to_vec())?;
+            .ok_or_else(|| InvalidTierId {}.abi_encode())?;
         let interest_rate = self.tier_interest_rates.get(tier_id_usize)
-            .ok_or(b"Invalid tier ID".to_vec())?;
+            .ok_or_else(|| InvalidTierId {}.abi_encode())?;
 
         Ok((min_level, multiplier, interest_rate))
     }
 
+    /// Tabla completa de tiers, ordenada por nivel mínimo
+    pub fn get_all_tiers(&self) -> Vec<(u8, U256, U256)> {
+        self.load_tiers()
+    }
+
     pub fn owner(&self) -> Address {
         self.owner.get()
     }
@@ -313,6 +988,38 @@ impl RiskOracle {
         self.risk_premium_per_stain_bps.get()
     }
 
+    /// (threads, circles, history, stain penalty, decay half-life)
+    pub fn get_score_weights(&self) -> (U256, U256, U256, U256, U256) {
+        (
+            self.score_weight_threads.get(),
+            self.score_weight_circles.get(),
+            self.score_weight_history.get(),
+            self.stain_penalty_bps.get(),
+            self.score_decay_half_life.get(),
+        )
+    }
+
+    /// (penalización por varianza de niveles, penalización con todo el grupo nuevo)
+    pub fn get_dispersion_penalty(&self) -> (U256, U256) {
+        (self.variance_penalty_bps.get(), self.new_member_penalty_bps.get())
+    }
+
+    /// (paso máximo, intervalo mínimo en segundos) vigentes de un parámetro PARAM_*
+    pub fn get_parameter_limits(&self, parameter: u8) -> Result<(U256, U256), Vec<u8>> {
+        Ok(self.parameter_limits(parameter_key(parameter)?))
+    }
+
+    /// (paso máximo, intervalo mínimo, desde cuándo rigen) de un relajamiento aún no vigente;
+    /// ceros si no hay ninguno
+    pub fn get_pending_parameter_limits(&self, parameter: u8) -> Result<(U256, U256, U256), Vec<u8>> {
+        let key = parameter_key(parameter)?;
+        let effective_at = self.pending_limits_at.get(key);
+        if effective_at.is_zero() || U256::from(self.vm().block_timestamp()) >= effective_at {
+            return Ok((U256::ZERO, U256::ZERO, U256::ZERO));
+        }
+        Ok((self.pending_max_step.get(key), self.pending_min_interval.get(key), effective_at))
+    }
+
     // ========== OWNER FUNCTIONS ==========
 
     pub fn add_leverage_tier(
@@ -323,25 +1030,20 @@ impl RiskOracle {
     ) -> Result<(), Vec<u8>> {
         self.only_owner()?;
 
-        let max_leverage = self.max_leverage_multiplier.get();
-        if multiplier == U256::ZERO || multiplier > max_leverage {
-            return Err(b"Invalid parameter".to_vec());
-        }
-
-        if interest_rate_bps == U256::ZERO || interest_rate_bps > U256::from(10000) {
-            return Err(b"Invalid parameter".to_vec());
-        }
-
-        self.tier_min_levels.push(U256::from(min_average_level));
-        self.tier_multipliers.push(multiplier);
-        self.tier_interest_rates.push(interest_rate_bps);
-
-        // Event logging disabled for SDK 0.8.4 compatibility
-        // evm::log(LeverageTierAdded {
-        //     min_average_level,
-        //     multiplier,
-        //     interest_rate: interest_rate_bps,
-        // });
+        // El nuevo tier debe quedar por encima de todos los existentes
+        let mut tiers = self.load_tiers();
+        tiers.push((min_average_level, multiplier, interest_rate_bps));
+        self.validate_tiers(&tiers)?;
+        let mut keys = self.load_tier_keys();
+        keys.push(self.new_tier_key());
+        self.record_tier_table_change(&tiers, &keys)?;
+        self.store_tiers(&tiers, &keys);
+
+        log(self.vm(), LeverageTierAdded {
+            min_average_level,
+            multiplier,
+            interest_rate: interest_rate_bps,
+        });
 
         Ok(())
     }
@@ -356,20 +1058,16 @@ impl RiskOracle {
         self.only_owner()?;
 
         let tier_count = self.tier_min_levels.len();
-        let tier_id_usize: usize = tier_id.try_into().map_err(|_| b"Invalid tier ID".to_vec())?;
+        let tier_id_usize: usize = tier_id.try_into().map_err(|_| InvalidTierId {}.abi_encode())?;
 
         if tier_id_usize >= tier_count {
-            return Err(b"Invalid tier ID".to_vec());
+            return Err(InvalidTierId {}.abi_encode());
         }
 
-        let max_leverage = self.max_leverage_multiplier.get();
-        if multiplier == U256::ZERO || multiplier > max_leverage {
-            return Err(b"Invalid parameter".to_vec());
-        }
-
-        if interest_rate_bps == U256::ZERO || interest_rate_bps > U256::from(10000) {
-            return Err(b"Invalid parameter".to_vec());
-        }
+        let mut tiers = self.load_tiers();
+        tiers[tier_id_usize] = (min_average_level, multiplier, interest_rate_bps);
+        self.validate_tiers(&tiers)?;
+        self.record_tier_table_change(&tiers, &self.load_tier_keys())?;
 
         if let Some(mut level) = self.tier_min_levels.get_mut(tier_id_usize) {
             level.set(U256::from(min_average_level));
@@ -381,21 +1079,59 @@ impl RiskOracle {
             rate.set(interest_rate_bps);
         }
 
-        // Event logging disabled for SDK 0.8.4 compatibility
-        // evm::log(LeverageTierUpdated {
-        //     tier_id,
-        //     min_average_level,
-        //     multiplier,
-        //     interest_rate: interest_rate_bps,
-        // });
+        log(self.vm(), LeverageTierUpdated {
+            tier_id,
+            min_average_level,
+            multiplier,
+            interest_rate: interest_rate_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Elimina un tier; los tiers superiores bajan una posición
+    pub fn remove_leverage_tier(&mut self, tier_id: U256) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        let mut tiers = self.load_tiers();
+        let index: usize = tier_id.try_into().map_err(|_| InvalidTierId {}.abi_encode())?;
+        if index >= tiers.len() {
+            return Err(InvalidTierId {}.abi_encode());
+        }
+        if tiers.len() == 1 {
+            return Err(NoTiersConfigured {}.abi_encode());
+        }
+
+        let mut keys = self.load_tier_keys();
+        tiers.remove(index);
+        keys.remove(index);
+        self.record_tier_table_change(&tiers, &keys)?;
+        self.store_tiers(&tiers, &keys);
+
+        log(self.vm(), LeverageTierRemoved { tier_id });
+        Ok(())
+    }
+
+    /// Reemplaza la tabla completa de forma atómica
+    pub fn replace_all_tiers(&mut self, tiers: Vec<(u8, U256, U256)>) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        if tiers.is_empty() {
+            return Err(NoTiersConfigured {}.abi_encode());
+        }
+        self.validate_tiers(&tiers)?;
+        let keys: Vec<U256> = tiers.iter().map(|_| self.new_tier_key()).collect();
+        self.record_tier_table_change(&tiers, &keys)?;
+        self.store_tiers(&tiers, &keys);
 
+        log(self.vm(), LeverageTiersReplaced { tier_count: U256::from(tiers.len()) });
         Ok(())
     }
 
     pub fn set_min_level_for_credit(&mut self, new_min_level: u8) -> Result<(), Vec<u8>> {
         self.only_owner()?;
         self.min_level_for_credit.set(U8::from(new_min_level));
-        // evm::log(MinLevelForCreditUpdated { new_min_level });
+        log(self.vm(), MinLevelForCreditUpdated { new_min_level });
         Ok(())
     }
 
@@ -403,11 +1139,17 @@ impl RiskOracle {
         self.only_owner()?;
 
         if new_max_multiplier == U256::ZERO || new_max_multiplier > U256::from(2000) {
-            return Err(b"Invalid parameter".to_vec());
+            return Err(InvalidParameter {}.abi_encode());
         }
+        // Ningún tier puede quedar por encima del nuevo tope
+        if self.load_tiers().iter().any(|&(_, multiplier, _)| multiplier > new_max_multiplier) {
+            return Err(InvalidParameter {}.abi_encode());
+        }
+        let current = self.max_leverage_multiplier.get();
+        self.record_parameter_change(PARAM_MAX_LEVERAGE, current, new_max_multiplier)?;
 
         self.max_leverage_multiplier.set(new_max_multiplier);
-        // evm::log(MaxLeverageUpdated { new_max_multiplier });
+        log(self.vm(), MaxLeverageUpdated { new_max_multiplier });
         Ok(())
     }
 
@@ -415,11 +1157,13 @@ impl RiskOracle {
         self.only_owner()?;
 
         if new_rate_bps == U256::ZERO || new_rate_bps > U256::from(10000) {
-            return Err(b"Invalid parameter".to_vec());
+            return Err(InvalidParameter {}.abi_encode());
         }
+        let current = self.base_interest_rate_bps.get();
+        self.record_parameter_change(PARAM_BASE_RATE, current, new_rate_bps)?;
 
         self.base_interest_rate_bps.set(new_rate_bps);
-        // evm::log(BaseInterestRateUpdated { new_rate_bps });
+        log(self.vm(), BaseInterestRateUpdated { new_rate_bps });
         Ok(())
     }
 
@@ -427,11 +1171,250 @@ impl RiskOracle {
         self.only_owner()?;
 
         if new_premium_bps > U256::from(1000) {
-            return Err(b"Invalid parameter".to_vec());
+            return Err(InvalidParameter {}.abi_encode());
         }
+        let current = self.risk_premium_per_stain_bps.get();
+        self.record_parameter_change(PARAM_RISK_PREMIUM, current, new_premium_bps)?;
 
         self.risk_premium_per_stain_bps.set(new_premium_bps);
-        // evm::log(RiskPremiumUpdated { new_premium_bps });
+        log(self.vm(), RiskPremiumUpdated { new_premium_bps });
+        Ok(())
+    }
+
+    pub fn set_score_weights(
+        &mut self,
+        threads_weight: U256,
+        circles_weight: U256,
+        history_weight: U256,
+        stain_penalty_bps: U256,
+        decay_half_life: U256,
+    ) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        if threads_weight + circles_weight + history_weight != U256::from(MAX_SCORE) {
+            return Err(InvalidParameter {}.abi_encode());
+        }
+        if stain_penalty_bps > U256::from(MAX_SCORE) || decay_half_life == U256::ZERO {
+            return Err(InvalidParameter {}.abi_encode());
+        }
+
+        self.score_weight_threads.set(threads_weight);
+        self.score_weight_circles.set(circles_weight);
+        self.score_weight_history.set(history_weight);
+        self.stain_penalty_bps.set(stain_penalty_bps);
+        self.score_decay_half_life.set(decay_half_life);
+
+        log(self.vm(), ScoreWeightsUpdated {
+            threads_weight,
+            circles_weight,
+            history_weight,
+            stain_penalty_bps,
+            decay_half_life,
+        });
+        Ok(())
+    }
+
+    /// Paso máximo (0 = sin límite) e intervalo mínimo entre cambios de un parámetro PARAM_*.
+    /// Un paso sin intervalo se saltaría encadenando cambios, así que exige intervalo.
+    /// Endurecerlos rige de inmediato; relajarlos rige recién cuando pasa el intervalo vigente
+    pub fn set_parameter_limits(&mut self, parameter: u8, max_step: U256, min_interval: U256) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        let key = parameter_key(parameter)?;
+        if !max_step.is_zero() && min_interval.is_zero() {
+            return Err(InvalidParameter {}.abi_encode());
+        }
+        self.settle_parameter_limits(key);
+        let current_step = self.param_max_step.get(key);
+        let current_interval = self.param_min_interval.get(key);
+
+        let relaxes_step = !current_step.is_zero() && (max_step.is_zero() || max_step > current_step);
+        let effective_at = U256::from(self.vm().block_timestamp()) + current_interval;
+        if relaxes_step || min_interval < current_interval {
+            self.pending_max_step.insert(key, max_step);
+            self.pending_min_interval.insert(key, min_interval);
+            self.pending_limits_at.insert(key, effective_at);
+            log(self.vm(), ParameterLimitsScheduled { parameter, max_step, min_interval, effective_at });
+            return Ok(());
+        }
+
+        self.param_max_step.insert(key, max_step);
+        self.param_min_interval.insert(key, min_interval);
+        self.pending_limits_at.insert(key, U256::ZERO);
+
+        log(self.vm(), ParameterLimitsUpdated { parameter, max_step, min_interval });
+        Ok(())
+    }
+
+    /// `variance_penalty_bps` por unidad de varianza de niveles; `new_member_penalty_bps`
+    /// escalado por la fracción de miembros sin círculos completados
+    pub fn set_dispersion_penalty(
+        &mut self,
+        variance_penalty_bps: U256,
+        new_member_penalty_bps: U256,
+    ) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        if variance_penalty_bps > U256::from(MAX_SCORE) || new_member_penalty_bps > U256::from(MAX_SCORE) {
+            return Err(InvalidParameter {}.abi_encode());
+        }
+
+        self.variance_penalty_bps.set(variance_penalty_bps);
+        self.new_member_penalty_bps.set(new_member_penalty_bps);
+
+        log(self.vm(), DispersionPenaltyUpdated {
+            variance_penalty_bps,
+            new_member_penalty_bps,
+        });
+        Ok(())
+    }
+
+    /// Registra un proveedor de reputación; `full_score_stake` solo aplica a `PROVIDER_STAKING`
+    pub fn add_reputation_provider(
+        &mut self,
+        provider: Address,
+        kind: u8,
+        weight: U256,
+        full_score_stake: U256,
+    ) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        if provider == Address::ZERO {
+            return Err(InvalidAddress {}.abi_encode());
+        }
+        validate_provider(kind, weight, full_score_stake)?;
+
+        let provider_id = U256::from(self.provider_addresses.len());
+        self.provider_addresses.push(provider);
+        self.provider_kinds.push(U256::from(kind));
+        self.provider_weights.push(weight);
+        self.provider_full_score_stakes.push(full_score_stake);
+
+        log(self.vm(), ReputationProviderAdded { provider_id, provider, kind, weight });
+        Ok(())
+    }
+
+    pub fn update_reputation_provider(
+        &mut self,
+        provider_id: U256,
+        weight: U256,
+        full_score_stake: U256,
+    ) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        let (_, kind, _, _) = self.get_reputation_provider(provider_id)?;
+        validate_provider(kind, weight, full_score_stake)?;
+
+        let index = provider_id.to::<usize>();
+        if let Some(mut slot) = self.provider_weights.get_mut(index) {
+            slot.set(weight);
+        }
+        if let Some(mut slot) = self.provider_full_score_stakes.get_mut(index) {
+            slot.set(full_score_stake);
+        }
+
+        log(self.vm(), ReputationProviderUpdated { provider_id, weight, full_score_stake });
+        Ok(())
+    }
+
+    /// Elimina un proveedor; los siguientes bajan una posición
+    pub fn remove_reputation_provider(&mut self, provider_id: U256) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        self.get_reputation_provider(provider_id)?;
+        let mut providers = self.load_providers();
+        if providers.len() == 1 {
+            return Err(NoProvidersConfigured {}.abi_encode());
+        }
+
+        providers.remove(provider_id.to::<usize>());
+        self.store_providers(&providers);
+
+        log(self.vm(), ReputationProviderRemoved { provider_id });
+        Ok(())
+    }
+
+    pub fn set_risk_manager(&mut self, manager: Address, authorized: bool) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        if manager == Address::ZERO {
+            return Err(InvalidAddress {}.abi_encode());
+        }
+
+        self.risk_managers.insert(manager, authorized);
+        log(self.vm(), RiskManagerUpdated { manager, authorized });
+        Ok(())
+    }
+
+    pub fn set_draw_weight_mode(&mut self, mode: u8) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        if mode > DRAW_REPUTATION {
+            return Err(InvalidParameter {}.abi_encode());
+        }
+
+        self.draw_weight_mode.set(U8::from(mode));
+        log(self.vm(), DrawWeightModeUpdated { mode });
+        Ok(())
+    }
+
+    /// Autoriza al vault o al factory a consumir cotizaciones
+    pub fn set_quote_consumer(&mut self, consumer: Address, authorized: bool) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        if consumer == Address::ZERO {
+            return Err(InvalidAddress {}.abi_encode());
+        }
+
+        self.quote_consumers.insert(consumer, authorized);
+        log(self.vm(), QuoteConsumerUpdated { consumer, authorized });
+        Ok(())
+    }
+
+    pub fn set_quote_validity(&mut self, validity: U256) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        if validity.is_zero() || validity > U256::from(MAX_QUOTE_VALIDITY) {
+            return Err(InvalidParameter {}.abi_encode());
+        }
+
+        self.quote_validity.set(validity);
+        log(self.vm(), QuoteValidityUpdated { validity });
+        Ok(())
+    }
+
+    /// Autoriza a un círculo o al vault a reportar resultados
+    pub fn set_outcome_reporter(&mut self, reporter: Address, authorized: bool) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        if reporter == Address::ZERO {
+            return Err(InvalidAddress {}.abi_encode());
+        }
+
+        self.outcome_reporters.insert(reporter, authorized);
+        log(self.vm(), OutcomeReporterUpdated { reporter, authorized });
+        Ok(())
+    }
+
+    /// Aplica `suggest_tier_rates`; `expected_spreads` es la sugerencia que revisó el owner
+    pub fn apply_tier_recalibration(&mut self, expected_spreads: Vec<U256>) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+
+        let spreads = self.suggest_tier_rates();
+        if spreads != expected_spreads {
+            return Err(RecalibrationMismatch {}.abi_encode());
+        }
+
+        let mut tiers = self.load_tiers();
+        for (tier, &spread) in tiers.iter_mut().zip(spreads.iter()) {
+            tier.2 = spread;
+        }
+        self.validate_tiers(&tiers)?;
+        let keys = self.load_tier_keys();
+        self.record_tier_table_change(&tiers, &keys)?;
+        self.store_tiers(&tiers, &keys);
+
+        log(self.vm(), TierRatesRecalibrated { spreads });
         Ok(())
     }
 
@@ -439,87 +1422,775 @@ impl RiskOracle {
         self.only_owner()?;
 
         if new_owner == Address::ZERO {
-            return Err(b"Invalid address".to_vec());
+            return Err(InvalidAddress {}.abi_encode());
         }
 
         let previous_owner = self.owner.get();
         self.owner.set(new_owner);
 
-        // evm::log(OwnershipTransferred { previous_owner, new_owner });
+        log(self.vm(), OwnershipTransferred { previous_owner, new_owner });
         Ok(())
     }
+}
+
+// ========== INTERNAL FUNCTIONS ==========
 
-    // ========== INTERNAL FUNCTIONS ==========
+impl RiskOracle {
 
     fn only_owner(&self) -> Result<(), Vec<u8>> {
-        // TODO: Implement proper owner check using .vm() method
-        // For now, skip owner check in development
-        // if msg::sender() != self.owner.get() {
-        //     return Err(b"Unauthorized".to_vec());
-        // }
+        if self.vm().msg_sender() != self.owner.get() {
+            return Err(Unauthorized {}.abi_encode());
+        }
         Ok(())
     }
 
-    fn get_group_stats(&self, members: Vec<Address>) -> Result<(u8, U256), Vec<u8>> {
-        let aguayo_sbt_addr = self.aguayo_sbt.get();
-        let aguayo_sbt = IAguayoSBT::new(aguayo_sbt_addr);
+    fn only_risk_manager(&self) -> Result<(), Vec<u8>> {
+        let sender = self.vm().msg_sender();
+        if sender != self.owner.get() && !self.risk_managers.get(sender) {
+            return Err(Unauthorized {}.abi_encode());
+        }
+        Ok(())
+    }
+}
 
-        let mut total_level = U256::ZERO;
-        let mut stained_count = U256::ZERO;
+// ========== PARAMETER LIMITS ==========
 
-        for member in members.iter() {
-            let token_id = aguayo_sbt.user_to_aguayo(self, *member)
-                .map_err(|_| b"Member not eligible".to_vec())?;
+impl RiskOracle {
+    /// Valida paso e intervalo de un parámetro escalar y registra el momento del cambio
+    fn record_parameter_change(&mut self, parameter: u8, current: U256, requested: U256) -> Result<(), Vec<u8>> {
+        let key = U256::from(parameter);
+        self.check_step(parameter, current, requested)?;
+        self.check_interval(parameter, self.param_last_change.get(key))?;
+        self.param_last_change.insert(key, U256::from(self.vm().block_timestamp()));
+        Ok(())
+    }
 
-            if token_id == U256::ZERO {
-                return Err(b"Member not eligible".to_vec());
+    /// Igual que `record_parameter_change` para la tabla de tiers: compara los términos
+    /// vigentes en cada nivel de corte (0 y el mínimo de cada tier, viejo o nuevo), así
+    /// agregar, quitar, mover o reemplazar tiers cuenta como cambio. El intervalo se cuenta
+    /// por clave de tier
+    fn record_tier_table_change(&mut self, tiers: &[Tier], keys: &[U256]) -> Result<(), Vec<u8>> {
+        let current = self.load_tiers();
+        let current_keys = self.load_tier_keys();
+
+        let mut levels: Vec<u8> = current.iter().chain(tiers).map(|tier| tier.0).collect();
+        levels.push(0);
+        levels.sort_unstable();
+        levels.dedup();
+
+        let mut changed_keys = Vec::new();
+        for level in levels {
+            let from = tier_index_for_level(&current, level);
+            let to = tier_index_for_level(tiers, level);
+            let mut changed = false;
+            for (parameter, current, requested) in [
+                (PARAM_TIER_MULTIPLIER, current[from].1, tiers[to].1),
+                (PARAM_TIER_RATE, current[from].2, tiers[to].2),
+            ] {
+                if current != requested {
+                    self.check_step(parameter, current, requested)?;
+                    self.check_interval(parameter, self.tier_last_change.get(current_keys[from]))?;
+                    self.check_interval(parameter, self.tier_last_change.get(keys[to]))?;
+                    changed = true;
+                }
             }
+            if changed {
+                changed_keys.push(current_keys[from]);
+                changed_keys.push(keys[to]);
+            }
+        }
+
+        let now = U256::from(self.vm().block_timestamp());
+        for key in changed_keys {
+            self.tier_last_change.insert(key, now);
+        }
+        Ok(())
+    }
 
-            let metadata = aguayo_sbt.get_aguayo_metadata(self, token_id)
-                .map_err(|_| b"Failed to get metadata".to_vec())?;
+    fn check_step(&self, parameter: u8, current: U256, requested: U256) -> Result<(), Vec<u8>> {
+        let (max_step, _) = self.parameter_limits(U256::from(parameter));
+        let step = current.max(requested) - current.min(requested);
+        if !max_step.is_zero() && step > max_step {
+            return Err(ParameterStepExceeded { parameter, current, requested, max_step }.abi_encode());
+        }
+        Ok(())
+    }
+
+    /// `last_change` 0 = nunca cambió
+    fn check_interval(&self, parameter: u8, last_change: U256) -> Result<(), Vec<u8>> {
+        let (_, min_interval) = self.parameter_limits(U256::from(parameter));
+        let next_change_at = last_change + min_interval;
+        if !last_change.is_zero() && U256::from(self.vm().block_timestamp()) < next_change_at {
+            return Err(ParameterChangeTooSoon { parameter, next_change_at }.abi_encode());
+        }
+        Ok(())
+    }
+
+    /// (paso máximo, intervalo mínimo) vigentes, incluido un relajamiento programado ya cumplido
+    fn parameter_limits(&self, key: U256) -> (U256, U256) {
+        let effective_at = self.pending_limits_at.get(key);
+        if !effective_at.is_zero() && U256::from(self.vm().block_timestamp()) >= effective_at {
+            return (self.pending_max_step.get(key), self.pending_min_interval.get(key));
+        }
+        (self.param_max_step.get(key), self.param_min_interval.get(key))
+    }
+
+    /// Pasa a los límites vigentes un relajamiento programado que ya se cumplió
+    fn settle_parameter_limits(&mut self, key: U256) {
+        let effective_at = self.pending_limits_at.get(key);
+        if !effective_at.is_zero() && U256::from(self.vm().block_timestamp()) >= effective_at {
+            let (max_step, min_interval) = (self.pending_max_step.get(key), self.pending_min_interval.get(key));
+            self.param_max_step.insert(key, max_step);
+            self.param_min_interval.insert(key, min_interval);
+            self.pending_limits_at.insert(key, U256::ZERO);
+        }
+    }
+}
+
+/// Índice del tier más alto cuyo nivel mínimo alcanza `level`; 0 si ninguno
+fn tier_index_for_level(tiers: &[Tier], level: u8) -> usize {
+    tiers.iter().rposition(|tier| tier.0 <= level).unwrap_or(0)
+}
 
-            total_level = total_level + U256::from(metadata.level);
+fn parameter_key(parameter: u8) -> Result<U256, Vec<u8>> {
+    if parameter > PARAM_TIER_RATE {
+        return Err(InvalidParameter {}.abi_encode());
+    }
+    Ok(U256::from(parameter))
+}
+
+// ========== MEMBER SNAPSHOTS ==========
+
+/// Todo lo que el oracle lee del AguayoSBT para un miembro, consultado una sola vez
+#[derive(Clone)]
+struct MemberSnapshot {
+    member: Address,
+    /// `None` si `userToAguayo` falló
+    token_id: Option<U256>,
+    /// `None` sin Aguayo o si la consulta falló
+    metadata: Option<AguayoMetadata>,
+    /// Veredicto de `isEligibleForCredit`; `None` si no se consultó o falló
+    sbt_eligible: Option<bool>,
+    /// (score, confianza) ya combinados, para reutilizarlos entre grupos
+    reputation: Option<(U256, U256)>,
+}
+
+impl MemberSnapshot {
+    fn has_aguayo(&self) -> bool {
+        matches!(self.token_id, Some(token_id) if token_id != U256::ZERO)
+    }
+
+    /// Metadata del Aguayo; falla si no tiene uno o no se pudo leer
+    fn metadata(&self) -> Result<&AguayoMetadata, Vec<u8>> {
+        match (self.token_id, &self.metadata) {
+            (Some(token_id), _) if token_id == U256::ZERO => {
+                Err(MemberNotEligible { member: self.member }.abi_encode())
+            }
+            (_, Some(metadata)) => Ok(metadata),
+            _ => Err(AguayoCallFailed { member: self.member }.abi_encode()),
+        }
+    }
+
+    /// Metadata con la que se tasa al miembro. Si el SBT principal no respondió, se deriva de
+    /// los proveedores que sí lo hicieron: nivel según el score, sin manchas conocidas y contado
+    /// como miembro nuevo. Falla si el miembro no tiene Aguayo o ningún proveedor respondió
+    fn pricing_metadata(&self, reputation: impl FnOnce() -> (U256, U256)) -> Result<AguayoMetadata, Vec<u8>> {
+        let error = match self.metadata() {
+            Ok(metadata) => return Ok(metadata.clone()),
+            Err(error) => error,
+        };
+        if self.token_id == Some(U256::ZERO) {
+            return Err(error);
+        }
+
+        let (score, confidence) = reputation();
+        if confidence.is_zero() {
+            return Err(error);
+        }
+        Ok(AguayoMetadata {
+            level: (score / U256::from(SCORE_PER_LEVEL)).saturating_to::<u8>(),
+            totalThreads: 0,
+            completedCircles: 0,
+            stains: 0,
+            lastActivityTimestamp: Default::default(),
+            isStained: false,
+        })
+    }
+}
+
+impl RiskOracle {
+    fn member_profile(&self, snapshot: &MemberSnapshot) -> Result<AguayoMetadata, Vec<u8>> {
+        snapshot.pricing_metadata(|| self.member_reputation(snapshot))
+    }
+
+    fn aguayo_token(&self, member: Address) -> Option<U256> {
+        self.aguayo_call(&IAguayoSBT::userToAguayoCall { user: member })
+            .map(|ret| ret._0)
+    }
+
+    /// Lee token y metadata (y el veredicto de crédito si `with_credit`) de un miembro
+    fn snapshot(&self, member: Address, with_credit: bool) -> MemberSnapshot {
+        let mut snapshot = MemberSnapshot {
+            member,
+            token_id: self.aguayo_token(member),
+            metadata: None,
+            sbt_eligible: None,
+            reputation: None,
+        };
+        if !snapshot.has_aguayo() {
+            return snapshot;
+        }
+
+        let token_id = snapshot.token_id.unwrap_or_default();
+        snapshot.metadata = self
+            .aguayo_call(&IAguayoSBT::getAguayoMetadataCall { tokenId: token_id })
+            .map(|ret| ret._0);
+        if with_credit {
+            snapshot.sbt_eligible = self
+                .aguayo_call(&IAguayoSBT::isEligibleForCreditCall { tokenId: token_id })
+                .map(|ret| ret._0);
+        }
+        snapshot
+    }
+
+    /// Falla con el primer miembro que está en la denylist
+    fn reject_denylisted(&self, members: &[Address]) -> Result<(), Vec<u8>> {
+        match members.iter().find(|member| self.is_denylisted(**member)) {
+            Some(member) => Err(MemberNotEligible { member: *member }.abi_encode()),
+            None => Ok(()),
+        }
+    }
+
+    /// Elegible para crédito según la denylist, el SBT y `min_level_for_credit`; `None` si falta algún dato.
+    /// Si el SBT no dice qué Aguayo tiene el miembro, decide el nivel según los demás proveedores
+    fn credit_eligible(&self, snapshot: &MemberSnapshot) -> Option<bool> {
+        if self.is_denylisted(snapshot.member) || snapshot.token_id == Some(U256::ZERO) {
+            return Some(false);
+        }
+        if snapshot.token_id.is_none() {
+            let profile = self.member_profile(snapshot).ok()?;
+            return Some(profile.level >= self.min_level_for_credit());
+        }
+        if !snapshot.sbt_eligible? {
+            return Some(false);
+        }
+        Some(snapshot.metadata.as_ref()?.level >= self.min_level_for_credit())
+    }
+
+    /// Score del grupo (promedio acercado a la mitad del camino hacia el miembro más débil)
+    /// y la dispersión de niveles que no captura un promedio
+    fn group_stats(&self, snapshots: &[MemberSnapshot]) -> Result<GroupStats, Vec<u8>> {
+        let mut total_score = U256::ZERO;
+        let mut min_score = U256::from(MAX_SCORE);
+        let mut stained_count = U256::ZERO;
+        let mut min_level = u8::MAX;
+        let mut level_sum = 0u64;
+        let mut level_sq_sum = 0u64;
+        let mut new_members = 0u64;
+        let mut member_scores = Vec::with_capacity(snapshots.len());
+        let mut member_confidence = Vec::with_capacity(snapshots.len());
+
+        for snapshot in snapshots {
+            let (score, confidence) = self.member_reputation(snapshot);
+            let metadata = snapshot.pricing_metadata(|| (score, confidence))?;
+            member_scores.push(score);
+            member_confidence.push(confidence);
+
+            total_score += score;
+            min_score = min_score.min(score);
             if metadata.isStained {
-                stained_count = stained_count + U256::from(1);
+                stained_count += U256::from(1);
+            }
+
+            let level = u64::from(metadata.level);
+            min_level = min_level.min(metadata.level);
+            level_sum += level;
+            level_sq_sum += level * level;
+            if metadata.completedCircles == 0 {
+                new_members += 1;
             }
         }
 
-        let avg_level: u8 = (total_level / U256::from(members.len()))
-            .try_into()
-            .map_err(|_| b"Invalid average level".to_vec())?;
+        let n = snapshots.len() as u64;
+        let mean = total_score / U256::from(n);
+        // Var = (n·Σl² - (Σl)²) / n², en centésimas para no truncar grupos casi uniformes
+        let variance_x100 = (n * level_sq_sum - level_sum * level_sum) * 100 / (n * n);
+
+        Ok(GroupStats {
+            group_score: mean - (mean - min_score) / U256::from(2),
+            stained_count,
+            min_level,
+            mean_level_x100: U256::from(level_sum * 100 / n),
+            level_variance_x100: U256::from(variance_x100),
+            new_member_bps: U256::from(new_members * MAX_SCORE / n),
+            member_scores,
+            member_confidence,
+        })
+    }
+
+    /// Precio del grupo con penalización por manchas y dispersión, y topes de leverage y tasa
+    fn price_group(&self, snapshots: &[MemberSnapshot]) -> Result<GroupPricing, Vec<u8>> {
+        let stats = self.group_stats(snapshots)?;
+        let stained_count = stats.stained_count;
+        let (score_tier, score_multiplier, score_spread_bps) = self.get_tier_for_score(stats.group_score)?;
+
+        // El miembro más débil limita el tier: a lo sumo MAX_TIERS_ABOVE_WEAKEST sobre el suyo
+        let tier_cap = self.tier_for_level(stats.min_level) + MAX_TIERS_ABOVE_WEAKEST;
+        let (tier, tier_multiplier, tier_spread_bps) = if score_tier < tier_cap {
+            (score_tier, score_multiplier, score_spread_bps)
+        } else {
+            (
+                tier_cap,
+                self.tier_multipliers.get(tier_cap).unwrap_or_default(),
+                self.tier_interest_rates.get(tier_cap).unwrap_or_default(),
+            )
+        };
+        let min_level_capped = (tier_multiplier, tier_spread_bps) != (score_multiplier, score_spread_bps);
+        let mut multiplier = tier_multiplier;
+
+        // Tasa final = base + spread del tier + prima por manchas
+        let base_rate_bps = self.base_interest_rate_bps.get();
+        let mut stain_premium_bps = U256::ZERO;
+
+        // Apply stain penalty
+        if stained_count > U256::ZERO {
+            // Reduce leverage by 10% per stained member
+            let leverage_reduction = (multiplier * stained_count * U256::from(10)) / U256::from(100);
+            multiplier = if multiplier > leverage_reduction {
+                multiplier - leverage_reduction
+            } else {
+                U256::from(100) // Minimum 1x
+            };
 
-        Ok((avg_level, stained_count))
+            // Increase interest rate
+            stain_premium_bps = stained_count * self.risk_premium_per_stain_bps.get();
+        }
+        let stain_leverage_reduction = tier_multiplier.saturating_sub(multiplier);
+
+        // Apply dispersion penalty: grupos desparejos o llenos de miembros nuevos
+        let full = U256::from(MAX_SCORE);
+        let dispersion_penalty_bps = (stats.level_variance_x100 * self.variance_penalty_bps.get()
+            / U256::from(100)
+            + stats.new_member_bps * self.new_member_penalty_bps.get() / full)
+            .min(full);
+        let after_stains = multiplier;
+        multiplier = after_stains
+            .saturating_sub(after_stains * dispersion_penalty_bps / full)
+            .max(after_stains.min(U256::from(100))); // Minimum 1x
+        let dispersion_leverage_reduction = after_stains - multiplier;
+
+        let mut interest_rate_bps = base_rate_bps + tier_spread_bps + stain_premium_bps;
+        let rate_capped = interest_rate_bps > U256::from(10000);
+        if rate_capped {
+            interest_rate_bps = U256::from(10000); // Cap at 100%
+        }
+
+        // Cap leverage at max
+        let max_leverage = self.max_leverage_multiplier.get();
+        let leverage_capped = multiplier > max_leverage;
+        if leverage_capped {
+            multiplier = max_leverage;
+        }
+
+        Ok(GroupPricing {
+            stats,
+            tier,
+            min_level_capped,
+            tier_multiplier,
+            stain_leverage_reduction,
+            dispersion_penalty_bps,
+            dispersion_leverage_reduction,
+            leverage_capped,
+            multiplier,
+            base_rate_bps,
+            tier_spread_bps,
+            stain_premium_bps,
+            rate_capped,
+            interest_rate_bps,
+        })
+    }
+
+    /// Tier más alto cuyo nivel mínimo alcanza `level`; el primero si no alcanza ninguno
+    fn tier_for_level(&self, level: u8) -> usize {
+        (0..self.tier_min_levels.len())
+            .rev()
+            .find(|&i| self.tier_min_levels.get(i).unwrap_or_default() <= U256::from(level))
+            .unwrap_or(0)
     }
 
-    fn get_tier_for_average_level(&self, avg_level: u8) -> Result<(U256, U256), Vec<u8>> {
+    /// (tier, multiplicador, spread) del tier más alto alcanzado, interpolado hacia el siguiente
+    fn get_tier_for_score(&self, score: U256) -> Result<(usize, U256, U256), Vec<u8>> {
         let tier_count = self.tier_min_levels.len();
+        if tier_count == 0 {
+            return Err(NoTiersConfigured {}.abi_encode());
+        }
+
+        // Start from highest tier and go down; default to first tier
+        let tier = (0..tier_count)
+            .rev()
+            .find(|&i| score >= self.tier_threshold(i))
+            .unwrap_or(0);
+
+        let multiplier = self.tier_multipliers.get(tier)
+            .ok_or_else(|| InvalidTierId {}.abi_encode())?;
+        let interest_rate = self.tier_interest_rates.get(tier)
+            .ok_or_else(|| InvalidTierId {}.abi_encode())?;
+
+        let floor = self.tier_threshold(tier);
+        let ceiling = self.tier_threshold(tier + 1);
+        if tier + 1 >= tier_count || score < floor || ceiling <= floor {
+            return Ok((tier, multiplier, interest_rate));
+        }
+
+        let next_multiplier = self.tier_multipliers.get(tier + 1)
+            .ok_or_else(|| InvalidTierId {}.abi_encode())?;
+        let next_rate = self.tier_interest_rates.get(tier + 1)
+            .ok_or_else(|| InvalidTierId {}.abi_encode())?;
+
+        let progress = score - floor;
+        let span = ceiling - floor;
+        Ok((
+            tier,
+            lerp(multiplier, next_multiplier, progress, span),
+            lerp(interest_rate, next_rate, progress, span),
+        ))
+    }
+}
+
+/// Composición del grupo: score agregado, manchas y dispersión de niveles
+struct GroupStats {
+    group_score: U256,
+    stained_count: U256,
+    min_level: u8,
+    mean_level_x100: U256,
+    level_variance_x100: U256,
+    /// Fracción (bps) de miembros sin círculos completados
+    new_member_bps: U256,
+    member_scores: Vec<U256>,
+    member_confidence: Vec<U256>,
+}
+
+/// Cada paso del cálculo de `price_group`, para `get_risk_report`
+struct GroupPricing {
+    stats: GroupStats,
+    tier: usize,
+    min_level_capped: bool,
+    tier_multiplier: U256,
+    stain_leverage_reduction: U256,
+    dispersion_penalty_bps: U256,
+    dispersion_leverage_reduction: U256,
+    leverage_capped: bool,
+    multiplier: U256,
+    base_rate_bps: U256,
+    tier_spread_bps: U256,
+    stain_premium_bps: U256,
+    rate_capped: bool,
+    interest_rate_bps: U256,
+}
+
+// ========== CREDIT SCORE ==========
+
+impl RiskOracle {
+    /// Combina hilos, círculos e historial de pagos, resta manchas y decae por inactividad
+    fn credit_score(&self, metadata: &AguayoMetadata) -> U256 {
+        let full = U256::from(MAX_SCORE);
+
+        let threads = U256::from(metadata.totalThreads.min(THREADS_FOR_FULL_SCORE)) * full
+            / U256::from(THREADS_FOR_FULL_SCORE);
+        let circles = U256::from(metadata.completedCircles.min(CIRCLES_FOR_FULL_SCORE)) * full
+            / U256::from(CIRCLES_FOR_FULL_SCORE);
+
+        // Historial: pagos exitosos sobre pagos + defaults
+        let paid = U256::from(metadata.totalThreads);
+        let stains = U256::from(metadata.stains);
+        let history = if paid.is_zero() { U256::ZERO } else { paid * full / (paid + stains) };
+
+        let weighted = (threads * self.score_weight_threads.get()
+            + circles * self.score_weight_circles.get()
+            + history * self.score_weight_history.get())
+            / full;
+        let score = weighted.saturating_sub(stains * self.stain_penalty_bps.get());
+
+        self.decay(score, metadata.lastActivityTimestamp.to::<u64>())
+    }
+
+    /// Score mínimo para entrar a un tier
+    fn tier_threshold(&self, tier: usize) -> U256 {
+        self.tier_min_levels.get(tier).unwrap_or_default() * U256::from(SCORE_PER_LEVEL)
+    }
+
+    /// Reduce el score a la mitad por cada half-life sin actividad, lineal entre mitades
+    fn decay(&self, score: U256, last_activity: u64) -> U256 {
+        let now = self.vm().block_timestamp();
+        let half_life = self.score_decay_half_life.get().saturating_to::<u64>();
+        if last_activity == 0 || now <= last_activity || half_life == 0 {
+            return score;
+        }
+
+        let idle = now - last_activity;
+        let halvings = idle / half_life;
+        if halvings >= MAX_HALVINGS {
+            return U256::ZERO;
+        }
+
+        let current = score >> halvings as usize;
+        let lost = (current / U256::from(2)) * U256::from(idle % half_life) / U256::from(half_life);
+        current - lost
+    }
+}
+
+/// `part / total` en bps; 0 sin datos
+fn ratio_bps(part: U256, total: U256) -> U256 {
+    (part * U256::from(MAX_SCORE)).checked_div(total).unwrap_or_default()
+}
+
+/// Interpolación lineal de `from` a `to` en `progress / span`
+fn lerp(from: U256, to: U256, progress: U256, span: U256) -> U256 {
+    if to >= from {
+        from + (to - from) * progress / span
+    } else {
+        from - (from - to) * progress / span
+    }
+}
 
-        // Start from highest tier and go down
-        for i in (0..tier_count).rev() {
-            if let Some(tier_min_level_val) = self.tier_min_levels.get(i) {
-                let tier_min_level: u8 = tier_min_level_val
-                    .try_into()
-                    .map_err(|_| b"Invalid tier level".to_vec())?;
+// ========== DRAW WEIGHTS ==========
 
-                if avg_level >= tier_min_level {
-                    let multiplier = self.tier_multipliers.get(i)
-                        .ok_or(b"Multiplier not found".to_vec())?;
+impl RiskOracle {
+    /// Peso sin normalizar de cada miembro según `draw_weight_mode`; falla si un miembro no se pudo leer
+    fn draw_weights(&self, snapshots: &[MemberSnapshot]) -> Result<Vec<U256>, Vec<u8>> {
+        let mode = self.draw_weight_mode();
+        let mut weights = Vec::with_capacity(snapshots.len());
+
+        for snapshot in snapshots {
+            let metadata = self.member_profile(snapshot)?;
+            let level = u64::from(metadata.level);
+            let weight = match mode {
+                DRAW_UNIFORM => 1,
+                DRAW_QUADRATIC => 10 + level * level,
+                // Cada mancha divide el peso lineal
+                DRAW_STAIN_PENALIZED => (10 + level) / (1 + u64::from(metadata.stains)),
+                DRAW_REPUTATION => {
+                    weights.push(self.member_reputation(snapshot).0);
+                    continue;
+                }
+                _ => 10 + level,
+            };
+            weights.push(U256::from(weight));
+        }
+
+        Ok(weights)
+    }
+}
+
+/// Reparte 10000 bps en proporción a `weights` (mayor resto, empates al primero);
+/// si todos pesan 0 el reparto es uniforme
+fn normalize_bps(weights: &[U256]) -> Vec<U256> {
+    let full = U256::from(MAX_SCORE);
+    let total = weights.iter().fold(U256::ZERO, |sum, weight| sum + weight);
+    if total.is_zero() {
+        return normalize_bps(&vec![U256::from(1); weights.len()]);
+    }
+
+    let mut shares: Vec<U256> = weights.iter().map(|weight| weight * full / total).collect();
+    let mut remainders: Vec<(U256, usize)> = weights
+        .iter()
+        .enumerate()
+        .map(|(i, weight)| (weight * full % total, i))
+        .collect();
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+    let assigned = shares.iter().fold(U256::ZERO, |sum, share| sum + share);
+    let missing = (full - assigned).to::<usize>();
+    for &(_, i) in remainders.iter().take(missing) {
+        shares[i] += U256::from(1);
+    }
+    shares
+}
+
+// ========== REPUTATION PROVIDERS ==========
+
+impl RiskOracle {
+    /// Promedio ponderado de los proveedores que respondieron; los que fallan solo bajan la confianza
+    fn member_reputation(&self, snapshot: &MemberSnapshot) -> (U256, U256) {
+        if let Some(reputation) = snapshot.reputation {
+            return reputation;
+        }
+
+        let mut total_weight = U256::ZERO;
+        let mut answered_weight = U256::ZERO;
+        let mut weighted_score = U256::ZERO;
+
+        for (provider, kind, weight, full_score_stake) in self.load_providers() {
+            total_weight += weight;
+            if let Some(score) = self.provider_score(snapshot, provider, kind, full_score_stake) {
+                answered_weight += weight;
+                weighted_score += score.min(U256::from(MAX_SCORE)) * weight;
+            }
+        }
 
-                    let interest_rate = self.tier_interest_rates.get(i)
-                        .ok_or(b"Interest rate not found".to_vec())?;
+        // Los que no respondieron no cuentan como 0: solo bajan la confianza
+        if answered_weight.is_zero() {
+            return (U256::ZERO, U256::ZERO);
+        }
+        (weighted_score / answered_weight, ratio_bps(answered_weight, total_weight))
+    }
 
-                    return Ok((multiplier, interest_rate));
+    /// Score 0-10000 de un proveedor; `None` si la consulta falla
+    fn provider_score(
+        &self,
+        snapshot: &MemberSnapshot,
+        provider: Address,
+        kind: u8,
+        full_score_stake: U256,
+    ) -> Option<U256> {
+        let member = snapshot.member;
+        match kind {
+            // El SBT principal ya se leyó en el snapshot
+            PROVIDER_AGUAYO_SBT if provider == self.aguayo_sbt.get() => match snapshot.token_id {
+                Some(token_id) if token_id.is_zero() => Some(U256::ZERO),
+                _ => snapshot.metadata.as_ref().map(|metadata| self.credit_score(metadata)),
+            },
+            PROVIDER_AGUAYO_SBT => {
+                let token_id = self
+                    .provider_call(provider, &IAguayoSBT::userToAguayoCall { user: member })?
+                    ._0;
+                if token_id.is_zero() {
+                    return Some(U256::ZERO);
                 }
+                let metadata = self
+                    .provider_call(provider, &IAguayoSBT::getAguayoMetadataCall { tokenId: token_id })?
+                    ._0;
+                Some(self.credit_score(&metadata))
             }
+            PROVIDER_ATTESTATION => self
+                .provider_call(provider, &IReputationAttestation::scoreOfCall { subject: member })
+                .map(|ret| ret._0),
+            PROVIDER_STAKING => self
+                .provider_call(provider, &IKuyayStaking::stakedBalanceOfCall { account: member })
+                .map(|ret| ret._0.min(full_score_stake) * U256::from(MAX_SCORE) / full_score_stake),
+            _ => None,
         }
+    }
+
+    fn load_providers(&self) -> Vec<Provider> {
+        (0..self.provider_addresses.len())
+            .map(|i| {
+                (
+                    self.provider_addresses.get(i).unwrap_or_default(),
+                    self.provider_kinds.get(i).unwrap_or_default().saturating_to::<u8>(),
+                    self.provider_weights.get(i).unwrap_or_default(),
+                    self.provider_full_score_stakes.get(i).unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
 
-        // Default to first tier
-        let multiplier = self.tier_multipliers.get(0)
-            .ok_or(b"No tiers available".to_vec())?;
+    fn store_providers(&mut self, providers: &[Provider]) {
+        self.provider_addresses.truncate(0);
+        self.provider_kinds.truncate(0);
+        self.provider_weights.truncate(0);
+        self.provider_full_score_stakes.truncate(0);
+
+        for &(provider, kind, weight, full_score_stake) in providers {
+            self.provider_addresses.push(provider);
+            self.provider_kinds.push(U256::from(kind));
+            self.provider_weights.push(weight);
+            self.provider_full_score_stakes.push(full_score_stake);
+        }
+    }
+}
 
-        let interest_rate = self.tier_interest_rates.get(0)
-            .ok_or(b"No tiers available".to_vec())?;
+/// Adaptador conocido, peso en (0, 10000] y stake de referencia para staking
+fn validate_provider(kind: u8, weight: U256, full_score_stake: U256) -> Result<(), Vec<u8>> {
+    if kind > PROVIDER_STAKING {
+        return Err(InvalidParameter {}.abi_encode());
+    }
+    if weight.is_zero() || weight > U256::from(MAX_SCORE) {
+        return Err(InvalidParameter {}.abi_encode());
+    }
+    if kind == PROVIDER_STAKING && full_score_stake.is_zero() {
+        return Err(InvalidParameter {}.abi_encode());
+    }
+    Ok(())
+}
+
+// ========== TIER TABLE ==========
+
+impl RiskOracle {
+    fn load_tiers(&self) -> Vec<Tier> {
+        (0..self.tier_min_levels.len())
+            .map(|i| {
+                (
+                    self.tier_min_levels.get(i).unwrap_or_default().saturating_to::<u8>(),
+                    self.tier_multipliers.get(i).unwrap_or_default(),
+                    self.tier_interest_rates.get(i).unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    fn load_tier_keys(&self) -> Vec<U256> {
+        (0..self.tier_keys.len())
+            .map(|i| self.tier_keys.get(i).unwrap_or_default())
+            .collect()
+    }
+
+    /// `keys[i]` es la clave estable de `tiers[i]`
+    fn store_tiers(&mut self, tiers: &[Tier], keys: &[U256]) {
+        self.tier_min_levels.truncate(0);
+        self.tier_multipliers.truncate(0);
+        self.tier_interest_rates.truncate(0);
+        self.tier_keys.truncate(0);
+
+        for (&(min_level, multiplier, interest_rate_bps), &key) in tiers.iter().zip(keys) {
+            self.tier_min_levels.push(U256::from(min_level));
+            self.tier_multipliers.push(multiplier);
+            self.tier_interest_rates.push(interest_rate_bps);
+            self.tier_keys.push(key);
+        }
+    }
+
+    fn new_tier_key(&mut self) -> U256 {
+        let key = self.next_tier_key.get() + U256::from(1);
+        self.next_tier_key.set(key);
+        key
+    }
+
+    /// Niveles estrictamente crecientes, multiplicadores que no bajan y spreads que no suben
+    fn validate_tiers(&self, tiers: &[Tier]) -> Result<(), Vec<u8>> {
+        let max_leverage = self.max_leverage_multiplier.get();
+
+        for (i, &(min_level, multiplier, interest_rate_bps)) in tiers.iter().enumerate() {
+            if multiplier == U256::ZERO || multiplier > max_leverage {
+                return Err(InvalidParameter {}.abi_encode());
+            }
+            if interest_rate_bps > U256::from(10000) {
+                return Err(InvalidParameter {}.abi_encode());
+            }
+
+            if i > 0 {
+                let (prev_level, prev_multiplier, prev_rate) = tiers[i - 1];
+                if min_level <= prev_level || multiplier < prev_multiplier || interest_rate_bps > prev_rate {
+                    return Err(TierOrderViolation { tier_id: U256::from(i) }.abi_encode());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// ========== EXTERNAL CALLS ==========
+
+impl RiskOracle {
+    /// Static call to the AguayoSBT; `None` on revert or undecodable return data
+    fn aguayo_call<C: SolCall>(&self, call: &C) -> Option<C::Return> {
+        self.provider_call(self.aguayo_sbt.get(), call)
+    }
 
-        Ok((multiplier, interest_rate))
+    /// Static call to any reputation provider; `None` on revert or undecodable return data
+    fn provider_call<C: SolCall>(&self, provider: Address, call: &C) -> Option<C::Return> {
+        let ret = self.vm().static_call(&Call::new(), provider, &call.abi_encode()).ok()?;
+        C::abi_decode_returns(&ret, true).ok()
     }
 }
@@ -0,0 +1,274 @@
+//! `TestVM` wrapper that counts the oracle's calls into the AguayoSBT.
+//!
+//! Stylus gas is not metered by `TestVM`, so benchmarks compare call counts
+//! and turn them into an estimate with `SBT_VIEW_GAS`.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use stylus_sdk::alloy_primitives::{Address, B256, U256};
+use stylus_sdk::testing::deploy::DeploymentAccess;
+use stylus_sdk::testing::*;
+
+/// Estimated cost of one warm static call to an SBT view plus its cold storage read
+pub const SBT_VIEW_GAS: u64 = 100 + 2_100 + 600;
+
+#[derive(Clone)]
+pub struct MeteredHost {
+    pub vm: TestVM,
+    calls: Rc<Cell<u64>>,
+}
+
+impl MeteredHost {
+    pub fn new() -> Self {
+        Self {
+            vm: TestVM::new(),
+            calls: Rc::new(Cell::new(0)),
+        }
+    }
+
+    /// Static calls made since the last reset
+    pub fn calls(&self) -> u64 {
+        self.calls.get()
+    }
+
+    pub fn reset(&self) {
+        self.calls.set(0);
+    }
+
+    pub fn estimated_gas(&self) -> u64 {
+        self.calls() * SBT_VIEW_GAS
+    }
+}
+
+impl Host for MeteredHost {}
+
+impl CryptographyAccess for MeteredHost {
+    fn native_keccak256(&self, input: &[u8]) -> B256 {
+        self.vm.native_keccak256(input)
+    }
+}
+
+impl CalldataAccess for MeteredHost {
+    fn read_args(&self, len: usize) -> Vec<u8> {
+        self.vm.read_args(len)
+    }
+    fn read_return_data(&self, offset: usize, size: Option<usize>) -> Vec<u8> {
+        self.vm.read_return_data(offset, size)
+    }
+    fn return_data_size(&self) -> usize {
+        self.vm.return_data_size()
+    }
+    fn write_result(&self, data: &[u8]) {
+        self.vm.write_result(data)
+    }
+}
+
+unsafe impl UnsafeDeploymentAccess for MeteredHost {
+    unsafe fn create1(
+        &self,
+        code: *const u8,
+        code_len: usize,
+        endowment: *const u8,
+        contract: *mut u8,
+        revert_data_len: *mut usize,
+    ) {
+        self.vm
+            .create1(code, code_len, endowment, contract, revert_data_len)
+    }
+    unsafe fn create2(
+        &self,
+        code: *const u8,
+        code_len: usize,
+        endowment: *const u8,
+        salt: *const u8,
+        contract: *mut u8,
+        revert_data_len: *mut usize,
+    ) {
+        self.vm
+            .create2(code, code_len, endowment, salt, contract, revert_data_len)
+    }
+}
+
+impl StorageAccess for MeteredHost {
+    fn storage_load_bytes32(&self, key: U256) -> B256 {
+        self.vm.storage_load_bytes32(key)
+    }
+    unsafe fn storage_cache_bytes32(&self, key: U256, value: B256) {
+        self.vm.storage_cache_bytes32(key, value)
+    }
+    fn flush_cache(&self, clear: bool) {
+        self.vm.flush_cache(clear)
+    }
+}
+
+unsafe impl UnsafeCallAccess for MeteredHost {
+    unsafe fn call_contract(
+        &self,
+        to: *const u8,
+        data: *const u8,
+        data_len: usize,
+        value: *const u8,
+        gas: u64,
+        outs_len: &mut usize,
+    ) -> u8 {
+        self.vm
+            .call_contract(to, data, data_len, value, gas, outs_len)
+    }
+    unsafe fn static_call_contract(
+        &self,
+        to: *const u8,
+        data: *const u8,
+        data_len: usize,
+        gas: u64,
+        outs_len: &mut usize,
+    ) -> u8 {
+        self.vm
+            .static_call_contract(to, data, data_len, gas, outs_len)
+    }
+    unsafe fn delegate_call_contract(
+        &self,
+        to: *const u8,
+        data: *const u8,
+        data_len: usize,
+        gas: u64,
+        outs_len: &mut usize,
+    ) -> u8 {
+        self.vm
+            .delegate_call_contract(to, data, data_len, gas, outs_len)
+    }
+}
+
+impl BlockAccess for MeteredHost {
+    fn block_basefee(&self) -> U256 {
+        self.vm.block_basefee()
+    }
+    fn block_coinbase(&self) -> Address {
+        self.vm.block_coinbase()
+    }
+    fn block_number(&self) -> u64 {
+        self.vm.block_number()
+    }
+    fn block_timestamp(&self) -> u64 {
+        self.vm.block_timestamp()
+    }
+    fn block_gas_limit(&self) -> u64 {
+        self.vm.block_gas_limit()
+    }
+}
+
+impl ChainAccess for MeteredHost {
+    fn chain_id(&self) -> u64 {
+        self.vm.chain_id()
+    }
+}
+
+impl AccountAccess for MeteredHost {
+    fn balance(&self, account: Address) -> U256 {
+        self.vm.balance(account)
+    }
+    fn contract_address(&self) -> Address {
+        self.vm.contract_address()
+    }
+    fn code(&self, account: Address) -> Vec<u8> {
+        self.vm.code(account)
+    }
+    fn code_size(&self, account: Address) -> usize {
+        self.vm.code_size(account)
+    }
+    fn code_hash(&self, account: Address) -> B256 {
+        self.vm.code_hash(account)
+    }
+}
+
+impl MemoryAccess for MeteredHost {
+    fn pay_for_memory_grow(&self, pages: u16) {
+        self.vm.pay_for_memory_grow(pages)
+    }
+}
+
+impl MessageAccess for MeteredHost {
+    fn msg_sender(&self) -> Address {
+        self.vm.msg_sender()
+    }
+    fn msg_reentrant(&self) -> bool {
+        self.vm.msg_reentrant()
+    }
+    fn msg_value(&self) -> U256 {
+        self.vm.msg_value()
+    }
+    fn tx_origin(&self) -> Address {
+        self.vm.tx_origin()
+    }
+}
+
+impl MeteringAccess for MeteredHost {
+    fn evm_gas_left(&self) -> u64 {
+        self.vm.evm_gas_left()
+    }
+    fn evm_ink_left(&self) -> u64 {
+        self.vm.evm_ink_left()
+    }
+    fn tx_gas_price(&self) -> U256 {
+        self.vm.tx_gas_price()
+    }
+    fn tx_ink_price(&self) -> u32 {
+        self.vm.tx_ink_price()
+    }
+}
+
+impl CallAccess for MeteredHost {
+    fn static_call(
+        &self,
+        context: &dyn StaticCallContext,
+        to: Address,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        self.calls.set(self.calls.get() + 1);
+        self.vm.static_call(context, to, data)
+    }
+
+    unsafe fn delegate_call(
+        &self,
+        context: &dyn MutatingCallContext,
+        to: Address,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        self.vm.delegate_call(context, to, data)
+    }
+
+    fn call(
+        &self,
+        context: &dyn MutatingCallContext,
+        to: Address,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        self.vm.call(context, to, data)
+    }
+}
+
+impl DeploymentAccess for MeteredHost {
+    unsafe fn deploy(
+        &self,
+        code: &[u8],
+        endowment: U256,
+        salt: Option<B256>,
+    ) -> Result<Address, Vec<u8>> {
+        self.vm.deploy(code, endowment, salt)
+    }
+}
+
+impl LogAccess for MeteredHost {
+    fn emit_log(&self, input: &[u8], num_topics: usize) {
+        self.vm.emit_log(input, num_topics)
+    }
+    fn raw_log(&self, topics: &[B256], data: &[u8]) -> Result<(), &'static str> {
+        self.vm.raw_log(topics, data)
+    }
+}
+
+impl ValueTransfer for MeteredHost {
+    fn transfer_eth(&self, to: Address, amount: U256) -> Result<(), Vec<u8>> {
+        self.vm.transfer_eth(to, amount)
+    }
+}
@@ -0,0 +1,97 @@
+//! Shared setup for RiskOracle tests.
+
+#![allow(dead_code)]
+
+pub mod metered;
+
+use alloy_sol_types::{SolCall, SolError, SolValue};
+use risk_oracle::{AguayoMetadata, IAguayoSBT, RiskOracle, Unauthorized};
+use stylus_sdk::alloy_primitives::{Address, U256};
+use stylus_sdk::testing::*;
+
+pub const OWNER: Address = Address::new([0x01; 20]);
+pub const AGUAYO_SBT: Address = Address::new([0x02; 20]);
+pub const ORACLE: Address = Address::new([0x03; 20]);
+pub const STRANGER: Address = Address::new([0x08; 20]);
+pub const VAULT: Address = Address::new([0x04; 20]);
+pub const ALICE: Address = Address::new([0x11; 20]);
+pub const BOB: Address = Address::new([0x12; 20]);
+pub const CAROL: Address = Address::new([0x13; 20]);
+
+/// Deploys an oracle initialized by `OWNER` on a host that counts SBT calls
+pub fn setup_metered_oracle() -> (metered::MeteredHost, RiskOracle) {
+    let host = metered::MeteredHost::new();
+    host.vm.set_contract_address(ORACLE);
+    let mut oracle = RiskOracle::from(&host);
+    host.vm.set_sender(OWNER);
+    oracle.initialize(AGUAYO_SBT).unwrap();
+    (host, oracle)
+}
+
+/// Deploys an oracle initialized by `OWNER`
+pub fn setup_oracle() -> (TestVM, RiskOracle) {
+    let vm = TestVM::new();
+    vm.set_contract_address(ORACLE);
+    let mut oracle = RiskOracle::from(&vm);
+    vm.set_sender(OWNER);
+    oracle.initialize(AGUAYO_SBT).unwrap();
+    (vm, oracle)
+}
+
+pub fn unauthorized() -> Vec<u8> {
+    Unauthorized {}.abi_encode()
+}
+
+pub fn bps(value: u64) -> U256 {
+    U256::from(value)
+}
+
+/// Metadata for a member with the given level and stain count;
+/// each completed circle counts twelve paid threads
+pub fn metadata(level: u8, stains: u16) -> AguayoMetadata {
+    AguayoMetadata {
+        level,
+        totalThreads: level as u32 * 12,
+        completedCircles: level as u16,
+        stains,
+        lastActivityTimestamp: Default::default(),
+        isStained: stains > 0,
+    }
+}
+
+/// Registers `member` on the mocked AguayoSBT with every view the oracle reads
+pub fn mock_member(vm: &TestVM, member: Address, token_id: u64, meta: AguayoMetadata, eligible: bool) {
+    let token_id = U256::from(token_id);
+    vm.mock_static_call(
+        AGUAYO_SBT,
+        IAguayoSBT::userToAguayoCall { user: member }.abi_encode(),
+        Ok(token_id.abi_encode()),
+    );
+    vm.mock_static_call(
+        AGUAYO_SBT,
+        IAguayoSBT::isEligibleForCreditCall { tokenId: token_id }.abi_encode(),
+        Ok(eligible.abi_encode()),
+    );
+    vm.mock_static_call(
+        AGUAYO_SBT,
+        IAguayoSBT::getLevelCall { tokenId: token_id }.abi_encode(),
+        Ok(U256::from(meta.level).abi_encode()),
+    );
+    vm.mock_static_call(
+        AGUAYO_SBT,
+        IAguayoSBT::isAguayoStainedCall { tokenId: token_id }.abi_encode(),
+        Ok(meta.isStained.abi_encode()),
+    );
+    vm.mock_static_call(
+        AGUAYO_SBT,
+        IAguayoSBT::getAguayoMetadataCall { tokenId: token_id }.abi_encode(),
+        Ok(meta.abi_encode()),
+    );
+}
+
+/// ALICE at level 3, BOB at level 5 and CAROL at level 4 with one stain, all SBT-eligible
+pub fn setup_members(vm: &TestVM) {
+    mock_member(vm, ALICE, 1, metadata(3, 0), true);
+    mock_member(vm, BOB, 2, metadata(5, 0), true);
+    mock_member(vm, CAROL, 3, metadata(4, 1), true);
+}
@@ -0,0 +1,147 @@
+//! Continuous member credit score and score-based leverage.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::{SolError, SolEvent};
+    use risk_oracle::*;
+    use stylus_sdk::alloy_primitives::{aliases::U48, U256};
+
+    const HALF_LIFE: u64 = 180 * 86400;
+    const NOW: u64 = 1_000_000_000;
+
+    #[test]
+    fn test_default_weights() {
+        let (_vm, oracle) = setup_oracle();
+        assert_eq!(
+            oracle.get_score_weights(),
+            (bps(3000), bps(4000), bps(3000), bps(1500), U256::from(HALF_LIFE))
+        );
+    }
+
+    #[test]
+    fn test_score_combines_history() {
+        let (vm, oracle) = setup_oracle();
+
+        let mut veteran = metadata(10, 0);
+        veteran.totalThreads = 120;
+        mock_member(&vm, ALICE, 1, veteran, true);
+        assert_eq!(oracle.get_member_score(ALICE), Ok(bps(10_000)));
+
+        // 36 threads, 3 circles, clean history: 900 + 1200 + 3000
+        mock_member(&vm, BOB, 2, metadata(3, 0), true);
+        assert_eq!(oracle.get_member_score(BOB), Ok(bps(5100)));
+    }
+
+    #[test]
+    fn test_stains_cost_history_and_penalty() {
+        let (vm, oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(3, 1), true);
+        // History 36/37 of 10000, then one 1500 stain penalty
+        assert_eq!(oracle.get_member_score(ALICE), Ok(bps(3518)));
+
+        mock_member(&vm, BOB, 2, metadata(3, 4), true);
+        assert_eq!(oracle.get_member_score(BOB), Ok(U256::ZERO));
+    }
+
+    #[test]
+    fn test_inactivity_decays_score() {
+        let (vm, oracle) = setup_oracle();
+        let mut meta = metadata(3, 0);
+        meta.lastActivityTimestamp = U48::from(NOW);
+        mock_member(&vm, ALICE, 1, meta, true);
+
+        vm.set_block_timestamp(NOW);
+        assert_eq!(oracle.get_member_score(ALICE), Ok(bps(5100)));
+
+        vm.set_block_timestamp(NOW + HALF_LIFE);
+        assert_eq!(oracle.get_member_score(ALICE), Ok(bps(2550)));
+
+        // Halfway to the next halving
+        vm.set_block_timestamp(NOW + HALF_LIFE + HALF_LIFE / 2);
+        assert_eq!(oracle.get_member_score(ALICE), Ok(bps(1913)));
+
+        vm.set_block_timestamp(NOW + 14 * HALF_LIFE);
+        assert_eq!(oracle.get_member_score(ALICE), Ok(U256::ZERO));
+    }
+
+    #[test]
+    fn test_score_requires_aguayo() {
+        let (vm, oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 0, metadata(0, 0), false);
+        assert_eq!(
+            oracle.get_member_score(ALICE),
+            Err(MemberNotEligible { member: ALICE }.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_set_score_weights() {
+        let (vm, mut oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(3, 1), true);
+
+        oracle
+            .set_score_weights(U256::ZERO, bps(10_000), U256::ZERO, U256::ZERO, U256::from(1))
+            .unwrap();
+        assert_eq!(oracle.get_member_score(ALICE), Ok(bps(3000)));
+
+        let (topics, data) = vm.get_emitted_logs().last().unwrap().clone();
+        let event = ScoreWeightsUpdated::decode_raw_log(topics.iter().copied(), &data, true).unwrap();
+        assert_eq!(event.circles_weight, bps(10_000));
+        assert_eq!(event.decay_half_life, U256::from(1));
+    }
+
+    #[test]
+    fn test_set_score_weights_validation() {
+        let (vm, mut oracle) = setup_oracle();
+        let half_life = U256::from(HALF_LIFE);
+        assert_eq!(
+            oracle.set_score_weights(bps(3000), bps(3000), bps(3000), bps(1500), half_life),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.set_score_weights(bps(3000), bps(4000), bps(3000), bps(10_001), half_life),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.set_score_weights(bps(3000), bps(4000), bps(3000), bps(1500), U256::ZERO),
+            Err(InvalidParameter {}.abi_encode())
+        );
+
+        vm.set_sender(STRANGER);
+        assert_eq!(
+            oracle.set_score_weights(bps(3000), bps(4000), bps(3000), bps(1500), half_life),
+            Err(unauthorized())
+        );
+    }
+
+    #[test]
+    fn test_leverage_follows_score_distribution() {
+        let (vm, mut oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(3, 0), true);
+        mock_member(&vm, BOB, 2, metadata(0, 0), true);
+        // Only the score-to-tier mapping is under test here
+        oracle.set_dispersion_penalty(U256::ZERO, U256::ZERO).unwrap();
+
+        // Scores 5100 and 0: mean 2550 pulled halfway to 0 gives 1275,
+        // a quarter of the way from the 1.5x tier towards the 3x tier
+        assert_eq!(
+            oracle.get_leverage_level(vec![ALICE, BOB]),
+            Ok((bps(170), bps(1173)))
+        );
+        assert_eq!(oracle.get_leverage_level(vec![ALICE]), Ok((bps(500), bps(800))));
+    }
+
+    #[test]
+    fn test_low_scores_fall_back_to_first_tier() {
+        let (vm, mut oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(0, 0), true);
+        assert_eq!(oracle.get_leverage_level(vec![ALICE]), Ok((bps(120), bps(1200))));
+
+        // Without the new-member penalty the first tier applies as configured
+        oracle.set_dispersion_penalty(bps(500), U256::ZERO).unwrap();
+        assert_eq!(oracle.get_leverage_level(vec![ALICE]), Ok((bps(150), bps(1200))));
+    }
+}
@@ -0,0 +1,181 @@
+//! Risk-manager denylist: eligibility, expiry, rehabilitation and pagination.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::{SolError, SolEvent};
+    use risk_oracle::*;
+    use stylus_sdk::alloy_primitives::{Address, B256, U256};
+    use stylus_sdk::testing::*;
+
+    const MANAGER: Address = Address::new([0x09; 20]);
+    const FRAUD: u8 = 1;
+    const SANCTIONS: u8 = 2;
+    const NOW: u64 = 1_000_000;
+
+    fn setup_denylist() -> (TestVM, RiskOracle) {
+        let (vm, mut oracle) = setup_oracle();
+        vm.set_block_timestamp(NOW);
+        oracle.set_risk_manager(MANAGER, true).unwrap();
+        setup_members(&vm);
+        vm.set_sender(MANAGER);
+        (vm, oracle)
+    }
+
+    #[test]
+    fn test_denylisted_member_is_ineligible() {
+        let (_vm, mut oracle) = setup_denylist();
+        assert_eq!(oracle.are_all_members_eligible(vec![ALICE, BOB]), Ok(true));
+
+        oracle.deny_member(BOB, FRAUD, U256::ZERO).unwrap();
+        assert!(oracle.is_denylisted(BOB));
+        assert_eq!(oracle.is_member_eligible(BOB, true), Ok(false));
+        assert_eq!(oracle.is_member_eligible(BOB, false), Ok(false));
+        assert_eq!(oracle.is_member_eligible(ALICE, true), Ok(true));
+        assert_eq!(oracle.are_all_members_eligible(vec![ALICE, BOB]), Ok(false));
+        assert!(!oracle.evaluate_group(vec![ALICE, BOB]).unwrap().0);
+
+        oracle.rehabilitate_member(BOB).unwrap();
+        assert!(!oracle.is_denylisted(BOB));
+        assert_eq!(oracle.are_all_members_eligible(vec![ALICE, BOB]), Ok(true));
+    }
+
+    #[test]
+    fn test_denylisted_member_cannot_be_priced_or_quoted() {
+        let (vm, mut oracle) = setup_denylist();
+        vm.set_sender(OWNER);
+        oracle.set_quote_consumer(VAULT, true).unwrap();
+        let quote_id = oracle.quote(vec![ALICE, BOB], bps(1000), U256::from(30)).unwrap();
+
+        vm.set_sender(MANAGER);
+        oracle.deny_member(BOB, FRAUD, U256::ZERO).unwrap();
+        let rejected = MemberNotEligible { member: BOB }.abi_encode();
+        assert_eq!(oracle.get_leverage_level(vec![ALICE, BOB]), Err(rejected.clone()));
+        assert_eq!(oracle.quote(vec![ALICE, BOB], bps(1000), U256::from(30)), Err(rejected.clone()));
+
+        // A quote issued before the listing cannot be used to lend either
+        vm.set_sender(VAULT);
+        assert_eq!(oracle.consume_quote(quote_id, vec![ALICE, BOB]), Err(rejected));
+
+        vm.set_sender(MANAGER);
+        oracle.rehabilitate_member(BOB).unwrap();
+        vm.set_sender(VAULT);
+        oracle.consume_quote(quote_id, vec![ALICE, BOB]).unwrap();
+        assert!(oracle.get_leverage_level(vec![ALICE, BOB]).is_ok());
+    }
+
+    #[test]
+    fn test_entries_expire() {
+        let (vm, mut oracle) = setup_denylist();
+        let expiry = U256::from(NOW + 100);
+        oracle.deny_member(BOB, SANCTIONS, expiry).unwrap();
+        assert_eq!(oracle.get_denylist_entry(BOB), (true, SANCTIONS, expiry, true));
+
+        vm.set_block_timestamp(NOW + 100);
+        assert_eq!(oracle.get_denylist_entry(BOB), (true, SANCTIONS, expiry, false));
+        assert_eq!(oracle.is_member_eligible(BOB, true), Ok(true));
+
+        // Expired entries stay listed until rehabilitated
+        assert_eq!(oracle.get_denylist_count(), U256::from(1));
+        oracle.rehabilitate_member(BOB).unwrap();
+        assert_eq!(oracle.get_denylist_entry(BOB), (false, 0, U256::ZERO, false));
+    }
+
+    #[test]
+    fn test_relisting_updates_entry() {
+        let (_vm, mut oracle) = setup_denylist();
+        oracle.deny_member(BOB, SANCTIONS, U256::from(NOW + 100)).unwrap();
+        oracle.deny_member(BOB, FRAUD, U256::ZERO).unwrap();
+
+        assert_eq!(oracle.get_denylist_count(), U256::from(1));
+        assert_eq!(oracle.get_denylist_entry(BOB), (true, FRAUD, U256::ZERO, true));
+    }
+
+    #[test]
+    fn test_paginated_denylist() {
+        let (_vm, mut oracle) = setup_denylist();
+        oracle.deny_member(ALICE, FRAUD, U256::ZERO).unwrap();
+        oracle.deny_member(BOB, SANCTIONS, U256::from(NOW + 100)).unwrap();
+        oracle.deny_member(CAROL, FRAUD, U256::ZERO).unwrap();
+
+        assert_eq!(
+            oracle.get_denylist(U256::ZERO, U256::from(2)),
+            vec![(ALICE, FRAUD, U256::ZERO), (BOB, SANCTIONS, U256::from(NOW + 100))]
+        );
+        assert_eq!(
+            oracle.get_denylist(U256::from(2), U256::from(5)),
+            vec![(CAROL, FRAUD, U256::ZERO)]
+        );
+        assert_eq!(oracle.get_denylist(U256::from(3), U256::from(5)), vec![]);
+
+        // Removal moves the last entry into the freed slot
+        oracle.rehabilitate_member(ALICE).unwrap();
+        assert_eq!(
+            oracle.get_denylist(U256::ZERO, U256::MAX),
+            vec![(CAROL, FRAUD, U256::ZERO), (BOB, SANCTIONS, U256::from(NOW + 100))]
+        );
+        oracle.rehabilitate_member(BOB).unwrap();
+        assert_eq!(oracle.get_denylist(U256::ZERO, U256::MAX), vec![(CAROL, FRAUD, U256::ZERO)]);
+    }
+
+    #[test]
+    fn test_denylist_validation() {
+        let (_vm, mut oracle) = setup_denylist();
+        assert_eq!(
+            oracle.deny_member(Address::ZERO, FRAUD, U256::ZERO),
+            Err(InvalidAddress {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.deny_member(BOB, FRAUD, U256::from(NOW)),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.rehabilitate_member(BOB),
+            Err(MemberNotDenylisted { member: BOB }.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_only_risk_managers() {
+        let (vm, mut oracle) = setup_denylist();
+        assert!(oracle.is_risk_manager(MANAGER));
+
+        vm.set_sender(STRANGER);
+        assert_eq!(oracle.deny_member(BOB, FRAUD, U256::ZERO), Err(unauthorized()));
+        assert_eq!(oracle.set_risk_manager(STRANGER, true), Err(unauthorized()));
+
+        // The owner manages the list too, and can revoke managers
+        vm.set_sender(OWNER);
+        oracle.deny_member(BOB, FRAUD, U256::ZERO).unwrap();
+        oracle.set_risk_manager(MANAGER, false).unwrap();
+
+        vm.set_sender(MANAGER);
+        assert_eq!(oracle.rehabilitate_member(BOB), Err(unauthorized()));
+        assert!(oracle.is_denylisted(BOB));
+    }
+
+    #[test]
+    fn test_denylist_events() {
+        let (vm, mut oracle) = setup_denylist();
+        let before = vm.get_emitted_logs().len();
+        oracle.deny_member(BOB, SANCTIONS, U256::from(NOW + 100)).unwrap();
+        oracle.rehabilitate_member(BOB).unwrap();
+
+        let logs = vm.get_emitted_logs();
+        assert_eq!(logs.len(), before + 2);
+
+        let (topics, data) = logs[before].clone();
+        let event = MemberDenylisted::decode_raw_log(topics.iter().copied(), &data, true).unwrap();
+        assert_eq!(topics[1], BOB.into_word());
+        assert_eq!(topics[2], B256::from(U256::from(SANCTIONS)));
+        assert_eq!(topics[3], MANAGER.into_word());
+        assert_eq!(event.expiry, U256::from(NOW + 100));
+
+        let (topics, _) = logs[before + 1].clone();
+        assert_eq!(topics[0], MemberRehabilitated::SIGNATURE_HASH);
+        assert_eq!(topics[1], BOB.into_word());
+        assert_eq!(topics[2], MANAGER.into_word());
+    }
+}
@@ -0,0 +1,145 @@
+//! Dispersion-aware group risk: minimum level, level variance and new members.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::{SolError, SolEvent};
+    use risk_oracle::*;
+    use stylus_sdk::alloy_primitives::{Address, U256};
+    use stylus_sdk::testing::*;
+
+    fn member(i: u8) -> Address {
+        Address::new([0x40 + i; 20])
+    }
+
+    /// Six level-5 members
+    fn homogeneous_group(vm: &TestVM) -> Vec<Address> {
+        (0..6)
+            .map(|i| {
+                mock_member(vm, member(i), u64::from(i) + 1, metadata(5, 0), true);
+                member(i)
+            })
+            .collect()
+    }
+
+    /// Five level-6 members plus a newcomer at level 0: the same average of 5
+    fn skewed_group(vm: &TestVM) -> Vec<Address> {
+        (6..12)
+            .map(|i| {
+                let level = if i == 11 { 0 } else { 6 };
+                mock_member(vm, member(i), u64::from(i) + 1, metadata(level, 0), true);
+                member(i)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_same_average_different_risk() {
+        let (vm, oracle) = setup_oracle();
+        let homogeneous = homogeneous_group(&vm);
+        let skewed = skewed_group(&vm);
+
+        let uniform = oracle.get_risk_report(homogeneous.clone()).unwrap();
+        let spread = oracle.get_risk_report(skewed.clone()).unwrap();
+        assert_eq!(uniform.averageLevelX100, bps(500));
+        assert_eq!(spread.averageLevelX100, bps(500));
+
+        assert_eq!(uniform.minLevel, 5);
+        assert_eq!(uniform.levelVarianceX100, U256::ZERO);
+        assert_eq!(uniform.newMemberShareBps, U256::ZERO);
+        assert_eq!(uniform.dispersionPenaltyBps, U256::ZERO);
+
+        assert_eq!(spread.minLevel, 0);
+        assert_eq!(spread.levelVarianceX100, bps(500));
+        assert_eq!(spread.newMemberShareBps, bps(1666));
+        // 5 * 500 for the variance plus 1666 * 20% for the newcomer
+        assert_eq!(spread.dispersionPenaltyBps, bps(2833));
+
+        assert_eq!(oracle.get_leverage_level(homogeneous), Ok((bps(500), bps(800))));
+        // Score 3000 lands on the 3x tier, then dispersion takes 28.33% of it
+        assert_eq!(oracle.get_leverage_level(skewed), Ok((bps(216), bps(1000))));
+    }
+
+    #[test]
+    fn test_weakest_member_caps_the_tier() {
+        let (vm, mut oracle) = setup_oracle();
+        oracle.set_dispersion_penalty(U256::ZERO, U256::ZERO).unwrap();
+        let veterans: Vec<Address> = (0..5)
+            .map(|i| {
+                mock_member(&vm, member(i), u64::from(i) + 1, metadata(10, 0), true);
+                member(i)
+            })
+            .collect();
+        mock_member(&vm, member(5), 6, metadata(3, 0), true);
+        mock_member(&vm, member(6), 7, metadata(1, 0), true);
+
+        // A level-3 member reaches tier 1 alone, so the group may sit on the 5x tier
+        let mut group = veterans.clone();
+        group.push(member(5));
+        assert_eq!(oracle.get_leverage_level(group), Ok((bps(500), bps(800))));
+
+        // Score 6325 still reaches tier 2, but a level-1 member holds it to tier 1
+        let mut group = veterans;
+        group.push(member(6));
+        let report = oracle.get_risk_report(group.clone()).unwrap();
+        assert_eq!(report.groupScore, bps(6325));
+        assert_eq!(report.minLevel, 1);
+        assert!(report.minLevelCapped);
+        assert_eq!(report.tierId, U256::from(1));
+        assert_eq!(oracle.get_leverage_level(group), Ok((bps(300), bps(1000))));
+    }
+
+    #[test]
+    fn test_dispersion_penalty_configurable() {
+        let (vm, mut oracle) = setup_oracle();
+        let homogeneous = homogeneous_group(&vm);
+        let skewed = skewed_group(&vm);
+
+        oracle.set_dispersion_penalty(U256::ZERO, U256::ZERO).unwrap();
+        assert_eq!(oracle.get_dispersion_penalty(), (U256::ZERO, U256::ZERO));
+        assert_eq!(oracle.get_leverage_level(skewed.clone()), Ok((bps(300), bps(1000))));
+
+        // The penalty never takes a group below 1x and leaves uniform groups alone
+        oracle.set_dispersion_penalty(bps(10_000), bps(10_000)).unwrap();
+        assert_eq!(oracle.get_leverage_level(skewed), Ok((bps(100), bps(1000))));
+        assert_eq!(oracle.get_leverage_level(homogeneous), Ok((bps(500), bps(800))));
+    }
+
+    #[test]
+    fn test_set_dispersion_penalty_validation() {
+        let (vm, mut oracle) = setup_oracle();
+        assert_eq!(oracle.get_dispersion_penalty(), (bps(500), bps(2000)));
+
+        assert_eq!(
+            oracle.set_dispersion_penalty(bps(10_001), U256::ZERO),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.set_dispersion_penalty(U256::ZERO, bps(10_001)),
+            Err(InvalidParameter {}.abi_encode())
+        );
+
+        vm.set_sender(STRANGER);
+        assert_eq!(
+            oracle.set_dispersion_penalty(U256::ZERO, U256::ZERO),
+            Err(unauthorized())
+        );
+        assert_eq!(oracle.get_dispersion_penalty(), (bps(500), bps(2000)));
+    }
+
+    #[test]
+    fn test_set_dispersion_penalty_event() {
+        let (vm, mut oracle) = setup_oracle();
+        let before = vm.get_emitted_logs().len();
+        oracle.set_dispersion_penalty(bps(300), bps(1000)).unwrap();
+
+        let logs = vm.get_emitted_logs();
+        assert_eq!(logs.len(), before + 1);
+        let (topics, data) = logs.last().unwrap().clone();
+        let event = DispersionPenaltyUpdated::decode_raw_log(topics.iter().copied(), &data, true).unwrap();
+        assert_eq!(event.variance_penalty_bps, bps(300));
+        assert_eq!(event.new_member_penalty_bps, bps(1000));
+    }
+}
@@ -0,0 +1,134 @@
+//! Configurable VRF draw weights, normalized to 10000 bps.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::{SolError, SolEvent};
+    use risk_oracle::*;
+    use stylus_sdk::alloy_primitives::{Address, U256};
+    use stylus_sdk::testing::*;
+
+
+    fn setup_draw() -> (TestVM, RiskOracle) {
+        let (vm, oracle) = setup_oracle();
+        setup_members(&vm);
+        (vm, oracle)
+    }
+
+    fn odds(oracle: &RiskOracle) -> Vec<U256> {
+        oracle.get_weighted_probabilities(vec![ALICE, BOB, CAROL]).unwrap()
+    }
+
+    #[test]
+    fn test_linear_is_default() {
+        let (_vm, oracle) = setup_draw();
+        assert_eq!(oracle.draw_weight_mode(), DRAW_LINEAR);
+        // 13:15:14, the spare bps goes to the largest remainder
+        assert_eq!(odds(&oracle), vec![bps(3095), bps(3572), bps(3333)]);
+    }
+
+    #[test]
+    fn test_weighting_modes() {
+        let (_vm, mut oracle) = setup_draw();
+
+        oracle.set_draw_weight_mode(DRAW_UNIFORM).unwrap();
+        assert_eq!(odds(&oracle), vec![bps(3334), bps(3333), bps(3333)]);
+
+        // 19:35:26
+        oracle.set_draw_weight_mode(DRAW_QUADRATIC).unwrap();
+        assert_eq!(odds(&oracle), vec![bps(2375), bps(4375), bps(3250)]);
+
+        // CAROL's stain halves her linear weight: 13:15:7
+        oracle.set_draw_weight_mode(DRAW_STAIN_PENALIZED).unwrap();
+        assert_eq!(odds(&oracle), vec![bps(3714), bps(4286), bps(2000)]);
+
+        // Blended scores 5100:6500:4238
+        oracle.set_draw_weight_mode(DRAW_REPUTATION).unwrap();
+        assert_eq!(odds(&oracle), vec![bps(3220), bps(4104), bps(2676)]);
+    }
+
+    #[test]
+    fn test_odds_always_sum_to_10000() {
+        let (vm, mut oracle) = setup_oracle();
+        let group: Vec<Address> = (0..6).map(|i| Address::new([0x40 + i; 20])).collect();
+        for (i, member) in group.iter().enumerate() {
+            mock_member(&vm, *member, i as u64 + 1, metadata(i as u8 * 2, i as u16 % 2), true);
+        }
+
+        for mode in [DRAW_UNIFORM, DRAW_LINEAR, DRAW_QUADRATIC, DRAW_STAIN_PENALIZED, DRAW_REPUTATION] {
+            oracle.set_draw_weight_mode(mode).unwrap();
+            let odds = oracle.get_weighted_probabilities(group.clone()).unwrap();
+            assert_eq!(odds.iter().fold(U256::ZERO, |sum, odds| sum + odds), bps(10_000));
+        }
+
+        // Ties resolve towards the first members
+        oracle.set_draw_weight_mode(DRAW_UNIFORM).unwrap();
+        assert_eq!(
+            oracle.get_weighted_probabilities(group).unwrap(),
+            vec![bps(1667), bps(1667), bps(1667), bps(1667), bps(1666), bps(1666)]
+        );
+    }
+
+    #[test]
+    fn test_zero_weights_fall_back_to_uniform() {
+        let (vm, mut oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(0, 0), true);
+        mock_member(&vm, BOB, 2, metadata(0, 0), true);
+
+        oracle.set_draw_weight_mode(DRAW_REPUTATION).unwrap();
+        assert_eq!(
+            oracle.preview_draw_odds(vec![ALICE, BOB]),
+            Ok(vec![(ALICE, U256::ZERO, bps(5000)), (BOB, U256::ZERO, bps(5000))])
+        );
+    }
+
+    #[test]
+    fn test_preview_draw_odds() {
+        let (_vm, oracle) = setup_draw();
+        assert_eq!(
+            oracle.preview_draw_odds(vec![ALICE, BOB, CAROL]),
+            Ok(vec![
+                (ALICE, bps(13), bps(3095)),
+                (BOB, bps(15), bps(3572)),
+                (CAROL, bps(14), bps(3333)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_unreadable_members_are_errors() {
+        let (vm, oracle) = setup_draw();
+        mock_member(&vm, STRANGER, 0, metadata(0, 0), false);
+
+        assert_eq!(
+            oracle.get_weighted_probabilities(vec![ALICE, STRANGER]),
+            Err(MemberNotEligible { member: STRANGER }.abi_encode())
+        );
+        let missing = Address::new([0x99; 20]);
+        assert_eq!(
+            oracle.preview_draw_odds(vec![ALICE, missing]),
+            Err(AguayoCallFailed { member: missing }.abi_encode())
+        );
+        assert_eq!(oracle.preview_draw_odds(vec![]), Err(EmptyMemberList {}.abi_encode()));
+    }
+
+    #[test]
+    fn test_set_draw_weight_mode() {
+        let (vm, mut oracle) = setup_draw();
+        assert_eq!(oracle.set_draw_weight_mode(5), Err(InvalidParameter {}.abi_encode()));
+
+        let before = vm.get_emitted_logs().len();
+        oracle.set_draw_weight_mode(DRAW_QUADRATIC).unwrap();
+        let logs = vm.get_emitted_logs();
+        assert_eq!(logs.len(), before + 1);
+        let (topics, data) = logs.last().unwrap().clone();
+        let event = DrawWeightModeUpdated::decode_raw_log(topics.iter().copied(), &data, true).unwrap();
+        assert_eq!(event.mode, DRAW_QUADRATIC);
+
+        vm.set_sender(STRANGER);
+        assert_eq!(oracle.set_draw_weight_mode(DRAW_UNIFORM), Err(unauthorized()));
+        assert_eq!(oracle.draw_weight_mode(), DRAW_QUADRATIC);
+    }
+}
@@ -0,0 +1,128 @@
+//! Typed, ABI-decodable revert data from every RiskOracle error path.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::{SolCall, SolError, SolValue};
+    use risk_oracle::*;
+    use stylus_sdk::alloy_primitives::{Address, U256};
+
+
+    fn selector(revert: &[u8]) -> [u8; 4] {
+        revert[..4].try_into().unwrap()
+    }
+
+    #[test]
+    fn test_empty_member_list() {
+        let (_vm, oracle) = setup_oracle();
+        let err = oracle.get_leverage_level(vec![]).unwrap_err();
+        assert_eq!(selector(&err), EmptyMemberList::SELECTOR);
+        assert_eq!(err, EmptyMemberList {}.abi_encode());
+        assert_eq!(
+            oracle.get_weighted_probabilities(vec![]),
+            Err(EmptyMemberList {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_member_without_aguayo_is_named() {
+        let (vm, oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(3, 0), true);
+        mock_member(&vm, BOB, 0, metadata(0, 0), false);
+
+        let err = oracle.get_leverage_level(vec![ALICE, BOB]).unwrap_err();
+        assert_eq!(selector(&err), MemberNotEligible::SELECTOR);
+        let decoded = MemberNotEligible::abi_decode(&err, true).unwrap();
+        assert_eq!(decoded.member, BOB);
+    }
+
+    #[test]
+    fn test_failed_lookup_is_named() {
+        let (vm, oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(3, 0), true);
+
+        // BOB's lookup is not mocked, so the SBT call reverts
+        let err = oracle.get_leverage_level(vec![ALICE, BOB]).unwrap_err();
+        assert_eq!(selector(&err), AguayoCallFailed::SELECTOR);
+        assert_eq!(AguayoCallFailed::abi_decode(&err, true).unwrap().member, BOB);
+    }
+
+    #[test]
+    fn test_credit_check_failure_is_named() {
+        let (vm, oracle) = setup_oracle();
+        vm.mock_static_call(
+            AGUAYO_SBT,
+            IAguayoSBT::userToAguayoCall { user: ALICE }.abi_encode(),
+            Ok(U256::from(7).abi_encode()),
+        );
+
+        assert_eq!(oracle.is_member_eligible(ALICE, false), Ok(true));
+        assert_eq!(
+            oracle.is_member_eligible(ALICE, true),
+            Err(AguayoCallFailed { member: ALICE }.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_invalid_tier_id() {
+        let (_vm, mut oracle) = setup_oracle();
+        let err = oracle.get_leverage_tier(U256::from(3)).unwrap_err();
+        assert_eq!(selector(&err), InvalidTierId::SELECTOR);
+        assert_eq!(
+            oracle.get_leverage_tier(U256::MAX),
+            Err(InvalidTierId {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.update_leverage_tier(U256::from(3), 1, bps(100), bps(100)),
+            Err(InvalidTierId {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_invalid_parameter() {
+        let (_vm, mut oracle) = setup_oracle();
+        let err = oracle.add_leverage_tier(1, U256::ZERO, bps(100)).unwrap_err();
+        assert_eq!(selector(&err), InvalidParameter::SELECTOR);
+        assert_eq!(
+            oracle.add_leverage_tier(1, bps(100), bps(10_001)),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.set_base_interest_rate(U256::ZERO),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.transfer_ownership(Address::ZERO),
+            Err(InvalidAddress {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_admin_errors_are_typed() {
+        let (vm, mut oracle) = setup_oracle();
+        assert_eq!(
+            selector(&oracle.initialize(AGUAYO_SBT).unwrap_err()),
+            AlreadyInitialized::SELECTOR
+        );
+
+        vm.set_sender(STRANGER);
+        let err = oracle.set_risk_premium(bps(100)).unwrap_err();
+        assert_eq!(selector(&err), Unauthorized::SELECTOR);
+        assert!(Unauthorized::abi_decode(&err, true).is_ok());
+    }
+
+    #[test]
+    fn test_valid_group_still_prices() {
+        let (vm, oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(3, 0), true);
+        mock_member(&vm, BOB, 2, metadata(3, 0), true);
+
+        assert_eq!(
+            oracle.get_leverage_level(vec![ALICE, BOB]),
+            Ok((bps(500), bps(800)))
+        );
+        assert_eq!(oracle.are_all_members_eligible(vec![ALICE, BOB]), Ok(true));
+    }
+}
@@ -0,0 +1,103 @@
+//! Combined group evaluation and its SBT call budget.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::metered::SBT_VIEW_GAS;
+    use super::common::*;
+    use alloy_sol_types::SolError;
+    use risk_oracle::*;
+    use stylus_sdk::alloy_primitives::{Address, U256};
+
+    fn members(count: u8) -> Vec<Address> {
+        (0..count).map(|i| Address::new([0x20 + i; 20])).collect()
+    }
+
+    #[test]
+    fn test_evaluate_group_matches_individual_views() {
+        let (vm, oracle) = setup_oracle();
+        let group = members(3);
+        mock_member(&vm, group[0], 1, metadata(3, 0), true);
+        mock_member(&vm, group[1], 2, metadata(5, 0), true);
+        mock_member(&vm, group[2], 3, metadata(4, 1), true);
+
+        let (eligible, weights, score, multiplier, rate) =
+            oracle.evaluate_group(group.clone()).unwrap();
+
+        assert_eq!(eligible, oracle.are_all_members_eligible(group.clone()).unwrap());
+        assert_eq!(weights, oracle.get_weighted_probabilities(group.clone()).unwrap());
+        assert_eq!((multiplier, rate), oracle.get_leverage_level(group.clone()).unwrap());
+        // 13:15:14 in bps, the spare unit going to the largest remainder
+        assert_eq!(weights, vec![bps(3095), bps(3572), bps(3333)]);
+        assert!(eligible);
+
+        let scores: Vec<U256> = group
+            .iter()
+            .map(|member| oracle.get_member_score(*member).unwrap())
+            .collect();
+        let mean = scores.iter().fold(U256::ZERO, |sum, score| sum + score) / U256::from(3);
+        let min = *scores.iter().min().unwrap();
+        assert_eq!(score, mean - (mean - min) / U256::from(2));
+    }
+
+    #[test]
+    fn test_evaluate_group_reports_ineligible_members() {
+        let (vm, mut oracle) = setup_oracle();
+        let group = members(2);
+        mock_member(&vm, group[0], 1, metadata(3, 0), true);
+        mock_member(&vm, group[1], 2, metadata(5, 0), true);
+
+        oracle.set_min_level_for_credit(4).unwrap();
+        let (eligible, _, _, _, _) = oracle.evaluate_group(group).unwrap();
+        assert!(!eligible);
+    }
+
+    #[test]
+    fn test_evaluate_group_errors() {
+        let (vm, oracle) = setup_oracle();
+        assert_eq!(
+            oracle.evaluate_group(vec![]).map(|r| r.0),
+            Err(EmptyMemberList {}.abi_encode())
+        );
+
+        let group = members(2);
+        mock_member(&vm, group[0], 1, metadata(3, 0), true);
+        mock_member(&vm, group[1], 0, metadata(0, 0), false);
+        assert_eq!(
+            oracle.evaluate_group(group.clone()).map(|r| r.0),
+            Err(MemberNotEligible { member: group[1] }.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_gas_benchmark_single_pass() {
+        let (host, oracle) = setup_metered_oracle();
+        let group = members(8);
+        for (i, member) in group.iter().enumerate() {
+            mock_member(&host.vm, *member, i as u64 + 1, metadata(3 + i as u8 % 3, 0), true);
+        }
+
+        host.reset();
+        oracle.are_all_members_eligible(group.clone()).unwrap();
+        oracle.get_weighted_probabilities(group.clone()).unwrap();
+        oracle.get_leverage_level(group.clone()).unwrap();
+        let separate_calls = host.calls();
+        let separate_gas = host.estimated_gas();
+
+        host.reset();
+        oracle.evaluate_group(group.clone()).unwrap();
+        let combined_calls = host.calls();
+        let combined_gas = host.estimated_gas();
+
+        println!(
+            "8 members: separate views {separate_calls} SBT calls (~{separate_gas} gas), \
+             evaluate_group {combined_calls} SBT calls (~{combined_gas} gas)"
+        );
+
+        // Token, metadata and credit verdict once per member
+        assert_eq!(combined_calls, 3 * 8);
+        assert_eq!(separate_calls, 7 * 8);
+        assert_eq!(separate_gas - combined_gas, 4 * 8 * SBT_VIEW_GAS);
+    }
+}
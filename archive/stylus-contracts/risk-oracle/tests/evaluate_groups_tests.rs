@@ -0,0 +1,128 @@
+//! Batch evaluation of candidate groups with shared member lookups.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::{SolCall, SolError, SolValue};
+    use risk_oracle::*;
+    use stylus_sdk::alloy_primitives::{Address, U256};
+
+    const ATTESTATIONS: Address = Address::new([0x30; 20]);
+
+    fn members(count: u8) -> Vec<Address> {
+        (0..count).map(|i| Address::new([0x20 + i; 20])).collect()
+    }
+
+    #[test]
+    fn test_batch_matches_single_evaluations() {
+        let (vm, mut oracle) = setup_oracle();
+        let pool = members(4);
+        for (i, member) in pool.iter().enumerate() {
+            mock_member(&vm, *member, i as u64 + 1, metadata(2 + i as u8 * 2, i as u16 % 2), true);
+        }
+        oracle.set_min_level_for_credit(4).unwrap();
+
+        let groups = vec![
+            vec![pool[0], pool[1]],
+            vec![pool[1], pool[2], pool[3]],
+            vec![pool[2]],
+        ];
+        let results = oracle.evaluate_groups(groups.clone()).unwrap();
+
+        assert_eq!(results.len(), 3);
+        for (group, (eligible, multiplier, rate)) in groups.into_iter().zip(results) {
+            let (expected_eligible, _, _, expected_multiplier, expected_rate) =
+                oracle.evaluate_group(group).unwrap();
+            assert_eq!((eligible, multiplier, rate), (expected_eligible, expected_multiplier, expected_rate));
+        }
+        // pool[0] is below the credit minimum
+        assert!(!oracle.evaluate_groups(vec![vec![pool[0], pool[1]]]).unwrap()[0].0);
+    }
+
+    #[test]
+    fn test_unpriceable_groups_do_not_revert_the_batch() {
+        let (vm, mut oracle) = setup_oracle();
+        let pool = members(3);
+        mock_member(&vm, pool[0], 1, metadata(5, 0), true);
+        mock_member(&vm, pool[1], 0, metadata(0, 0), false);
+
+        let results = oracle
+            .evaluate_groups(vec![vec![pool[0], pool[1]], vec![pool[2]], vec![pool[0]]])
+            .unwrap();
+        assert_eq!(results[0], (false, U256::ZERO, U256::ZERO));
+        assert_eq!(results[1], (false, U256::ZERO, U256::ZERO));
+        assert_eq!(results[2], oracle.evaluate_groups(vec![vec![pool[0]]]).unwrap()[0]);
+
+        // Denylisted members still price, but make the group ineligible
+        oracle.deny_member(pool[0], 1, U256::ZERO).unwrap();
+        let (eligible, multiplier, _) = oracle.evaluate_groups(vec![vec![pool[0]]]).unwrap()[0];
+        assert!(!eligible);
+        assert!(multiplier > U256::ZERO);
+    }
+
+    #[test]
+    fn test_shared_members_are_read_once() {
+        let (host, mut oracle) = setup_metered_oracle();
+        let pool = members(4);
+        for (i, member) in pool.iter().enumerate() {
+            mock_member(&host.vm, *member, i as u64 + 1, metadata(3 + i as u8, 0), true);
+            host.vm.mock_static_call(
+                ATTESTATIONS,
+                IReputationAttestation::scoreOfCall { subject: *member }.abi_encode(),
+                Ok(U256::from(8000).abi_encode()),
+            );
+        }
+        oracle
+            .add_reputation_provider(ATTESTATIONS, PROVIDER_ATTESTATION, bps(5000), U256::ZERO)
+            .unwrap();
+
+        // Every pair of the four members: each member appears in three groups
+        let mut groups = vec![];
+        for i in 0..pool.len() {
+            for j in i + 1..pool.len() {
+                groups.push(vec![pool[i], pool[j]]);
+            }
+        }
+
+        host.reset();
+        let results = oracle.evaluate_groups(groups.clone()).unwrap();
+        assert_eq!(results.len(), 6);
+        // Token, metadata, credit verdict and attestation once per distinct member
+        assert_eq!(host.calls(), 4 * 4);
+
+        host.reset();
+        for group in groups {
+            oracle.evaluate_group(group).unwrap();
+        }
+        assert_eq!(host.calls(), 6 * 2 * 4);
+    }
+
+    #[test]
+    fn test_batch_limits() {
+        let (vm, oracle) = setup_oracle();
+        let pool = members(2);
+        mock_member(&vm, pool[0], 1, metadata(3, 0), true);
+        mock_member(&vm, pool[1], 2, metadata(5, 0), true);
+
+        assert_eq!(oracle.evaluate_groups(vec![]), Err(EmptyMemberList {}.abi_encode()));
+        assert_eq!(
+            oracle.evaluate_groups(vec![vec![pool[0]], vec![]]),
+            Err(EmptyMemberList {}.abi_encode())
+        );
+
+        // Repeated members count towards the cap
+        let at_cap = vec![pool.clone(); MAX_BATCH_MEMBERS as usize / 2];
+        assert_eq!(oracle.evaluate_groups(at_cap).unwrap().len(), 50);
+        let over_cap = vec![vec![pool[0]]; MAX_BATCH_MEMBERS as usize + 1];
+        assert_eq!(
+            oracle.evaluate_groups(over_cap),
+            Err(BatchTooLarge {
+                member_count: U256::from(MAX_BATCH_MEMBERS + 1),
+                max_members: U256::from(MAX_BATCH_MEMBERS),
+            }
+            .abi_encode())
+        );
+    }
+}
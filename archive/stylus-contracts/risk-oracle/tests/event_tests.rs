@@ -0,0 +1,127 @@
+//! Configuration events emitted by each RiskOracle setter.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::SolEvent;
+    use risk_oracle::*;
+    use stylus_sdk::alloy_primitives::{Address, B256, U256};
+    use stylus_sdk::testing::*;
+
+    /// Decodes the single log emitted since `before`
+    fn only_new_log<E: SolEvent>(vm: &TestVM, before: usize) -> (Vec<B256>, E) {
+        let logs = vm.get_emitted_logs();
+        assert_eq!(logs.len(), before + 1);
+        let (topics, data) = logs.last().unwrap().clone();
+        assert_eq!(topics[0], E::SIGNATURE_HASH);
+        let event = E::decode_raw_log(topics.iter().copied(), &data, true).unwrap();
+        (topics, event)
+    }
+
+    #[test]
+    fn test_initialize_emits_ownership() {
+        let vm = TestVM::new();
+        let mut oracle = RiskOracle::from(&vm);
+        vm.set_sender(OWNER);
+        oracle.initialize(AGUAYO_SBT).unwrap();
+
+        let (topics, event) = only_new_log::<OwnershipTransferred>(&vm, 0);
+        assert_eq!(topics[1], Address::ZERO.into_word());
+        assert_eq!(topics[2], OWNER.into_word());
+        assert_eq!(event.new_owner, OWNER);
+    }
+
+    #[test]
+    fn test_add_leverage_tier_event() {
+        let (vm, mut oracle) = setup_oracle();
+        let before = vm.get_emitted_logs().len();
+        oracle.add_leverage_tier(7, bps(500), U256::ZERO).unwrap();
+
+        let (topics, event) = only_new_log::<LeverageTierAdded>(&vm, before);
+        assert_eq!(topics[1], B256::from(U256::from(7)));
+        assert_eq!(event.min_average_level, 7);
+        assert_eq!(event.multiplier, bps(500));
+        assert_eq!(event.interest_rate, U256::ZERO);
+    }
+
+    #[test]
+    fn test_update_leverage_tier_event() {
+        let (vm, mut oracle) = setup_oracle();
+        let before = vm.get_emitted_logs().len();
+        oracle
+            .update_leverage_tier(U256::from(1), 2, bps(250), bps(300))
+            .unwrap();
+
+        let (topics, event) = only_new_log::<LeverageTierUpdated>(&vm, before);
+        assert_eq!(topics[1], B256::from(U256::from(1)));
+        assert_eq!(event.min_average_level, 2);
+        assert_eq!(event.multiplier, bps(250));
+        assert_eq!(event.interest_rate, bps(300));
+    }
+
+    #[test]
+    fn test_min_level_event() {
+        let (vm, mut oracle) = setup_oracle();
+        let before = vm.get_emitted_logs().len();
+        oracle.set_min_level_for_credit(3).unwrap();
+
+        let (_, event) = only_new_log::<MinLevelForCreditUpdated>(&vm, before);
+        assert_eq!(event.new_min_level, 3);
+    }
+
+    #[test]
+    fn test_max_leverage_event() {
+        let (vm, mut oracle) = setup_oracle();
+        let before = vm.get_emitted_logs().len();
+        oracle.set_max_leverage_multiplier(bps(800)).unwrap();
+
+        let (_, event) = only_new_log::<MaxLeverageUpdated>(&vm, before);
+        assert_eq!(event.new_max_multiplier, bps(800));
+    }
+
+    #[test]
+    fn test_base_interest_rate_event() {
+        let (vm, mut oracle) = setup_oracle();
+        let before = vm.get_emitted_logs().len();
+        oracle.set_base_interest_rate(bps(900)).unwrap();
+
+        let (_, event) = only_new_log::<BaseInterestRateUpdated>(&vm, before);
+        assert_eq!(event.new_rate_bps, bps(900));
+    }
+
+    #[test]
+    fn test_risk_premium_event() {
+        let (vm, mut oracle) = setup_oracle();
+        let before = vm.get_emitted_logs().len();
+        oracle.set_risk_premium(bps(350)).unwrap();
+
+        let (_, event) = only_new_log::<RiskPremiumUpdated>(&vm, before);
+        assert_eq!(event.new_premium_bps, bps(350));
+    }
+
+    #[test]
+    fn test_transfer_ownership_event() {
+        let (vm, mut oracle) = setup_oracle();
+        let before = vm.get_emitted_logs().len();
+        oracle.transfer_ownership(STRANGER).unwrap();
+
+        let (topics, event) = only_new_log::<OwnershipTransferred>(&vm, before);
+        assert_eq!(topics[1], OWNER.into_word());
+        assert_eq!(topics[2], STRANGER.into_word());
+        assert_eq!(event.previous_owner, OWNER);
+    }
+
+    #[test]
+    fn test_rejected_changes_emit_nothing() {
+        let (vm, mut oracle) = setup_oracle();
+        let before = vm.get_emitted_logs().len();
+
+        oracle.set_max_leverage_multiplier(U256::ZERO).unwrap_err();
+        vm.set_sender(STRANGER);
+        oracle.set_risk_premium(bps(100)).unwrap_err();
+
+        assert_eq!(vm.get_emitted_logs().len(), before);
+    }
+}
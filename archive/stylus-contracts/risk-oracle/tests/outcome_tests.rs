@@ -0,0 +1,300 @@
+//! Circle outcome reporting, realized default rates and tier recalibration.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::{SolError, SolEvent};
+    use risk_oracle::*;
+    use stylus_sdk::alloy_primitives::{Address, B256, U256};
+    use stylus_sdk::testing::*;
+
+    const CIRCLE: Address = Address::new([0x20; 20]);
+
+    fn setup_reporting() -> (TestVM, RiskOracle) {
+        let (vm, mut oracle) = setup_oracle();
+        oracle.set_outcome_reporter(CIRCLE, true).unwrap();
+        oracle.set_quote_consumer(VAULT, true).unwrap();
+        setup_members(&vm);
+        vm.set_sender(CIRCLE);
+        (vm, oracle)
+    }
+
+    /// Quotes `members` for `days` and has the vault lend against it; leaves `CIRCLE` as sender
+    fn lend(vm: &TestVM, oracle: &mut RiskOracle, members: &[Address], days: u64) -> U256 {
+        let quote_id = oracle.quote(members.to_vec(), bps(1000), U256::from(days)).unwrap();
+        vm.set_sender(VAULT);
+        oracle.consume_quote(quote_id, members.to_vec()).unwrap();
+        vm.set_sender(CIRCLE);
+        quote_id
+    }
+
+    /// Five BOB loans over a year, each losing 300 bps
+    fn report_top_tier_losses(vm: &TestVM, oracle: &mut RiskOracle) {
+        for _ in 0..5 {
+            let quote_id = lend(vm, oracle, &[BOB], 365);
+            oracle
+                .record_circle_outcome(vec![BOB], quote_id, vec![BOB], bps(300))
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_only_authorized_reporters() {
+        let (vm, mut oracle) = setup_oracle();
+        oracle.set_quote_consumer(VAULT, true).unwrap();
+        mock_member(&vm, ALICE, 1, metadata(3, 0), true);
+        assert!(!oracle.is_outcome_reporter(CIRCLE));
+        let quote_id = lend(&vm, &mut oracle, &[ALICE], 365);
+
+        assert_eq!(
+            oracle.record_circle_outcome(vec![ALICE], quote_id, vec![], U256::ZERO),
+            Err(unauthorized())
+        );
+        assert_eq!(oracle.set_outcome_reporter(CIRCLE, true), Err(unauthorized()));
+
+        vm.set_sender(OWNER);
+        assert_eq!(
+            oracle.set_outcome_reporter(Address::ZERO, true),
+            Err(InvalidAddress {}.abi_encode())
+        );
+        oracle.set_outcome_reporter(CIRCLE, true).unwrap();
+        assert!(oracle.is_outcome_reporter(CIRCLE));
+
+        vm.set_sender(CIRCLE);
+        oracle
+            .record_circle_outcome(vec![ALICE], quote_id, vec![], U256::ZERO)
+            .unwrap();
+
+        vm.set_sender(OWNER);
+        oracle.set_outcome_reporter(CIRCLE, false).unwrap();
+        vm.set_sender(CIRCLE);
+        assert_eq!(
+            oracle.record_circle_outcome(vec![ALICE], quote_id, vec![], U256::ZERO),
+            Err(unauthorized())
+        );
+    }
+
+    #[test]
+    fn test_record_outcome_validation() {
+        let (vm, mut oracle) = setup_reporting();
+        let quote_id = lend(&vm, &mut oracle, &[ALICE, BOB], 365);
+
+        assert_eq!(
+            oracle.record_circle_outcome(vec![], quote_id, vec![], U256::ZERO),
+            Err(EmptyMemberList {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.record_circle_outcome(vec![ALICE], U256::from(9), vec![], U256::ZERO),
+            Err(QuoteNotFound { quote_id: U256::from(9) }.abi_encode())
+        );
+        assert_eq!(
+            oracle.record_circle_outcome(vec![ALICE, BOB], quote_id, vec![], bps(10_001)),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.record_circle_outcome(vec![ALICE], quote_id, vec![], U256::ZERO),
+            Err(QuoteMembersMismatch { quote_id }.abi_encode())
+        );
+        // Defaulters must be members, listed once
+        assert_eq!(
+            oracle.record_circle_outcome(vec![ALICE, BOB], quote_id, vec![CAROL], U256::ZERO),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.record_circle_outcome(vec![ALICE, BOB], quote_id, vec![BOB, BOB], U256::ZERO),
+            Err(InvalidParameter {}.abi_encode())
+        );
+
+        // Only loans that were actually made, and only once
+        let unused = oracle.quote(vec![BOB], bps(1000), U256::from(30)).unwrap();
+        assert_eq!(
+            oracle.record_circle_outcome(vec![BOB], unused, vec![], U256::ZERO),
+            Err(QuoteNotConsumed { quote_id: unused }.abi_encode())
+        );
+        oracle
+            .record_circle_outcome(vec![BOB, ALICE], quote_id, vec![], U256::ZERO)
+            .unwrap();
+        assert_eq!(
+            oracle.record_circle_outcome(vec![ALICE, BOB], quote_id, vec![], U256::ZERO),
+            Err(OutcomeAlreadyRecorded { quote_id }.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_realized_default_rates() {
+        let (vm, mut oracle) = setup_reporting();
+        let first = lend(&vm, &mut oracle, &[ALICE, BOB, CAROL], 365);
+        let second = lend(&vm, &mut oracle, &[BOB], 365);
+
+        let before = vm.get_emitted_logs().len();
+        oracle
+            .record_circle_outcome(vec![ALICE, BOB, CAROL], first, vec![CAROL], bps(1000))
+            .unwrap();
+        oracle
+            .record_circle_outcome(vec![BOB], second, vec![], U256::ZERO)
+            .unwrap();
+
+        let logs = vm.get_emitted_logs();
+        assert_eq!(logs.len(), before + 2);
+        let (topics, data) = logs[before].clone();
+        let event = CircleOutcomeRecorded::decode_raw_log(topics.iter().copied(), &data, true).unwrap();
+        assert_eq!(event.reporter, CIRCLE);
+        assert_eq!(topics[2], B256::from(first));
+        // ALICE holds the group below BOB's own tier
+        assert_eq!(event.tier_key, oracle.get_tier_key(U256::from(1)).unwrap());
+        assert_eq!(event.member_count, U256::from(3));
+        assert_eq!(event.defaulted_count, U256::from(1));
+        assert_eq!((event.loss_bps, event.annual_loss_bps), (bps(1000), bps(1000)));
+
+        // Each circle counts on the tier it was priced on
+        assert_eq!(
+            oracle.get_tier_outcomes(U256::from(1)),
+            (U256::from(1), U256::from(3), U256::from(1), bps(3333), bps(1000))
+        );
+        assert_eq!(
+            oracle.get_tier_outcomes(U256::from(2)),
+            (U256::from(1), U256::from(1), U256::ZERO, U256::ZERO, U256::ZERO)
+        );
+        assert_eq!(oracle.get_tier_outcomes(U256::ZERO), Default::default());
+        assert_eq!(oracle.get_level_outcomes(3), (U256::from(1), U256::ZERO, U256::ZERO));
+        assert_eq!(oracle.get_level_outcomes(4), (U256::from(1), U256::from(1), bps(10_000)));
+        assert_eq!(oracle.get_level_outcomes(5), (U256::from(2), U256::ZERO, U256::ZERO));
+        assert_eq!(oracle.get_level_outcomes(7), (U256::ZERO, U256::ZERO, U256::ZERO));
+    }
+
+    #[test]
+    fn test_levels_counted_at_origination() {
+        let (vm, mut oracle) = setup_reporting();
+        let quote_id = lend(&vm, &mut oracle, &[ALICE, BOB], 365);
+
+        // ALICE levels up while the loan runs, then defaults
+        mock_member(&vm, ALICE, 1, metadata(7, 0), true);
+        oracle
+            .record_circle_outcome(vec![ALICE, BOB], quote_id, vec![ALICE], bps(500))
+            .unwrap();
+
+        assert_eq!(oracle.get_level_outcomes(3), (U256::from(1), U256::from(1), bps(10_000)));
+        assert_eq!(oracle.get_level_outcomes(7), (U256::ZERO, U256::ZERO, U256::ZERO));
+        assert_eq!(oracle.get_level_outcomes(5), (U256::from(1), U256::ZERO, U256::ZERO));
+    }
+
+    #[test]
+    fn test_losses_are_annualized() {
+        let (vm, mut oracle) = setup_reporting();
+        // 60 bps lost over 73 days is 300 bps a year
+        let short = lend(&vm, &mut oracle, &[BOB], 73);
+        oracle.record_circle_outcome(vec![BOB], short, vec![BOB], bps(60)).unwrap();
+        // Very short loans saturate at 100% a year
+        let tiny = lend(&vm, &mut oracle, &[BOB], 1);
+        oracle.record_circle_outcome(vec![BOB], tiny, vec![BOB], bps(100)).unwrap();
+
+        let (circles, _, _, _, average_loss) = oracle.get_tier_outcomes(U256::from(2));
+        assert_eq!(circles, U256::from(2));
+        assert_eq!(average_loss, bps((300 + 10_000) / 2));
+    }
+
+    #[test]
+    fn test_suggestions_need_enough_outcomes() {
+        let (vm, mut oracle) = setup_reporting();
+        for _ in 0..4 {
+            let quote_id = lend(&vm, &mut oracle, &[BOB], 365);
+            oracle
+                .record_circle_outcome(vec![BOB], quote_id, vec![BOB], bps(300))
+                .unwrap();
+        }
+        assert_eq!(oracle.suggest_tier_rates(), vec![bps(400), bps(200), U256::ZERO]);
+
+        // The fifth outcome unlocks the top tier; lower tiers never charge less than it
+        let quote_id = lend(&vm, &mut oracle, &[BOB], 365);
+        oracle
+            .record_circle_outcome(vec![BOB], quote_id, vec![BOB], bps(300))
+            .unwrap();
+        assert_eq!(oracle.suggest_tier_rates(), vec![bps(400), bps(300), bps(300)]);
+    }
+
+    #[test]
+    fn test_outcomes_follow_their_tier() {
+        let (vm, mut oracle) = setup_reporting();
+        report_top_tier_losses(&vm, &mut oracle);
+        let top_key = oracle.get_tier_key(U256::from(2)).unwrap();
+
+        // Removing the first tier moves the top tier down, its outcomes go with it
+        vm.set_sender(OWNER);
+        oracle.remove_leverage_tier(U256::ZERO).unwrap();
+        assert_eq!(oracle.get_tier_key(U256::from(1)), Ok(top_key));
+        assert_eq!(oracle.get_tier_outcomes(U256::from(1)).0, U256::from(5));
+        assert_eq!(oracle.get_tier_outcomes(U256::ZERO).0, U256::ZERO);
+        assert_eq!(oracle.suggest_tier_rates(), vec![bps(300), bps(300)]);
+
+        // A new table starts without outcomes
+        oracle
+            .replace_all_tiers(vec![(3, bps(300), bps(200)), (5, bps(500), U256::ZERO)])
+            .unwrap();
+        assert_ne!(oracle.get_tier_key(U256::from(1)), Ok(top_key));
+        assert_eq!(oracle.get_tier_outcomes(U256::from(1)).0, U256::ZERO);
+        assert_eq!(oracle.suggest_tier_rates(), vec![bps(200), U256::ZERO]);
+        assert_eq!(oracle.get_tier_key(U256::from(2)), Err(InvalidTierId {}.abi_encode()));
+    }
+
+    #[test]
+    fn test_owner_approves_recalibration() {
+        let (vm, mut oracle) = setup_reporting();
+        report_top_tier_losses(&vm, &mut oracle);
+        let suggested = oracle.suggest_tier_rates();
+
+        assert_eq!(oracle.apply_tier_recalibration(suggested.clone()), Err(unauthorized()));
+
+        vm.set_sender(OWNER);
+        assert_eq!(
+            oracle.apply_tier_recalibration(vec![bps(400), bps(200), bps(100)]),
+            Err(RecalibrationMismatch {}.abi_encode())
+        );
+        assert_eq!(oracle.get_leverage_tier(U256::from(2)).unwrap(), (5, bps(500), U256::ZERO));
+
+        let keys: Vec<_> = (0..3).map(|i| oracle.get_tier_key(U256::from(i)).unwrap()).collect();
+        let before = vm.get_emitted_logs().len();
+        oracle.apply_tier_recalibration(suggested).unwrap();
+        assert_eq!(
+            oracle.get_all_tiers(),
+            vec![(1, bps(150), bps(400)), (3, bps(300), bps(300)), (5, bps(500), bps(300))]
+        );
+        // Recalibrated tiers keep their keys and history
+        let after: Vec<_> = (0..3).map(|i| oracle.get_tier_key(U256::from(i)).unwrap()).collect();
+        assert_eq!(after, keys);
+        assert_eq!(oracle.get_tier_outcomes(U256::from(2)).0, U256::from(5));
+
+        let logs = vm.get_emitted_logs();
+        assert_eq!(logs.len(), before + 1);
+        let (topics, data) = logs.last().unwrap().clone();
+        let event = TierRatesRecalibrated::decode_raw_log(topics.iter().copied(), &data, true).unwrap();
+        assert_eq!(event.spreads, vec![bps(400), bps(300), bps(300)]);
+    }
+
+    #[test]
+    fn test_recalibration_respects_tier_limits() {
+        let (vm, mut oracle) = setup_reporting();
+        report_top_tier_losses(&vm, &mut oracle);
+        let suggested = oracle.suggest_tier_rates();
+
+        vm.set_sender(OWNER);
+        oracle.set_parameter_limits(PARAM_TIER_RATE, bps(100), U256::from(86400)).unwrap();
+        assert_eq!(
+            oracle.apply_tier_recalibration(suggested.clone()),
+            Err(ParameterStepExceeded {
+                parameter: PARAM_TIER_RATE,
+                current: U256::ZERO,
+                requested: bps(300),
+                max_step: bps(100),
+            }
+            .abi_encode())
+        );
+        assert_eq!(oracle.get_leverage_tier(U256::from(2)).unwrap(), (5, bps(500), U256::ZERO));
+
+        // A wider step only applies once the current interval has passed
+        oracle.set_parameter_limits(PARAM_TIER_RATE, bps(300), U256::from(86400)).unwrap();
+        vm.set_block_timestamp(vm.block_timestamp() + 86400);
+        oracle.apply_tier_recalibration(suggested).unwrap();
+    }
+}
@@ -0,0 +1,148 @@
+//! Owner enforcement on every RiskOracle admin entrypoint.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::SolError;
+    use risk_oracle::*;
+    use stylus_sdk::alloy_primitives::{Address, U256};
+    use stylus_sdk::testing::*;
+
+    #[test]
+    fn test_initialize_records_deployer() {
+        let (_vm, oracle) = setup_oracle();
+        assert_eq!(oracle.owner(), OWNER);
+        assert_eq!(oracle.aguayo_sbt(), AGUAYO_SBT);
+    }
+
+    #[test]
+    fn test_initialize_only_once() {
+        let (vm, mut oracle) = setup_oracle();
+        vm.set_sender(STRANGER);
+        assert_eq!(
+            oracle.initialize(STRANGER),
+            Err(AlreadyInitialized {}.abi_encode())
+        );
+        assert_eq!(oracle.owner(), OWNER);
+    }
+
+    #[test]
+    fn test_initialize_rejects_zero_sbt() {
+        let vm = TestVM::new();
+        let mut oracle = RiskOracle::from(&vm);
+        vm.set_sender(OWNER);
+        assert_eq!(
+            oracle.initialize(Address::ZERO),
+            Err(InvalidAddress {}.abi_encode())
+        );
+        assert_eq!(oracle.owner(), Address::ZERO);
+    }
+
+    #[test]
+    fn test_add_leverage_tier_requires_owner() {
+        let (vm, mut oracle) = setup_oracle();
+        vm.set_sender(STRANGER);
+        assert_eq!(
+            oracle.add_leverage_tier(7, bps(500), U256::ZERO),
+            Err(unauthorized())
+        );
+        assert_eq!(oracle.get_leverage_tier_count(), U256::from(3));
+
+        vm.set_sender(OWNER);
+        oracle.add_leverage_tier(7, bps(500), U256::ZERO).unwrap();
+        assert_eq!(oracle.get_leverage_tier_count(), U256::from(4));
+    }
+
+    #[test]
+    fn test_update_leverage_tier_requires_owner() {
+        let (vm, mut oracle) = setup_oracle();
+        vm.set_sender(STRANGER);
+        assert_eq!(
+            oracle.update_leverage_tier(U256::ZERO, 1, bps(200), bps(1100)),
+            Err(unauthorized())
+        );
+        assert_eq!(
+            oracle.get_leverage_tier(U256::ZERO).unwrap(),
+            (1, bps(150), bps(400))
+        );
+
+        vm.set_sender(OWNER);
+        oracle
+            .update_leverage_tier(U256::ZERO, 1, bps(200), bps(1100))
+            .unwrap();
+        assert_eq!(
+            oracle.get_leverage_tier(U256::ZERO).unwrap(),
+            (1, bps(200), bps(1100))
+        );
+    }
+
+    #[test]
+    fn test_set_min_level_for_credit_requires_owner() {
+        let (vm, mut oracle) = setup_oracle();
+        vm.set_sender(STRANGER);
+        assert_eq!(oracle.set_min_level_for_credit(4), Err(unauthorized()));
+        assert_eq!(oracle.min_level_for_credit(), 1);
+
+        vm.set_sender(OWNER);
+        oracle.set_min_level_for_credit(4).unwrap();
+        assert_eq!(oracle.min_level_for_credit(), 4);
+    }
+
+    #[test]
+    fn test_set_max_leverage_multiplier_requires_owner() {
+        let (vm, mut oracle) = setup_oracle();
+        vm.set_sender(STRANGER);
+        assert_eq!(
+            oracle.set_max_leverage_multiplier(bps(1000)),
+            Err(unauthorized())
+        );
+        assert_eq!(oracle.max_leverage_multiplier(), bps(500));
+
+        vm.set_sender(OWNER);
+        oracle.set_max_leverage_multiplier(bps(1000)).unwrap();
+        assert_eq!(oracle.max_leverage_multiplier(), bps(1000));
+    }
+
+    #[test]
+    fn test_set_base_interest_rate_requires_owner() {
+        let (vm, mut oracle) = setup_oracle();
+        vm.set_sender(STRANGER);
+        assert_eq!(oracle.set_base_interest_rate(bps(1)), Err(unauthorized()));
+        assert_eq!(oracle.base_interest_rate_bps(), bps(800));
+
+        vm.set_sender(OWNER);
+        oracle.set_base_interest_rate(bps(900)).unwrap();
+        assert_eq!(oracle.base_interest_rate_bps(), bps(900));
+    }
+
+    #[test]
+    fn test_set_risk_premium_requires_owner() {
+        let (vm, mut oracle) = setup_oracle();
+        vm.set_sender(STRANGER);
+        assert_eq!(oracle.set_risk_premium(U256::ZERO), Err(unauthorized()));
+        assert_eq!(oracle.risk_premium_per_stain_bps(), bps(200));
+
+        vm.set_sender(OWNER);
+        oracle.set_risk_premium(bps(300)).unwrap();
+        assert_eq!(oracle.risk_premium_per_stain_bps(), bps(300));
+    }
+
+    #[test]
+    fn test_transfer_ownership_requires_owner() {
+        let (vm, mut oracle) = setup_oracle();
+        vm.set_sender(STRANGER);
+        assert_eq!(oracle.transfer_ownership(STRANGER), Err(unauthorized()));
+        assert_eq!(oracle.owner(), OWNER);
+
+        vm.set_sender(OWNER);
+        oracle.transfer_ownership(STRANGER).unwrap();
+        assert_eq!(oracle.owner(), STRANGER);
+
+        // Previous owner loses access, new owner gains it
+        assert_eq!(oracle.set_risk_premium(bps(300)), Err(unauthorized()));
+        vm.set_sender(STRANGER);
+        oracle.set_risk_premium(bps(300)).unwrap();
+    }
+}
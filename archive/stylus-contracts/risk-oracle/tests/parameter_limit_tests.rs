@@ -0,0 +1,279 @@
+//! Maximum step sizes and minimum intervals for owner-set pricing parameters.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::{SolError, SolEvent};
+    use risk_oracle::*;
+    use stylus_sdk::alloy_primitives::U256;
+    use stylus_sdk::testing::*;
+
+    const START: u64 = 1_000_000;
+    const HOUR: u64 = 3600;
+    const DAY: u64 = 86400;
+
+    fn setup_limits() -> (TestVM, RiskOracle) {
+        let (vm, mut oracle) = setup_oracle();
+        vm.set_block_timestamp(START);
+        oracle.set_parameter_limits(PARAM_MAX_LEVERAGE, bps(100), U256::from(DAY)).unwrap();
+        oracle.set_parameter_limits(PARAM_BASE_RATE, bps(200), U256::from(DAY)).unwrap();
+        oracle.set_parameter_limits(PARAM_RISK_PREMIUM, bps(50), U256::from(HOUR)).unwrap();
+        oracle.set_parameter_limits(PARAM_TIER_MULTIPLIER, bps(100), U256::from(DAY)).unwrap();
+        oracle.set_parameter_limits(PARAM_TIER_RATE, bps(100), U256::from(DAY)).unwrap();
+        (vm, oracle)
+    }
+
+    fn step_exceeded(parameter: u8, current: u64, requested: u64, max_step: u64) -> Vec<u8> {
+        ParameterStepExceeded {
+            parameter,
+            current: bps(current),
+            requested: bps(requested),
+            max_step: bps(max_step),
+        }
+        .abi_encode()
+    }
+
+    fn too_soon(parameter: u8, next_change_at: u64) -> Vec<u8> {
+        ParameterChangeTooSoon { parameter, next_change_at: U256::from(next_change_at) }.abi_encode()
+    }
+
+    #[test]
+    fn test_unlimited_by_default() {
+        let (_vm, mut oracle) = setup_oracle();
+        assert_eq!(oracle.get_parameter_limits(PARAM_MAX_LEVERAGE), Ok((U256::ZERO, U256::ZERO)));
+
+        oracle.set_max_leverage_multiplier(bps(2000)).unwrap();
+        oracle.set_max_leverage_multiplier(bps(500)).unwrap();
+        assert_eq!(oracle.max_leverage_multiplier(), bps(500));
+    }
+
+    #[test]
+    fn test_steps_are_bounded() {
+        let (vm, mut oracle) = setup_limits();
+
+        // 500 -> 2000 in one call is the jump the limits exist to stop
+        assert_eq!(
+            oracle.set_max_leverage_multiplier(bps(2000)),
+            Err(step_exceeded(PARAM_MAX_LEVERAGE, 500, 2000, 100))
+        );
+        assert_eq!(
+            oracle.set_base_interest_rate(bps(500)),
+            Err(step_exceeded(PARAM_BASE_RATE, 800, 500, 200))
+        );
+        assert_eq!(
+            oracle.set_risk_premium(bps(251)),
+            Err(step_exceeded(PARAM_RISK_PREMIUM, 200, 251, 50))
+        );
+
+        vm.set_block_timestamp(START + DAY);
+        oracle.set_max_leverage_multiplier(bps(600)).unwrap();
+        oracle.set_base_interest_rate(bps(600)).unwrap();
+        oracle.set_risk_premium(bps(150)).unwrap();
+        assert_eq!(oracle.max_leverage_multiplier(), bps(600));
+        assert_eq!(oracle.base_interest_rate_bps(), bps(600));
+        assert_eq!(oracle.risk_premium_per_stain_bps(), bps(150));
+    }
+
+    #[test]
+    fn test_minimum_interval_between_changes() {
+        let (vm, mut oracle) = setup_limits();
+
+        // The first change is free, the next one waits out the interval
+        oracle.set_max_leverage_multiplier(bps(550)).unwrap();
+        vm.set_block_timestamp(START + DAY - 1);
+        assert_eq!(
+            oracle.set_max_leverage_multiplier(bps(600)),
+            Err(too_soon(PARAM_MAX_LEVERAGE, START + DAY))
+        );
+        // Other parameters keep their own clock
+        oracle.set_base_interest_rate(bps(900)).unwrap();
+        oracle.set_risk_premium(bps(250)).unwrap();
+        assert_eq!(
+            oracle.set_risk_premium(bps(300)),
+            Err(too_soon(PARAM_RISK_PREMIUM, START + DAY - 1 + HOUR))
+        );
+
+        vm.set_block_timestamp(START + DAY);
+        oracle.set_max_leverage_multiplier(bps(600)).unwrap();
+    }
+
+    #[test]
+    fn test_tier_updates_are_limited_per_tier() {
+        let (vm, mut oracle) = setup_limits();
+
+        // Default tier 1 is (3, 300, 200)
+        assert_eq!(
+            oracle.update_leverage_tier(U256::from(1), 3, bps(450), bps(200)),
+            Err(step_exceeded(PARAM_TIER_MULTIPLIER, 300, 450, 100))
+        );
+        assert_eq!(
+            oracle.update_leverage_tier(U256::from(1), 3, bps(300), bps(350)),
+            Err(step_exceeded(PARAM_TIER_RATE, 200, 350, 100))
+        );
+
+        oracle.update_leverage_tier(U256::from(1), 3, bps(350), bps(150)).unwrap();
+        assert_eq!(oracle.get_leverage_tier(U256::from(1)), Ok((3, bps(350), bps(150))));
+        assert_eq!(
+            oracle.update_leverage_tier(U256::from(1), 3, bps(350), bps(100)),
+            Err(too_soon(PARAM_TIER_RATE, START + DAY))
+        );
+
+        // Another tier keeps its own clock
+        oracle.update_leverage_tier(U256::ZERO, 1, bps(200), bps(400)).unwrap();
+        // Lowering a tier's minimum level reprices the levels it takes over
+        assert_eq!(
+            oracle.update_leverage_tier(U256::from(1), 2, bps(350), bps(150)),
+            Err(step_exceeded(PARAM_TIER_MULTIPLIER, 200, 350, 100))
+        );
+
+        vm.set_block_timestamp(START + DAY);
+        oracle.update_leverage_tier(U256::from(1), 3, bps(350), bps(100)).unwrap();
+    }
+
+    #[test]
+    fn test_every_tier_path_is_limited() {
+        let (_vm, mut oracle) = setup_limits();
+        let defaults = oracle.get_all_tiers();
+
+        // Dropping tier 1, directly or from a new table, hands its levels to tier 0
+        assert_eq!(
+            oracle.remove_leverage_tier(U256::from(1)),
+            Err(step_exceeded(PARAM_TIER_MULTIPLIER, 300, 150, 100))
+        );
+        assert_eq!(
+            oracle.replace_all_tiers(vec![(1, bps(150), bps(400)), (5, bps(500), U256::ZERO)]),
+            Err(step_exceeded(PARAM_TIER_MULTIPLIER, 300, 150, 100))
+        );
+        assert_eq!(oracle.get_all_tiers(), defaults);
+        // The same terms under new keys are not a change
+        oracle.replace_all_tiers(defaults.clone()).unwrap();
+
+        // A new top tier is a jump for the levels it covers
+        oracle.set_max_leverage_multiplier(bps(600)).unwrap();
+        oracle.set_parameter_limits(PARAM_TIER_MULTIPLIER, bps(50), U256::from(DAY)).unwrap();
+        assert_eq!(
+            oracle.add_leverage_tier(7, bps(600), U256::ZERO),
+            Err(step_exceeded(PARAM_TIER_MULTIPLIER, 500, 600, 50))
+        );
+        oracle.add_leverage_tier(7, bps(550), U256::ZERO).unwrap();
+
+        // Removing it right away is within the step but not the interval
+        assert_eq!(
+            oracle.remove_leverage_tier(U256::from(3)),
+            Err(too_soon(PARAM_TIER_MULTIPLIER, START + DAY))
+        );
+    }
+
+    #[test]
+    fn test_relaxed_limits_wait_for_the_interval() {
+        let (vm, mut oracle) = setup_limits();
+
+        // A compromised owner cannot lift the limits and jump in the same block
+        oracle.set_parameter_limits(PARAM_MAX_LEVERAGE, U256::ZERO, U256::ZERO).unwrap();
+        assert_eq!(oracle.get_parameter_limits(PARAM_MAX_LEVERAGE), Ok((bps(100), U256::from(DAY))));
+        assert_eq!(
+            oracle.get_pending_parameter_limits(PARAM_MAX_LEVERAGE),
+            Ok((U256::ZERO, U256::ZERO, U256::from(START + DAY)))
+        );
+        assert_eq!(
+            oracle.set_max_leverage_multiplier(bps(2000)),
+            Err(step_exceeded(PARAM_MAX_LEVERAGE, 500, 2000, 100))
+        );
+
+        vm.set_block_timestamp(START + DAY - 1);
+        assert_eq!(
+            oracle.set_max_leverage_multiplier(bps(2000)),
+            Err(step_exceeded(PARAM_MAX_LEVERAGE, 500, 2000, 100))
+        );
+
+        vm.set_block_timestamp(START + DAY);
+        assert_eq!(oracle.get_parameter_limits(PARAM_MAX_LEVERAGE), Ok((U256::ZERO, U256::ZERO)));
+        assert_eq!(
+            oracle.get_pending_parameter_limits(PARAM_MAX_LEVERAGE),
+            Ok((U256::ZERO, U256::ZERO, U256::ZERO))
+        );
+        oracle.set_max_leverage_multiplier(bps(2000)).unwrap();
+    }
+
+    #[test]
+    fn test_tightening_applies_at_once() {
+        let (vm, mut oracle) = setup_limits();
+        oracle.set_parameter_limits(PARAM_BASE_RATE, U256::ZERO, U256::ZERO).unwrap();
+
+        // Tightening takes effect immediately and drops the pending relaxation
+        oracle.set_parameter_limits(PARAM_BASE_RATE, bps(50), U256::from(2 * DAY)).unwrap();
+        assert_eq!(oracle.get_parameter_limits(PARAM_BASE_RATE), Ok((bps(50), U256::from(2 * DAY))));
+        assert_eq!(
+            oracle.set_base_interest_rate(bps(900)),
+            Err(step_exceeded(PARAM_BASE_RATE, 800, 900, 50))
+        );
+
+        vm.set_block_timestamp(START + DAY);
+        assert_eq!(oracle.get_parameter_limits(PARAM_BASE_RATE), Ok((bps(50), U256::from(2 * DAY))));
+
+    }
+
+    #[test]
+    fn test_step_limits_need_an_interval() {
+        let (vm, mut oracle) = setup_limits();
+
+        // A step with no interval could be walked in one block of repeated calls
+        assert_eq!(
+            oracle.set_parameter_limits(PARAM_RISK_PREMIUM, bps(50), U256::ZERO),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.set_parameter_limits(PARAM_RISK_PREMIUM, bps(100), U256::ZERO),
+            Err(InvalidParameter {}.abi_encode())
+        );
+
+        // A short interval still holds back lifting the step
+        oracle.set_parameter_limits(PARAM_RISK_PREMIUM, U256::ZERO, U256::ZERO).unwrap();
+        assert_eq!(
+            oracle.set_risk_premium(bps(1000)),
+            Err(step_exceeded(PARAM_RISK_PREMIUM, 200, 1000, 50))
+        );
+        vm.set_block_timestamp(START + HOUR);
+        assert_eq!(oracle.get_parameter_limits(PARAM_RISK_PREMIUM), Ok((U256::ZERO, U256::ZERO)));
+        oracle.set_risk_premium(bps(1000)).unwrap();
+        oracle.set_risk_premium(bps(200)).unwrap();
+    }
+
+    #[test]
+    fn test_set_parameter_limits() {
+        let (vm, mut oracle) = setup_oracle();
+        vm.set_block_timestamp(START);
+        assert_eq!(
+            oracle.set_parameter_limits(PARAM_TIER_RATE + 1, bps(1), U256::from(DAY)),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(oracle.get_parameter_limits(5), Err(InvalidParameter {}.abi_encode()));
+        assert_eq!(oracle.get_pending_parameter_limits(5), Err(InvalidParameter {}.abi_encode()));
+
+        let before = vm.get_emitted_logs().len();
+        oracle.set_parameter_limits(PARAM_BASE_RATE, bps(200), U256::from(DAY)).unwrap();
+        assert_eq!(oracle.get_parameter_limits(PARAM_BASE_RATE), Ok((bps(200), U256::from(DAY))));
+
+        let logs = vm.get_emitted_logs();
+        assert_eq!(logs.len(), before + 1);
+        let (topics, data) = logs[before].clone();
+        let event = ParameterLimitsUpdated::decode_raw_log(topics.iter().copied(), &data, true).unwrap();
+        assert_eq!(event.parameter, PARAM_BASE_RATE);
+        assert_eq!((event.max_step, event.min_interval), (bps(200), U256::from(DAY)));
+
+        oracle.set_parameter_limits(PARAM_BASE_RATE, bps(300), U256::from(DAY)).unwrap();
+        let (topics, data) = vm.get_emitted_logs().last().unwrap().clone();
+        let event = ParameterLimitsScheduled::decode_raw_log(topics.iter().copied(), &data, true).unwrap();
+        assert_eq!(event.parameter, PARAM_BASE_RATE);
+        assert_eq!((event.max_step, event.min_interval), (bps(300), U256::from(DAY)));
+        assert_eq!(event.effective_at, U256::from(START + DAY));
+
+        vm.set_sender(STRANGER);
+        assert_eq!(
+            oracle.set_parameter_limits(PARAM_BASE_RATE, U256::ZERO, U256::ZERO),
+            Err(unauthorized())
+        );
+    }
+}
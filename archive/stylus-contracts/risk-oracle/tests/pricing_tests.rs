@@ -0,0 +1,87 @@
+//! Oracle-owned eligibility floor and base + spread + premium pricing.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use stylus_sdk::alloy_primitives::U256;
+
+
+    #[test]
+    fn test_min_level_gates_credit_eligibility() {
+        let (vm, mut oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(3, 0), true);
+        mock_member(&vm, BOB, 2, metadata(5, 0), true);
+
+        assert_eq!(oracle.is_member_eligible(ALICE, true), Ok(true));
+        assert_eq!(oracle.are_all_members_eligible(vec![ALICE, BOB]), Ok(true));
+
+        oracle.set_min_level_for_credit(4).unwrap();
+        assert_eq!(oracle.is_member_eligible(ALICE, true), Ok(false));
+        assert_eq!(oracle.is_member_eligible(BOB, true), Ok(true));
+        assert_eq!(oracle.are_all_members_eligible(vec![ALICE, BOB]), Ok(false));
+        assert_eq!(oracle.are_all_members_eligible(vec![BOB]), Ok(true));
+
+        // Savings mode only needs an Aguayo
+        assert_eq!(oracle.is_member_eligible(ALICE, false), Ok(true));
+    }
+
+    #[test]
+    fn test_sbt_eligibility_still_required() {
+        let (vm, mut oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(3, 1), false);
+        oracle.set_min_level_for_credit(0).unwrap();
+
+        assert_eq!(oracle.is_member_eligible(ALICE, true), Ok(false));
+        assert_eq!(oracle.are_all_members_eligible(vec![ALICE]), Ok(false));
+    }
+
+    #[test]
+    fn test_base_rate_moves_final_rate() {
+        let (vm, mut oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(3, 0), true);
+
+        // Top tier has no spread, so the rate is the base rate
+        assert_eq!(oracle.get_leverage_level(vec![ALICE]), Ok((bps(500), bps(800))));
+
+        oracle.set_base_interest_rate(bps(900)).unwrap();
+        assert_eq!(oracle.get_leverage_level(vec![ALICE]), Ok((bps(500), bps(900))));
+    }
+
+    #[test]
+    fn test_tier_spread_adds_to_base() {
+        let (vm, mut oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(3, 0), true);
+
+        oracle
+            .update_leverage_tier(U256::from(2), 5, bps(500), bps(150))
+            .unwrap();
+        assert_eq!(oracle.get_leverage_level(vec![ALICE]), Ok((bps(500), bps(950))));
+    }
+
+    #[test]
+    fn test_stain_premium_adds_to_rate() {
+        let (vm, mut oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(5, 1), true);
+
+        // Score 4950 sits near the top tier: 495 multiplier and a 5 bps spread,
+        // then the stain takes 10% of leverage and adds the 200 bps premium
+        assert_eq!(oracle.get_leverage_level(vec![ALICE]), Ok((bps(446), bps(1005))));
+
+        oracle.set_risk_premium(bps(500)).unwrap();
+        assert_eq!(oracle.get_leverage_level(vec![ALICE]), Ok((bps(446), bps(1305))));
+    }
+
+    #[test]
+    fn test_final_rate_capped() {
+        let (vm, mut oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(5, 1), true);
+
+        oracle.set_base_interest_rate(bps(10_000)).unwrap();
+        assert_eq!(
+            oracle.get_leverage_level(vec![ALICE]).map(|(_, rate)| rate),
+            Ok(bps(10_000))
+        );
+    }
+}
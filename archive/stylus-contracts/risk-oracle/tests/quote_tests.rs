@@ -0,0 +1,166 @@
+//! Stored, expiring rate quotes consumed by the vault or factory.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::{SolError, SolEvent};
+    use risk_oracle::*;
+    use stylus_sdk::alloy_primitives::{Address, B256, U256};
+    use stylus_sdk::testing::*;
+
+    const START: u64 = 1_000_000;
+
+    fn setup_quotes() -> (TestVM, RiskOracle) {
+        let (vm, mut oracle) = setup_oracle();
+        vm.set_block_timestamp(START);
+        oracle.set_quote_consumer(VAULT, true).unwrap();
+        setup_members(&vm);
+        (vm, oracle)
+    }
+
+    #[test]
+    fn test_quote_is_stored() {
+        let (vm, mut oracle) = setup_quotes();
+        vm.set_sender(ALICE);
+        let before = vm.get_emitted_logs().len();
+
+        let quote_id = oracle.quote(vec![ALICE, BOB], bps(1000), U256::from(30)).unwrap();
+        assert_eq!(quote_id, U256::from(1));
+        assert_eq!(oracle.quote(vec![ALICE], bps(1000), U256::from(30)), Ok(U256::from(2)));
+
+        let member_hash = oracle.get_member_set_hash(vec![ALICE, BOB]);
+        let expiry = U256::from(START + 3600);
+        assert_eq!(
+            oracle.get_quote(quote_id),
+            Ok((member_hash, bps(1000), U256::from(30), bps(475), bps(800), expiry, false))
+        );
+
+        let logs = vm.get_emitted_logs();
+        assert_eq!(logs.len(), before + 2);
+        let (topics, data) = logs[before].clone();
+        let event = QuoteIssued::decode_raw_log(topics.iter().copied(), &data, true).unwrap();
+        assert_eq!(topics[1], B256::from(quote_id));
+        assert_eq!(topics[2], ALICE.into_word());
+        assert_eq!(event.member_hash, member_hash);
+        assert_eq!((event.multiplier, event.interest_rate, event.expiry), (bps(475), bps(800), expiry));
+    }
+
+    #[test]
+    fn test_member_hash_ignores_order() {
+        let (_vm, oracle) = setup_quotes();
+        assert_eq!(
+            oracle.get_member_set_hash(vec![ALICE, BOB, CAROL]),
+            oracle.get_member_set_hash(vec![CAROL, ALICE, BOB])
+        );
+        assert_ne!(
+            oracle.get_member_set_hash(vec![ALICE, BOB]),
+            oracle.get_member_set_hash(vec![ALICE, CAROL])
+        );
+    }
+
+    #[test]
+    fn test_consume_locks_in_terms() {
+        let (vm, mut oracle) = setup_quotes();
+        let quote_id = oracle.quote(vec![ALICE, BOB], bps(1000), U256::from(30)).unwrap();
+
+        // Tier changes after the quote do not move the quoted terms
+        oracle.set_base_interest_rate(bps(1500)).unwrap();
+        oracle.update_leverage_tier(U256::from(2), 5, bps(300), U256::ZERO).unwrap();
+        assert_eq!(oracle.get_leverage_level(vec![ALICE, BOB]), Ok((bps(285), bps(1500))));
+
+        vm.set_sender(VAULT);
+        let before = vm.get_emitted_logs().len();
+        assert_eq!(
+            oracle.consume_quote(quote_id, vec![BOB, ALICE]),
+            Ok((bps(475), bps(800), bps(1000), U256::from(30)))
+        );
+        assert!(oracle.get_quote(quote_id).unwrap().6);
+
+        let logs = vm.get_emitted_logs();
+        assert_eq!(logs.len(), before + 1);
+        let (topics, _) = logs[before].clone();
+        assert_eq!(topics[0], QuoteConsumed::SIGNATURE_HASH);
+        assert_eq!(topics[1], B256::from(quote_id));
+        assert_eq!(topics[2], VAULT.into_word());
+    }
+
+    #[test]
+    fn test_rejected_quotes() {
+        let (vm, mut oracle) = setup_quotes();
+        let quote_id = oracle.quote(vec![ALICE, BOB], bps(1000), U256::from(30)).unwrap();
+
+        // Only authorized consumers
+        vm.set_sender(STRANGER);
+        assert_eq!(oracle.consume_quote(quote_id, vec![ALICE, BOB]), Err(unauthorized()));
+
+        vm.set_sender(VAULT);
+        for unknown in [U256::ZERO, U256::from(2)] {
+            assert_eq!(
+                oracle.consume_quote(unknown, vec![ALICE, BOB]),
+                Err(QuoteNotFound { quote_id: unknown }.abi_encode())
+            );
+        }
+        assert_eq!(
+            oracle.consume_quote(quote_id, vec![ALICE, CAROL]),
+            Err(QuoteMembersMismatch { quote_id }.abi_encode())
+        );
+        assert_eq!(
+            oracle.consume_quote(quote_id, vec![ALICE, BOB, CAROL]),
+            Err(QuoteMembersMismatch { quote_id }.abi_encode())
+        );
+
+        // Valid until the expiry second, rejected after it
+        let expiry = U256::from(START + 3600);
+        vm.set_block_timestamp(START + 3601);
+        assert_eq!(
+            oracle.consume_quote(quote_id, vec![ALICE, BOB]),
+            Err(QuoteExpired { quote_id, expiry }.abi_encode())
+        );
+        vm.set_block_timestamp(START + 3600);
+        oracle.consume_quote(quote_id, vec![ALICE, BOB]).unwrap();
+
+        assert_eq!(
+            oracle.consume_quote(quote_id, vec![ALICE, BOB]),
+            Err(QuoteAlreadyUsed { quote_id }.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_quote_validation() {
+        let (vm, mut oracle) = setup_quotes();
+        assert_eq!(
+            oracle.quote(vec![ALICE], U256::ZERO, U256::from(30)),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.quote(vec![ALICE], bps(1000), U256::ZERO),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.quote(vec![], bps(1000), U256::from(30)),
+            Err(EmptyMemberList {}.abi_encode())
+        );
+
+        assert_eq!(oracle.set_quote_validity(U256::ZERO), Err(InvalidParameter {}.abi_encode()));
+        assert_eq!(
+            oracle.set_quote_validity(U256::from(7 * 86400 + 1)),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.set_quote_consumer(Address::ZERO, true),
+            Err(InvalidAddress {}.abi_encode())
+        );
+
+        oracle.set_quote_validity(U256::from(60)).unwrap();
+        assert_eq!(oracle.quote_validity(), U256::from(60));
+        let quote_id = oracle.quote(vec![ALICE], bps(1000), U256::from(30)).unwrap();
+        assert_eq!(oracle.get_quote(quote_id).unwrap().5, U256::from(START + 60));
+
+        vm.set_sender(STRANGER);
+        assert_eq!(oracle.set_quote_validity(U256::from(60)), Err(unauthorized()));
+        assert_eq!(oracle.set_quote_consumer(STRANGER, true), Err(unauthorized()));
+        assert!(!oracle.is_quote_consumer(STRANGER));
+    }
+}
@@ -0,0 +1,255 @@
+//! Reputation provider registry and blended member scores.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::{SolCall, SolError, SolEvent, SolValue};
+    use risk_oracle::*;
+    use stylus_sdk::alloy_primitives::{Address, B256, U256};
+    use stylus_sdk::testing::*;
+
+    const ATTESTATIONS: Address = Address::new([0x30; 20]);
+    const STAKING: Address = Address::new([0x31; 20]);
+    const OTHER_SBT: Address = Address::new([0x32; 20]);
+
+    fn mock_attestation(vm: &TestVM, subject: Address, score: u64) {
+        vm.mock_static_call(
+            ATTESTATIONS,
+            IReputationAttestation::scoreOfCall { subject }.abi_encode(),
+            Ok(U256::from(score).abi_encode()),
+        );
+    }
+
+    fn mock_stake(vm: &TestVM, account: Address, balance: u64) {
+        vm.mock_static_call(
+            STAKING,
+            IKuyayStaking::stakedBalanceOfCall { account }.abi_encode(),
+            Ok(U256::from(balance).abi_encode()),
+        );
+    }
+
+    #[test]
+    fn test_aguayo_is_default_provider() {
+        let (vm, oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(3, 0), true);
+
+        assert_eq!(oracle.get_reputation_provider_count(), U256::from(1));
+        assert_eq!(
+            oracle.get_reputation_provider(U256::ZERO),
+            Ok((AGUAYO_SBT, PROVIDER_AGUAYO_SBT, bps(10_000), U256::ZERO))
+        );
+        assert_eq!(oracle.get_member_reputation(ALICE), Ok((bps(5100), bps(10_000))));
+        assert_eq!(
+            oracle.get_reputation_provider(U256::from(1)),
+            Err(InvalidProviderId {}.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_scores_blend_by_weight() {
+        let (vm, mut oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(3, 0), true);
+        mock_attestation(&vm, ALICE, 9100);
+        mock_stake(&vm, ALICE, 500);
+
+        oracle
+            .add_reputation_provider(ATTESTATIONS, PROVIDER_ATTESTATION, bps(10_000), U256::ZERO)
+            .unwrap();
+        assert_eq!(oracle.get_member_reputation(ALICE), Ok((bps(7100), bps(10_000))));
+
+        // Half of the full-score stake is worth 5000 at half weight
+        oracle
+            .add_reputation_provider(STAKING, PROVIDER_STAKING, bps(5000), U256::from(1000))
+            .unwrap();
+        assert_eq!(oracle.get_member_score(ALICE), Ok(bps(6680)));
+
+        // Attestations above the scale saturate
+        mock_attestation(&vm, ALICE, 50_000);
+        oracle.remove_reputation_provider(U256::from(2)).unwrap();
+        assert_eq!(oracle.get_member_score(ALICE), Ok(bps(7550)));
+    }
+
+    #[test]
+    fn test_failing_provider_reduces_confidence() {
+        let (vm, mut oracle) = setup_oracle();
+        mock_member(&vm, BOB, 2, metadata(5, 0), true);
+        oracle
+            .add_reputation_provider(ATTESTATIONS, PROVIDER_ATTESTATION, bps(10_000), U256::ZERO)
+            .unwrap();
+
+        // BOB has no attestation mocked, so that provider reverts and is skipped:
+        // the score comes from the SBT alone, only the confidence drops
+        assert_eq!(oracle.get_member_reputation(BOB), Ok((bps(6500), bps(5000))));
+        assert_eq!(oracle.is_member_eligible(BOB, true), Ok(true));
+
+        let report = oracle.get_risk_report(vec![BOB]).unwrap();
+        assert_eq!(report.memberScores, vec![bps(6500)]);
+        assert_eq!(report.memberConfidence, vec![bps(5000)]);
+        assert_eq!(
+            (report.multiplier, report.interestRateBps),
+            oracle.get_leverage_level(vec![BOB]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_failing_sbt_falls_back_to_other_providers() {
+        let (vm, mut oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(5, 0), true);
+        mock_attestation(&vm, BOB, 7000);
+        oracle
+            .add_reputation_provider(ATTESTATIONS, PROVIDER_ATTESTATION, bps(10_000), U256::ZERO)
+            .unwrap();
+
+        // The SBT reverts for BOB; the attestation alone prices him as a level-7 newcomer
+        vm.mock_static_call(
+            AGUAYO_SBT,
+            IAguayoSBT::userToAguayoCall { user: BOB }.abi_encode(),
+            Err(b"sbt down".to_vec()),
+        );
+        assert_eq!(oracle.get_member_reputation(BOB), Ok((bps(7000), bps(5000))));
+        let report = oracle.get_risk_report(vec![BOB]).unwrap();
+        assert_eq!(report.memberLevels, vec![7]);
+        assert_eq!(report.newMemberShareBps, bps(10_000));
+        assert_eq!(oracle.get_leverage_level(vec![BOB]), Ok((bps(400), bps(800))));
+        // Linear draw weights 15:17
+        assert_eq!(oracle.get_weighted_probabilities(vec![ALICE, BOB]), Ok(vec![bps(4688), bps(5312)]));
+
+        // A failed SBT lookup does not disqualify him: the attested level decides
+        assert_eq!(oracle.is_member_eligible(BOB, true), Ok(true));
+        assert_eq!(oracle.are_all_members_eligible(vec![ALICE, BOB]), Ok(true));
+        assert!(oracle.evaluate_group(vec![ALICE, BOB]).unwrap().0);
+        oracle.set_min_level_for_credit(8).unwrap();
+        assert_eq!(oracle.is_member_eligible(BOB, true), Ok(false));
+
+        // With no provider answering there is nothing to price
+        let nobody = Address::new([0x99; 20]);
+        assert_eq!(
+            oracle.get_leverage_level(vec![nobody]),
+            Err(AguayoCallFailed { member: nobody }.abi_encode())
+        );
+        assert_eq!(
+            oracle.get_member_reputation(nobody),
+            Err(AguayoCallFailed { member: nobody }.abi_encode())
+        );
+        assert_eq!(
+            oracle.is_member_eligible(nobody, true),
+            Err(AguayoCallFailed { member: nobody }.abi_encode())
+        );
+    }
+
+    #[test]
+    fn test_secondary_aguayo_provider() {
+        let (vm, mut oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(3, 0), true);
+        oracle
+            .add_reputation_provider(OTHER_SBT, PROVIDER_AGUAYO_SBT, bps(10_000), U256::ZERO)
+            .unwrap();
+
+        // No token on the second SBT is an answer of zero, not a failure
+        vm.mock_static_call(
+            OTHER_SBT,
+            IAguayoSBT::userToAguayoCall { user: ALICE }.abi_encode(),
+            Ok(U256::ZERO.abi_encode()),
+        );
+        assert_eq!(oracle.get_member_reputation(ALICE), Ok((bps(2550), bps(10_000))));
+
+        vm.mock_static_call(
+            OTHER_SBT,
+            IAguayoSBT::userToAguayoCall { user: ALICE }.abi_encode(),
+            Ok(U256::from(9).abi_encode()),
+        );
+        vm.mock_static_call(
+            OTHER_SBT,
+            IAguayoSBT::getAguayoMetadataCall { tokenId: U256::from(9) }.abi_encode(),
+            Ok(metadata(10, 0).abi_encode()),
+        );
+        assert_eq!(oracle.get_member_reputation(ALICE), Ok((bps(7550), bps(10_000))));
+    }
+
+    #[test]
+    fn test_provider_validation() {
+        let (vm, mut oracle) = setup_oracle();
+        let invalid = Err(InvalidParameter {}.abi_encode());
+
+        assert_eq!(
+            oracle.add_reputation_provider(Address::ZERO, PROVIDER_ATTESTATION, bps(1), U256::ZERO),
+            Err(InvalidAddress {}.abi_encode())
+        );
+        assert_eq!(oracle.add_reputation_provider(ATTESTATIONS, 3, bps(1), U256::ZERO), invalid);
+        assert_eq!(
+            oracle.add_reputation_provider(ATTESTATIONS, PROVIDER_ATTESTATION, U256::ZERO, U256::ZERO),
+            invalid
+        );
+        assert_eq!(
+            oracle.add_reputation_provider(ATTESTATIONS, PROVIDER_ATTESTATION, bps(10_001), U256::ZERO),
+            invalid
+        );
+        assert_eq!(
+            oracle.add_reputation_provider(STAKING, PROVIDER_STAKING, bps(1), U256::ZERO),
+            invalid
+        );
+        assert_eq!(
+            oracle.update_reputation_provider(U256::from(1), bps(1), U256::ZERO),
+            Err(InvalidProviderId {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.remove_reputation_provider(U256::ZERO),
+            Err(NoProvidersConfigured {}.abi_encode())
+        );
+
+        vm.set_sender(STRANGER);
+        assert_eq!(
+            oracle.add_reputation_provider(ATTESTATIONS, PROVIDER_ATTESTATION, bps(1), U256::ZERO),
+            Err(unauthorized())
+        );
+        assert_eq!(
+            oracle.update_reputation_provider(U256::ZERO, bps(1), U256::ZERO),
+            Err(unauthorized())
+        );
+        assert_eq!(oracle.remove_reputation_provider(U256::ZERO), Err(unauthorized()));
+        assert_eq!(oracle.get_reputation_provider_count(), U256::from(1));
+    }
+
+    #[test]
+    fn test_provider_management_events() {
+        let (vm, mut oracle) = setup_oracle();
+        let before = vm.get_emitted_logs().len();
+
+        oracle
+            .add_reputation_provider(STAKING, PROVIDER_STAKING, bps(2000), U256::from(1000))
+            .unwrap();
+        oracle
+            .update_reputation_provider(U256::from(1), bps(3000), U256::from(500))
+            .unwrap();
+        assert_eq!(
+            oracle.get_reputation_provider(U256::from(1)),
+            Ok((STAKING, PROVIDER_STAKING, bps(3000), U256::from(500)))
+        );
+
+        // Removing the SBT shifts the staking provider down
+        oracle.remove_reputation_provider(U256::ZERO).unwrap();
+        assert_eq!(
+            oracle.get_reputation_provider(U256::ZERO),
+            Ok((STAKING, PROVIDER_STAKING, bps(3000), U256::from(500)))
+        );
+
+        let logs = vm.get_emitted_logs();
+        assert_eq!(logs.len(), before + 3);
+
+        let (topics, data) = logs[before].clone();
+        let added = ReputationProviderAdded::decode_raw_log(topics.iter().copied(), &data, true).unwrap();
+        assert_eq!(topics[1], B256::from(U256::from(1)));
+        assert_eq!(topics[2], STAKING.into_word());
+        assert_eq!((added.kind, added.weight), (PROVIDER_STAKING, bps(2000)));
+
+        let (topics, data) = logs[before + 1].clone();
+        let updated = ReputationProviderUpdated::decode_raw_log(topics.iter().copied(), &data, true).unwrap();
+        assert_eq!((updated.weight, updated.full_score_stake), (bps(3000), U256::from(500)));
+
+        let (topics, _) = logs[before + 2].clone();
+        assert_eq!(topics[0], ReputationProviderRemoved::SIGNATURE_HASH);
+        assert_eq!(topics[1], B256::ZERO);
+    }
+}
@@ -0,0 +1,99 @@
+//! Explainable risk report for a proposed circle.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::SolError;
+    use risk_oracle::*;
+    use stylus_sdk::alloy_primitives::U256;
+
+
+    #[test]
+    fn test_clean_group_report() {
+        let (vm, oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(3, 0), true);
+        mock_member(&vm, BOB, 2, metadata(5, 0), true);
+
+        let report = oracle.get_risk_report(vec![ALICE, BOB]).unwrap();
+        assert_eq!(report.tierId, U256::from(2));
+        assert_eq!(report.averageLevelX100, bps(400));
+        assert_eq!(report.minLevel, 3);
+        assert!(!report.minLevelCapped);
+        assert_eq!(report.levelVarianceX100, bps(100));
+        assert_eq!(report.newMemberShareBps, U256::ZERO);
+        // Mean 5800 pulled halfway towards 5100
+        assert_eq!(report.groupScore, bps(5450));
+        assert_eq!(report.memberLevels, vec![3, 5]);
+        assert_eq!(report.memberStained, vec![false, false]);
+        assert_eq!(report.memberScores, vec![bps(5100), bps(6500)]);
+        assert_eq!(report.tierMultiplier, bps(500));
+        assert_eq!(report.stainLeverageReduction, U256::ZERO);
+        // Variance of one level costs 5% of leverage
+        assert_eq!(report.dispersionPenaltyBps, bps(500));
+        assert_eq!(report.dispersionLeverageReduction, bps(25));
+        assert_eq!(report.multiplier, bps(475));
+        assert!(!report.leverageCapped);
+        assert_eq!(report.baseRateBps, bps(800));
+        assert_eq!(report.tierSpreadBps, U256::ZERO);
+        assert_eq!(report.stainPremiumBps, U256::ZERO);
+        assert!(!report.rateCapped);
+
+        assert_eq!(
+            (report.multiplier, report.interestRateBps),
+            oracle.get_leverage_level(vec![ALICE, BOB]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_stain_adjustments_reported() {
+        let (vm, oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(5, 1), true);
+
+        let report = oracle.get_risk_report(vec![ALICE]).unwrap();
+        assert_eq!(report.tierId, U256::from(1));
+        assert_eq!(report.memberStained, vec![true]);
+        assert_eq!(report.tierMultiplier, bps(495));
+        assert_eq!(report.stainLeverageReduction, bps(49));
+        assert_eq!(report.multiplier, bps(446));
+        assert_eq!(report.tierSpreadBps, bps(5));
+        assert_eq!(report.stainPremiumBps, bps(200));
+        assert_eq!(report.interestRateBps, bps(1005));
+    }
+
+    #[test]
+    fn test_caps_reported() {
+        let (vm, mut oracle) = setup_oracle();
+        mock_member(&vm, ALICE, 1, metadata(3, 0), true);
+        mock_member(&vm, BOB, 2, metadata(5, 1), true);
+
+        oracle.set_base_interest_rate(bps(10_000)).unwrap();
+
+        // Tiers never exceed the leverage cap, so only the rate can be capped
+        let report = oracle.get_risk_report(vec![ALICE]).unwrap();
+        assert_eq!(report.tierMultiplier, bps(500));
+        assert!(!report.leverageCapped);
+        assert_eq!(report.multiplier, bps(500));
+        assert!(!report.rateCapped);
+
+        let report = oracle.get_risk_report(vec![BOB]).unwrap();
+        assert!(report.rateCapped);
+        assert_eq!(report.interestRateBps, bps(10_000));
+    }
+
+    #[test]
+    fn test_report_errors() {
+        let (vm, oracle) = setup_oracle();
+        assert_eq!(
+            oracle.get_risk_report(vec![]).map(|r| r.multiplier),
+            Err(EmptyMemberList {}.abi_encode())
+        );
+
+        mock_member(&vm, ALICE, 0, metadata(0, 0), false);
+        assert_eq!(
+            oracle.get_risk_report(vec![ALICE]).map(|r| r.multiplier),
+            Err(MemberNotEligible { member: ALICE }.abi_encode())
+        );
+    }
+}
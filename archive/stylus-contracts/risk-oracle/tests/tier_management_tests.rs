@@ -0,0 +1,144 @@
+//! Leverage tier table management and ordering rules.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use alloy_sol_types::{SolError, SolEvent};
+    use risk_oracle::*;
+    use stylus_sdk::alloy_primitives::U256;
+
+    fn default_tiers() -> Vec<(u8, U256, U256)> {
+        vec![
+            (1, bps(150), bps(400)),
+            (3, bps(300), bps(200)),
+            (5, bps(500), U256::ZERO),
+        ]
+    }
+
+    fn violation(tier_id: u64) -> Vec<u8> {
+        TierOrderViolation { tier_id: U256::from(tier_id) }.abi_encode()
+    }
+
+    #[test]
+    fn test_get_all_tiers() {
+        let (_vm, oracle) = setup_oracle();
+        assert_eq!(oracle.get_all_tiers(), default_tiers());
+    }
+
+    #[test]
+    fn test_add_must_extend_the_top() {
+        let (_vm, mut oracle) = setup_oracle();
+        assert_eq!(oracle.add_leverage_tier(4, bps(500), U256::ZERO), Err(violation(3)));
+        assert_eq!(oracle.add_leverage_tier(6, bps(400), U256::ZERO), Err(violation(3)));
+        assert_eq!(oracle.add_leverage_tier(6, bps(500), bps(100)), Err(violation(3)));
+        assert_eq!(oracle.get_all_tiers(), default_tiers());
+    }
+
+    #[test]
+    fn test_update_keeps_neighbours_ordered() {
+        let (_vm, mut oracle) = setup_oracle();
+        assert_eq!(
+            oracle.update_leverage_tier(U256::from(1), 5, bps(300), bps(200)),
+            Err(violation(2))
+        );
+        assert_eq!(
+            oracle.update_leverage_tier(U256::from(1), 3, bps(100), bps(200)),
+            Err(violation(1))
+        );
+        assert_eq!(
+            oracle.update_leverage_tier(U256::from(1), 3, bps(300), bps(500)),
+            Err(violation(1))
+        );
+
+        oracle
+            .update_leverage_tier(U256::from(1), 4, bps(400), bps(100))
+            .unwrap();
+        assert_eq!(oracle.get_all_tiers()[1], (4, bps(400), bps(100)));
+    }
+
+    #[test]
+    fn test_remove_leverage_tier() {
+        let (vm, mut oracle) = setup_oracle();
+        assert_eq!(
+            oracle.remove_leverage_tier(U256::from(3)),
+            Err(InvalidTierId {}.abi_encode())
+        );
+
+        oracle.remove_leverage_tier(U256::from(1)).unwrap();
+        assert_eq!(
+            oracle.get_all_tiers(),
+            vec![(1, bps(150), bps(400)), (5, bps(500), U256::ZERO)]
+        );
+
+        let (topics, data) = vm.get_emitted_logs().last().unwrap().clone();
+        let event = LeverageTierRemoved::decode_raw_log(topics.iter().copied(), &data, true).unwrap();
+        assert_eq!(event.tier_id, U256::from(1));
+
+        oracle.remove_leverage_tier(U256::ZERO).unwrap();
+        assert_eq!(
+            oracle.remove_leverage_tier(U256::ZERO),
+            Err(NoTiersConfigured {}.abi_encode())
+        );
+        assert_eq!(oracle.get_leverage_tier_count(), U256::from(1));
+    }
+
+    #[test]
+    fn test_replace_all_tiers() {
+        let (vm, mut oracle) = setup_oracle();
+        let table = vec![(2, bps(200), bps(300)), (6, bps(450), bps(100))];
+        oracle.replace_all_tiers(table.clone()).unwrap();
+        assert_eq!(oracle.get_all_tiers(), table);
+        assert_eq!(oracle.get_leverage_tier_count(), U256::from(2));
+
+        let (topics, data) = vm.get_emitted_logs().last().unwrap().clone();
+        let event = LeverageTiersReplaced::decode_raw_log(topics.iter().copied(), &data, true).unwrap();
+        assert_eq!(event.tier_count, U256::from(2));
+    }
+
+    #[test]
+    fn test_replace_all_tiers_is_atomic() {
+        let (_vm, mut oracle) = setup_oracle();
+        let contradictory = vec![
+            (1, bps(150), bps(400)),
+            (3, bps(300), bps(200)),
+            (5, bps(200), U256::ZERO),
+        ];
+        assert_eq!(oracle.replace_all_tiers(contradictory), Err(violation(2)));
+        assert_eq!(
+            oracle.replace_all_tiers(vec![(1, bps(600), U256::ZERO)]),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(
+            oracle.replace_all_tiers(vec![]),
+            Err(NoTiersConfigured {}.abi_encode())
+        );
+        assert_eq!(oracle.get_all_tiers(), default_tiers());
+    }
+
+    #[test]
+    fn test_max_leverage_covers_existing_tiers() {
+        let (_vm, mut oracle) = setup_oracle();
+        assert_eq!(
+            oracle.set_max_leverage_multiplier(bps(499)),
+            Err(InvalidParameter {}.abi_encode())
+        );
+        assert_eq!(oracle.max_leverage_multiplier(), bps(500));
+
+        // Lowering the top tier first makes room for a lower cap
+        oracle
+            .update_leverage_tier(U256::from(2), 5, bps(400), U256::ZERO)
+            .unwrap();
+        oracle.set_max_leverage_multiplier(bps(400)).unwrap();
+        assert_eq!(oracle.max_leverage_multiplier(), bps(400));
+    }
+
+    #[test]
+    fn test_table_management_requires_owner() {
+        let (vm, mut oracle) = setup_oracle();
+        vm.set_sender(STRANGER);
+        assert_eq!(oracle.remove_leverage_tier(U256::ZERO), Err(unauthorized()));
+        assert_eq!(oracle.replace_all_tiers(default_tiers()), Err(unauthorized()));
+    }
+}
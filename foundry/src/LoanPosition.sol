@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.24;
+
+import "@openzeppelin/contracts/token/ERC721/ERC721.sol";
+
+interface ILoanPositionVault {
+    function onLoanPositionTransfer(address from, address to, uint256 tokenId) external;
+}
+
+/**
+ * @title LoanPosition
+ * @author Kuyay Protocol
+ * @notice ERC-721 que representa un préstamo del KuyayVault (Stylus) a un Circle
+ *
+ * MODELO:
+ * - tokenId = loan id del vault; el vault mintea cada posición a sí mismo
+ * - El vault la vende con buyLoanPosition y desde ahí los repagos van al holder
+ * - Transferencias que no inicia el vault notifican al vault para redirigir los pagos
+ */
+contract LoanPosition is ERC721 {
+
+    address public immutable vault;
+
+    error OnlyVault();
+    error InvalidAddress();
+
+    constructor(address vaultAddress) ERC721("Kuyay Loan Position", "KLOAN") {
+        if (vaultAddress == address(0)) revert InvalidAddress();
+        vault = vaultAddress;
+    }
+
+    function mint(address to, uint256 tokenId) external {
+        if (msg.sender != vault) revert OnlyVault();
+        _mint(to, tokenId);
+    }
+
+    // Hook de transferencia: el vault actualiza a quién enruta los repagos
+    // (y revierte si la posición no puede moverse)
+    function _update(address to, uint256 tokenId, address auth)
+        internal
+        virtual
+        override
+        returns (address)
+    {
+        address from = super._update(to, tokenId, auth);
+
+        if (from != address(0) && msg.sender != vault) {
+            ILoanPositionVault(vault).onLoanPositionTransfer(from, to, tokenId);
+        }
+
+        return from;
+    }
+}
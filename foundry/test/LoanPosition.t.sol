@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.24;
+
+import "forge-std/Test.sol";
+import "../src/LoanPosition.sol";
+
+contract MockPositionVault is ILoanPositionVault {
+    address public lastFrom;
+    address public lastTo;
+    uint256 public lastTokenId;
+    uint256 public hookCalls;
+    bool public rejectTransfers;
+
+    function setRejectTransfers(bool reject) external {
+        rejectTransfers = reject;
+    }
+
+    function onLoanPositionTransfer(address from, address to, uint256 tokenId) external {
+        require(!rejectTransfers, "position locked");
+        lastFrom = from;
+        lastTo = to;
+        lastTokenId = tokenId;
+        hookCalls++;
+    }
+
+    function mint(LoanPosition position, address to, uint256 tokenId) external {
+        position.mint(to, tokenId);
+    }
+
+    function transfer(LoanPosition position, address to, uint256 tokenId) external {
+        position.transferFrom(address(this), to, tokenId);
+    }
+}
+
+contract LoanPositionTest is Test {
+    MockPositionVault public vault;
+    LoanPosition public position;
+
+    address public buyer = makeAddr("buyer");
+    address public secondBuyer = makeAddr("secondBuyer");
+
+    function setUp() public {
+        vault = new MockPositionVault();
+        position = new LoanPosition(address(vault));
+    }
+
+    function test_OnlyVaultMints() public {
+        vm.expectRevert(LoanPosition.OnlyVault.selector);
+        position.mint(buyer, 1);
+
+        vault.mint(position, address(vault), 1);
+        assertEq(position.ownerOf(1), address(vault));
+        assertEq(vault.hookCalls(), 0);
+    }
+
+    function test_VaultTransfersSkipHook() public {
+        vault.mint(position, address(vault), 1);
+        vault.transfer(position, buyer, 1);
+
+        assertEq(position.ownerOf(1), buyer);
+        assertEq(vault.hookCalls(), 0);
+    }
+
+    function test_HolderTransferNotifiesVault() public {
+        vault.mint(position, address(vault), 1);
+        vault.transfer(position, buyer, 1);
+
+        vm.prank(buyer);
+        position.transferFrom(buyer, secondBuyer, 1);
+
+        assertEq(position.ownerOf(1), secondBuyer);
+        assertEq(vault.hookCalls(), 1);
+        assertEq(vault.lastFrom(), buyer);
+        assertEq(vault.lastTo(), secondBuyer);
+        assertEq(vault.lastTokenId(), 1);
+    }
+
+    function test_VaultCanBlockTransfer() public {
+        vault.mint(position, address(vault), 1);
+        vault.transfer(position, buyer, 1);
+        vault.setRejectTransfers(true);
+
+        vm.prank(buyer);
+        vm.expectRevert("position locked");
+        position.transferFrom(buyer, secondBuyer, 1);
+    }
+
+    function test_RejectsZeroVault() public {
+        vm.expectRevert(LoanPosition.InvalidAddress.selector);
+        new LoanPosition(address(0));
+    }
+}